@@ -1,7 +1,12 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Write};
+use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
 
-use anyhow::Result;
-use ini::{EscapePolicy, Ini, LineSeparator, ParseOption, WriteOption};
+use anyhow::{anyhow, Result};
+use ini::{EscapePolicy, Ini, LineSeparator, ParseOption, Properties, SectionSetter, WriteOption};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
 use crate::env::current_exe_dir;
 use crate::game::Branch;
@@ -11,9 +16,13 @@ use crate::servers::{Filter, Mode, Region, SortCriteria, SortKey, TypeFilter};
 pub struct Config {
     pub log_level: LogLevel,
     pub branch: Branch,
+    pub game_path: Option<PathBuf>,
+    pub mods_path: Option<PathBuf>,
+    pub local_mods_paths: Vec<PathBuf>,
     pub use_battleye: BattlEyeUsage,
     pub use_all_cores: bool,
     pub extra_args: String,
+    pub proton_command: Option<String>,
     pub mod_mismatch_checks: ModMismatchChecks,
     pub theme: ThemeChoice,
     pub server_browser: ServerBrowserConfig,
@@ -67,10 +76,17 @@ impl Default for ThemeChoice {
 #[derive(Debug, Default)]
 pub struct ServerBrowserConfig {
     pub filter: Filter,
-    pub sort_criteria: SortCriteria,
+    pub filter_presets: Vec<FilterPreset>,
+    pub sort_criteria: Vec<SortCriteria>,
     pub scroll_lock: bool,
 }
 
+#[derive(Debug, Clone)]
+pub struct FilterPreset {
+    pub name: String,
+    pub filter: Filter,
+}
+
 pub trait ConfigPersister {
     fn load(&self) -> Result<Config>;
     fn save(&self, config: &Config) -> Result<()>;
@@ -155,6 +171,24 @@ impl ConfigPersister for IniConfigPersister {
                 _ => None,
             })
             .unwrap_or_default();
+        let game_path = section
+            .and_then(|section| section.get(KEY_GAME_PATH))
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from);
+        let mods_path = section
+            .and_then(|section| section.get(KEY_MODS_PATH))
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from);
+        let local_mods_paths = section
+            .and_then(|section| section.get(KEY_LOCAL_MODS_PATHS))
+            .map(|value| {
+                std::env::split_paths(value.trim())
+                    .filter(|path| !path.as_os_str().is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
         let use_battleye = section
             .and_then(|section| section.get(KEY_USE_BATTLEYE))
             .and_then(|value| match value.trim().to_ascii_lowercase().as_str() {
@@ -172,6 +206,11 @@ impl ConfigPersister for IniConfigPersister {
             .and_then(|section| section.get(KEY_EXTRA_ARGS))
             .unwrap_or_default()
             .to_string();
+        let proton_command = section
+            .and_then(|section| section.get(KEY_PROTON_COMMAND))
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(ToString::to_string);
         let mod_mismatch_checks = section
             .and_then(|section| section.get(KEY_DISABLE_MOD_MISMATCH_CHECKS))
             .and_then(|value| match value.trim().to_ascii_lowercase().as_str() {
@@ -192,9 +231,13 @@ impl ConfigPersister for IniConfigPersister {
         Ok(Config {
             log_level,
             branch,
+            game_path,
+            mods_path,
+            local_mods_paths,
             use_battleye,
             use_all_cores,
             extra_args,
+            proton_command,
             mod_mismatch_checks,
             theme,
             server_browser: load_server_browser_config(&ini),
@@ -225,11 +268,29 @@ impl ConfigPersister for IniConfigPersister {
                 },
             )
             .set(KEY_USE_ALL_CORES, config.use_all_cores.to_string());
+        let setter = match &config.game_path {
+            Some(path) => setter.set(KEY_GAME_PATH, path.to_string_lossy()),
+            None => setter,
+        };
+        let setter = match &config.mods_path {
+            Some(path) => setter.set(KEY_MODS_PATH, path.to_string_lossy()),
+            None => setter,
+        };
+        let setter = match std::env::join_paths(&config.local_mods_paths) {
+            Ok(paths) if !paths.is_empty() => {
+                setter.set(KEY_LOCAL_MODS_PATHS, paths.to_string_lossy())
+            }
+            _ => setter,
+        };
         let setter = if config.extra_args.is_empty() {
             setter
         } else {
             setter.set(KEY_EXTRA_ARGS, &config.extra_args)
         };
+        let setter = match &config.proton_command {
+            Some(command) => setter.set(KEY_PROTON_COMMAND, command),
+            None => setter,
+        };
         setter
             .set(
                 KEY_DISABLE_MOD_MISMATCH_CHECKS,
@@ -271,6 +332,76 @@ pub fn save_ini<P: AsRef<Path>>(ini: &Ini, path: P) -> Result<()> {
     )?)
 }
 
+// Data that BUGLE keeps in a JSON file of its own next to the config file. Without a path, e.g. when
+// running with a transient config, the data only lasts until the launcher exits.
+pub struct JsonStore<T> {
+    path: Option<PathBuf>,
+    value: T,
+}
+
+impl<T: Default + Serialize + DeserializeOwned> JsonStore<T> {
+    pub fn load(path: Option<PathBuf>) -> Result<Self> {
+        let value = match &path {
+            Some(path) => load_json(path)?.unwrap_or_default(),
+            None => T::default(),
+        };
+        Ok(Self { path, value })
+    }
+
+    pub fn transient() -> Self {
+        Self {
+            path: None,
+            value: T::default(),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(path) = &self.path {
+            save_json(&self.value, path)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> Deref for JsonStore<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for JsonStore<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+// Returns None if the file does not exist.
+pub fn load_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    match File::open(path) {
+        Ok(file) => Ok(Some(serde_json::from_reader(BufReader::new(file))?)),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+// Writes the JSON into a temporary file next to the destination, and only replaces the destination
+// once the whole file has been written, so that a failed save never leaves a truncated file behind.
+pub fn save_json<T: Serialize + ?Sized>(value: &T, path: &Path) -> Result<()> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| anyhow!("Invalid file path: {}", path.display()))?;
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    {
+        let mut writer = BufWriter::new(file.as_file_mut());
+        serde_json::to_writer_pretty(&mut writer, value)?;
+        writer.flush()?;
+    }
+    file.as_file().sync_all()?;
+    file.persist(path)?;
+    Ok(())
+}
+
 fn load_text_lossy<P: AsRef<Path>>(path: P) -> std::io::Result<String> {
     let bytes = std::fs::read(path.as_ref())?;
 
@@ -287,6 +418,39 @@ fn load_server_browser_config(ini: &Ini) -> ServerBrowserConfig {
     use std::str::FromStr;
 
     let section = ini.section(Some(SECTION_SERVER_BROWSER));
+    let filter = load_filter(section);
+    let filter_presets = ini
+        .iter()
+        .filter_map(|(name, section)| {
+            let name = name?.strip_prefix(SECTION_FILTER_PRESET_PREFIX)?;
+            Some(FilterPreset {
+                name: name.to_string(),
+                filter: load_filter(Some(section)),
+            })
+        })
+        .collect();
+    let mut sort_criteria: Vec<SortCriteria> = section
+        .and_then(|section| section.get(KEY_SORT_CRITERIA))
+        .map(|s| s.split(',').filter_map(sort_criteria_from_str).collect())
+        .unwrap_or_default();
+    if sort_criteria.is_empty() {
+        sort_criteria.push(SortCriteria::default());
+    }
+    let scroll_lock = section
+        .and_then(|section| section.get(KEY_SCROLL_LOCK))
+        .and_then(|s| bool::from_str(&s.to_ascii_lowercase()).ok())
+        .unwrap_or(true);
+    ServerBrowserConfig {
+        filter,
+        filter_presets,
+        sort_criteria,
+        scroll_lock,
+    }
+}
+
+fn load_filter(section: Option<&Properties>) -> Filter {
+    use std::str::FromStr;
+
     let name = section
         .and_then(|section| section.get(KEY_NAME))
         .unwrap_or_default()
@@ -305,6 +469,10 @@ fn load_server_browser_config(ini: &Ini) -> ServerBrowserConfig {
     let region = section
         .and_then(|section| section.get(KEY_REGION))
         .and_then(|s| Region::from_str(s).ok());
+    let use_inferred_region = section
+        .and_then(|section| section.get(KEY_USE_INFERRED_REGION))
+        .and_then(|s| bool::from_str(&s.to_ascii_lowercase()).ok())
+        .unwrap_or_default();
     let battleye_required = section
         .and_then(|section| section.get(KEY_BATTLEYE_REQUIRED))
         .and_then(|s| bool::from_str(&s.to_ascii_lowercase()).ok());
@@ -319,90 +487,114 @@ fn load_server_browser_config(ini: &Ini) -> ServerBrowserConfig {
     let mods = section
         .and_then(|section| section.get(KEY_MODS))
         .and_then(|s| bool::from_str(&s.to_ascii_lowercase()).ok());
-    let sort_criteria = section
-        .and_then(|section| section.get(KEY_SORT_CRITERIA))
-        .map(|s| if s.starts_with('-') { (false, &s[1..]) } else { (true, s) })
-        .and_then(|(ascending, s)| {
-            SortKey::from_str(s)
-                .ok()
-                .map(|key| SortCriteria { key, ascending })
-        })
-        .unwrap_or_default();
-    let scroll_lock = section
-        .and_then(|section| section.get(KEY_SCROLL_LOCK))
-        .and_then(|s| bool::from_str(&s.to_ascii_lowercase()).ok())
-        .unwrap_or(true);
-    ServerBrowserConfig {
-        filter: Filter {
-            name,
-            map,
-            type_filter,
-            mode,
-            region,
-            battleye_required,
-            include_invalid,
-            exclude_password_protected: !include_password_protected,
-            mods,
-        },
-        sort_criteria,
-        scroll_lock,
+    let expression = section
+        .and_then(|section| section.get(KEY_EXPRESSION))
+        .unwrap_or_default()
+        .to_string();
+    Filter {
+        name,
+        map,
+        type_filter,
+        mode,
+        region,
+        use_inferred_region,
+        battleye_required,
+        include_invalid,
+        exclude_password_protected: !include_password_protected,
+        mods,
+        expression,
     }
 }
 
 fn save_server_browser_config(ini: &mut Ini, config: &ServerBrowserConfig) {
-    let setter = &mut ini.with_section(Some(SECTION_SERVER_BROWSER));
-    let setter = if config.filter.name.is_empty() {
-        setter
-    } else {
-        setter.set(KEY_NAME, &config.filter.name)
-    };
-    let setter =
-        if config.filter.map.is_empty() { setter } else { setter.set(KEY_MAP, &config.filter.map) };
-    let setter = setter.set(KEY_TYPE_FILTER, config.filter.type_filter.as_ref());
-    let setter = match config.filter.mode {
+    save_filter(
+        &mut ini.with_section(Some(SECTION_SERVER_BROWSER)),
+        &config.filter,
+    )
+    .set(
+        KEY_SORT_CRITERIA,
+        sort_criteria_to_string(&config.sort_criteria),
+    )
+    .set(KEY_SCROLL_LOCK, config.scroll_lock.to_string());
+    for preset in config.filter_presets.iter() {
+        let section = format!("{}{}", SECTION_FILTER_PRESET_PREFIX, preset.name);
+        save_filter(&mut ini.with_section(Some(section)), &preset.filter);
+    }
+}
+
+fn save_filter<'a, 'b>(
+    setter: &'b mut SectionSetter<'a>,
+    filter: &Filter,
+) -> &'b mut SectionSetter<'a> {
+    let setter = if filter.name.is_empty() { setter } else { setter.set(KEY_NAME, &filter.name) };
+    let setter = if filter.map.is_empty() { setter } else { setter.set(KEY_MAP, &filter.map) };
+    let setter = setter.set(KEY_TYPE_FILTER, filter.type_filter.as_ref());
+    let setter = match filter.mode {
         Some(mode) => setter.set(KEY_MODE, mode.as_ref()),
         None => setter,
     };
-    let setter = match config.filter.region {
+    let setter = match filter.region {
         Some(region) => setter.set(KEY_REGION, region.as_ref()),
         None => setter,
     };
-    let setter = match config.filter.battleye_required {
+    let setter = match filter.battleye_required {
         Some(required) => setter.set(KEY_BATTLEYE_REQUIRED, required.to_string()),
         None => setter,
     };
-    let setter = match config.filter.mods {
+    let setter = match filter.mods {
         Some(mods) => setter.set(KEY_MODS, mods.to_string()),
         None => setter,
     };
+    let setter = if filter.expression.is_empty() {
+        setter
+    } else {
+        setter.set(KEY_EXPRESSION, &filter.expression)
+    };
     setter
         .set(
-            KEY_INCLUDE_INVALID,
-            config.filter.include_invalid.to_string(),
+            KEY_USE_INFERRED_REGION,
+            filter.use_inferred_region.to_string(),
         )
+        .set(KEY_INCLUDE_INVALID, filter.include_invalid.to_string())
         .set(
             KEY_INCLUDE_PASSWORD_PROTECTED,
-            (!config.filter.exclude_password_protected).to_string(),
+            (!filter.exclude_password_protected).to_string(),
         )
-        .set(
-            KEY_SORT_CRITERIA,
-            sort_criteria_to_string(&config.sort_criteria),
-        )
-        .set(KEY_SCROLL_LOCK, config.scroll_lock.to_string());
 }
 
-fn sort_criteria_to_string(criteria: &SortCriteria) -> String {
-    let prefix = if criteria.ascending { "" } else { "-" };
-    format!("{}{}", prefix, criteria.key.as_ref())
+fn sort_criteria_from_str(s: &str) -> Option<SortCriteria> {
+    use std::str::FromStr;
+
+    let s = s.trim();
+    let (ascending, s) = if s.starts_with('-') { (false, &s[1..]) } else { (true, s) };
+    SortKey::from_str(s)
+        .ok()
+        .map(|key| SortCriteria { key, ascending })
+}
+
+fn sort_criteria_to_string(criteria: &[SortCriteria]) -> String {
+    criteria
+        .iter()
+        .map(|criteria| {
+            let prefix = if criteria.ascending { "" } else { "-" };
+            format!("{}{}", prefix, criteria.key.as_ref())
+        })
+        .collect::<Vec<_>>()
+        .join(",")
 }
 
 const SECTION_SERVER_BROWSER: &str = "ServerBrowser";
+const SECTION_FILTER_PRESET_PREFIX: &str = "FilterPreset:";
 
 const KEY_LOG_LEVEL: &str = "LogLevel";
 const KEY_BRANCH: &str = "Branch";
+const KEY_GAME_PATH: &str = "GamePath";
+const KEY_MODS_PATH: &str = "ModsPath";
+const KEY_LOCAL_MODS_PATHS: &str = "LocalModsPaths";
 const KEY_USE_BATTLEYE: &str = "UseBattlEye";
 const KEY_USE_ALL_CORES: &str = "UseAllCores";
 const KEY_EXTRA_ARGS: &str = "ExtraArgs";
+const KEY_PROTON_COMMAND: &str = "ProtonCommand";
 const KEY_DISABLE_MOD_MISMATCH_CHECKS: &str = "DisableModMismatchChecks";
 const KEY_THEME: &str = "Theme";
 const KEY_NAME: &str = "Name";
@@ -410,10 +602,12 @@ const KEY_MAP: &str = "Map";
 const KEY_TYPE_FILTER: &str = "Type";
 const KEY_MODE: &str = "Mode";
 const KEY_REGION: &str = "Region";
+const KEY_USE_INFERRED_REGION: &str = "UseInferredRegion";
 const KEY_BATTLEYE_REQUIRED: &str = "BattlEyeRequired";
 const KEY_INCLUDE_INVALID: &str = "IncludeInvalid";
 const KEY_INCLUDE_PASSWORD_PROTECTED: &str = "IncludePasswordProtected";
 const KEY_MODS: &str = "Mods";
+const KEY_EXPRESSION: &str = "Expression";
 const KEY_SORT_CRITERIA: &str = "SortBy";
 const KEY_SCROLL_LOCK: &str = "ScrollLock";
 
@@ -429,3 +623,33 @@ const MOD_MISMATCH_CHECKS_DISABLED: &str = "disabled";
 
 const THEME_LIGHT: &str = "light";
 const THEME_DARK: &str = "dark";
+
+#[cfg(test)]
+mod tests {
+    use super::JsonStore;
+
+    #[test]
+    fn json_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+
+        let mut store: JsonStore<Vec<String>> = JsonStore::load(Some(path.clone())).unwrap();
+        assert!(store.is_empty());
+        store.push("first".to_string());
+        store.save().unwrap();
+
+        let store: JsonStore<Vec<String>> = JsonStore::load(Some(path.clone())).unwrap();
+        assert_eq!(*store, ["first"]);
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(JsonStore::<Vec<String>>::load(Some(path)).is_err());
+    }
+
+    #[test]
+    fn transient_json_store_is_not_saved() {
+        let mut store: JsonStore<Vec<String>> = JsonStore::transient();
+        store.push("entry".to_string());
+        store.save().unwrap();
+        assert_eq!(*store, ["entry"]);
+    }
+}
@@ -1,12 +1,11 @@
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::BufReader;
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::str::FromStr;
 use std::sync::{Arc, Mutex, MutexGuard};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use ini::Properties;
 use lazy_static::lazy_static;
 use regex::Regex;
@@ -15,34 +14,45 @@ use slog::{debug, info, warn, Logger};
 mod engine;
 mod launch;
 mod mod_info;
+mod mod_pins;
+mod mod_tags;
 pub mod platform;
 
 use crate::auth::{CachedUser, CachedUsers};
 use crate::config;
 use crate::game::engine::version::get_game_version;
-use crate::servers::{FavoriteServer, FavoriteServers, Server};
+use crate::game::platform::GameLocation;
+use crate::net::parse_socket_addr;
+use crate::servers::{FavoriteServer, Server};
 
 pub use self::engine::db::{create_empty_db, list_mod_controllers, GameDB};
 use self::engine::map::MapExtractor;
 pub use self::engine::map::{MapInfo, Maps};
 pub use self::launch::Launch;
-pub use self::mod_info::{ModInfo, ModRef, Mods};
+#[cfg(test)]
+pub use self::mod_info::{test_mod, test_mods};
+pub use self::mod_info::{ModInfo, ModRef, ModSource, Mods};
+pub use self::mod_pins::{ModPins, PinnedMod};
+pub use self::mod_tags::{parse_tags, ModTags};
 
 pub struct Game {
     logger: Logger,
     root: PathBuf,
     branch: Branch,
     needs_update: bool,
-    version: (u32, u16),
+    version: Option<(u32, u16)>,
     save_path: PathBuf,
     game_ini_path: PathBuf,
     mod_list_path: PathBuf,
+    mods_path: Option<PathBuf>,
     installed_mods: Arc<Mods>,
     maps: Arc<Maps>,
     last_session: Mutex<Option<Session>>,
+    launch_method: LaunchMethod,
+    proton_prefix: Option<ProtonPrefix>,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Branch {
     Main,
     PublicBeta,
@@ -54,6 +64,19 @@ impl Default for Branch {
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+pub enum LaunchMethod {
+    Direct,
+    Steam { app_id: u32 },
+}
+
+// The Wine prefix Proton runs the game in, i.e. steamapps/compatdata/<app ID>.
+#[derive(Debug, Clone)]
+pub struct ProtonPrefix {
+    pub compat_data_path: PathBuf,
+    pub steam_path: Option<PathBuf>,
+}
+
 #[derive(Debug)]
 pub enum Session {
     SinglePlayer(MapRef),
@@ -78,21 +101,45 @@ pub struct LaunchOptions {
     pub enable_battleye: bool,
     pub use_all_cores: bool,
     pub extra_args: String,
+    pub proton_command: Option<String>,
 }
 
 impl Game {
     fn new(
         logger: Logger,
-        game_path: PathBuf,
-        branch: Branch,
-        needs_update: bool,
+        location: GameLocation,
         mut installed_mods: Vec<ModInfo>,
     ) -> Result<Self> {
-        let save_path = game_path.join("ConanSandbox/Saved");
+        let GameLocation {
+            game_path,
+            mods_path,
+            branch,
+            needs_update,
+            launch_method,
+            proton_prefix,
+            ..
+        } = location;
+
+        // Under Proton, the game may keep its settings and saves in the user profile of the
+        // prefix rather than in the installation folder.
+        let save_path = proton_prefix
+            .as_ref()
+            .map(|prefix| prefix.drive_c().join(PROTON_SAVE_PATH))
+            .filter(|path| path.is_dir())
+            .unwrap_or_else(|| game_path.join("ConanSandbox/Saved"));
+        debug!(logger, "Determined the save path"; "path" => save_path.display());
         let config_path = save_path.join("Config/WindowsNoEditor");
 
+        // Without the version, servers cannot be told apart by their build, but everything else
+        // still works.
         debug!(logger, "Querying game version");
-        let version = get_game_version(&game_path)?;
+        let version = match get_game_version(&logger, &game_path) {
+            Ok(version) => Some(version),
+            Err(err) => {
+                warn!(logger, "Cannot determine the game version"; "error" => %err);
+                None
+            }
+        };
 
         let mod_list_path = game_path.join("ConanSandbox/Mods/modlist.txt");
         installed_mods.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
@@ -107,7 +154,8 @@ impl Game {
         )?;
 
         debug!(logger, "Enumerating mod-provided maps");
-        for mod_info in installed_mods.iter() {
+        for mod_info in installed_mods.iter_mut() {
+            let map_count = maps.len();
             if let Err(err) = map_extractor.extract_mod_maps(&*mod_info.pak_path, &mut maps) {
                 warn!(
                     logger,
@@ -116,6 +164,7 @@ impl Game {
                     "error" => %err,
                 );
             }
+            mod_info.provides_maps = maps.len() > map_count;
         }
 
         let game_ini_path = config_path.join("Game.ini");
@@ -138,7 +187,7 @@ impl Game {
             let online_section = game_ini.section(Some(SECTION_SAVED_SERVERS));
             let server_addr = online_section
                 .and_then(|section| section.get(KEY_LAST_CONNECTED))
-                .and_then(|val| SocketAddr::from_str(val).ok());
+                .and_then(parse_socket_addr);
 
             if is_local {
                 local_map
@@ -181,9 +230,12 @@ impl Game {
             save_path,
             game_ini_path,
             mod_list_path,
+            mods_path,
             installed_mods: Arc::new(Mods::new(installed_mods)),
             maps: Arc::new(maps),
             last_session: Mutex::new(last_session),
+            launch_method,
+            proton_prefix,
         })
     }
 
@@ -195,13 +247,15 @@ impl Game {
         self.needs_update
     }
 
-    pub fn build_id(&self) -> u32 {
-        let revision_bits = (self.version.0 & 0x3ffff) << 13;
-        let snapshot_bits = (self.version.1 & 0x1fff) as u32;
-        revision_bits + snapshot_bits
+    pub fn build_id(&self) -> Option<u32> {
+        self.version.map(|(revision, snapshot)| {
+            let revision_bits = (revision & 0x3ffff) << 13;
+            let snapshot_bits = (snapshot & 0x1fff) as u32;
+            revision_bits + snapshot_bits
+        })
     }
 
-    pub fn version(&self) -> (u32, u16) {
+    pub fn version(&self) -> Option<(u32, u16)> {
         self.version
     }
 
@@ -221,6 +275,10 @@ impl Game {
         &self.installed_mods
     }
 
+    pub fn mods_path(&self) -> Option<&Path> {
+        self.mods_path.as_deref()
+    }
+
     pub fn maps(&self) -> &Arc<Maps> {
         &self.maps
     }
@@ -265,18 +323,16 @@ impl Game {
         config::save_ini(&game_ini, &self.game_ini_path)
     }
 
-    pub fn load_favorites(&self) -> Result<FavoriteServers> {
+    pub fn load_favorite_list(&self) -> Result<Vec<FavoriteServer>> {
         debug!(self.logger, "Loading favorite servers");
 
         let game_ini = config::load_ini(&self.game_ini_path)?;
-        let mut favorites = FavoriteServers::new();
+        let mut favorites = Vec::new();
 
         if let Some(section) = game_ini.section(Some(SECTION_FAVORITE_SERVERS)) {
             for value in section.get_all(KEY_SERVERS_LIST) {
                 match FavoriteServer::parse(value) {
-                    Ok(favorite) => {
-                        favorites.insert(favorite);
-                    }
+                    Ok(favorite) => favorites.push(favorite),
                     Err(err) => warn!(
                         self.logger,
                         "Error parsing favorite";
@@ -320,17 +376,10 @@ impl Game {
         debug!(self.logger, "Loading modlist"; "path" => path.display());
 
         let file = File::open(path)?;
-        let mut mod_list = Vec::new();
-        for line in BufReader::new(file).lines() {
-            if let Ok(mod_path) = line {
-                if !mod_path.starts_with('#') {
-                    let mod_path: PathBuf = mod_path.trim().into();
-                    mod_list.push(self.installed_mods.by_pak_path(&mod_path));
-                }
-            }
-        }
-
-        Ok(mod_list)
+        let local_mods_path = self.root.join("ConanSandbox/Mods");
+        Ok(self
+            .installed_mods
+            .read_mod_list(BufReader::new(file), &local_mods_path))
     }
 
     pub fn save_mod_list<'m>(&self, mod_list: impl IntoIterator<Item = &'m ModRef>) -> Result<()> {
@@ -342,21 +391,10 @@ impl Game {
         path: &Path,
         mod_list: impl IntoIterator<Item = &'m ModRef>,
     ) -> Result<()> {
-        use std::io::Write;
-
         debug!(self.logger, "Saving modlist"; "path" => path.display());
 
-        let mut file = File::create(path)?;
-        for mod_ref in mod_list {
-            let pak_path = match mod_ref {
-                ModRef::Installed(_) => &self.installed_mods.get(mod_ref).unwrap().pak_path,
-                ModRef::UnknownPakPath(path) => path,
-                ModRef::UnknownFolder(_) => continue,
-            };
-            writeln!(&mut file, "{}", pak_path.display())?;
-        }
-
-        Ok(())
+        let file = File::create(path)?;
+        self.installed_mods.write_mod_list(file, mod_list)
     }
 
     pub fn load_saved_games(&self) -> Result<Vec<GameDB>> {
@@ -403,7 +441,17 @@ impl Game {
             "ConanSandbox.exe"
         });
 
-        let mut cmd = Command::new(exe_path);
+        // Steam hands the launch over to the running client and exits, so its process ending is
+        // not a sign that the game failed to start.
+        let (mut cmd, detached) = match (&options.proton_command, self.launch_method) {
+            (Some(proton_command), _) => (self.proton_command(proton_command, &exe_path)?, false),
+            (None, LaunchMethod::Steam { app_id }) => {
+                let mut cmd = Command::new("steam");
+                cmd.arg("-applaunch").arg(app_id.to_string());
+                (cmd, true)
+            }
+            (None, LaunchMethod::Direct) => (Command::new(exe_path), false),
+        };
         cmd.args(args);
         if options.enable_battleye {
             cmd.arg("-BattlEye");
@@ -424,7 +472,23 @@ impl Game {
         };
 
         info!(self.logger, "Launching Conan Exiles"; "command" => format!("{:?}", cmd));
-        Launch::new(&self.logger, cmd)
+        Launch::new(&self.logger, cmd, detached)
+    }
+
+    fn proton_command(&self, proton_command: &str, exe_path: &Path) -> Result<Command> {
+        let args = shlex::split(proton_command)
+            .filter(|args| !args.is_empty())
+            .ok_or_else(|| anyhow!("Malformed Proton command: {}", proton_command))?;
+
+        let mut cmd = Command::new(&args[0]);
+        cmd.args(&args[1..]).arg(exe_path);
+        if let Some(prefix) = &self.proton_prefix {
+            cmd.env("STEAM_COMPAT_DATA_PATH", &prefix.compat_data_path);
+            if let Some(steam_path) = &prefix.steam_path {
+                cmd.env("STEAM_COMPAT_CLIENT_INSTALL_PATH", steam_path);
+            }
+        }
+        Ok(cmd)
     }
 
     pub fn continue_session(&self, options: LaunchOptions) -> Result<Launch> {
@@ -464,11 +528,19 @@ impl Game {
     }
 }
 
+impl ProtonPrefix {
+    pub fn drive_c(&self) -> PathBuf {
+        self.compat_data_path.join("pfx/drive_c")
+    }
+}
+
 lazy_static! {
     static ref BUILD_ID_REGEX: Regex =
         Regex::new(r"^OnlineSubsystem:BuildIdOverride:0\s*=\s*(\d+)$").unwrap();
 }
 
+const PROTON_SAVE_PATH: &str = "users/steamuser/AppData/Local/ConanSandbox/Saved";
+
 const SECTION_FAVORITE_SERVERS: &str = "FavoriteServers";
 const SECTION_FUNCOM_LIVE_SERVICES: &str = "FuncomLiveServices";
 const SECTION_SAVED_SERVERS: &str = "SavedServers";
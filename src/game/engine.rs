@@ -7,6 +7,7 @@ pub(super) mod db;
 pub(super) mod map;
 mod name;
 pub(super) mod pak;
+mod pe;
 mod property;
 mod uasset;
 pub(super) mod version;
@@ -54,6 +54,10 @@ impl Maps {
         }
     }
 
+    pub fn len(&self) -> usize {
+        self.maps.len()
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &MapEntry> {
         self.maps.iter()
     }
@@ -245,3 +245,75 @@ fn read_index(file: &mut File, offset: u64, size: u64) -> Result<HashSet<Archive
 }
 
 const INLINE_HEADER_SIZE: u64 = 57;
+
+// Writes a minimal uncompressed archive holding the given entries, for tests that need paks.
+#[cfg(test)]
+pub(crate) fn write_test_pak(path: &Path, entries: &[(&str, &[u8])]) -> Result<()> {
+    fn write_string(buf: &mut Vec<u8>, value: &str) {
+        buf.extend_from_slice(&(value.len() as i32 + 1).to_le_bytes());
+        buf.extend_from_slice(value.as_bytes());
+        buf.push(0);
+    }
+
+    let mut data = Vec::new();
+    let mut index = Vec::new();
+    write_string(&mut index, "../../../");
+    index.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+    for (entry_path, contents) in entries {
+        let offset = data.len() as u64;
+        data.resize(data.len() + INLINE_HEADER_SIZE as usize, 0);
+        data.extend_from_slice(contents);
+
+        write_string(&mut index, entry_path);
+        index.extend_from_slice(&offset.to_le_bytes());
+        index.extend_from_slice(&(contents.len() as u64).to_le_bytes());
+        index.extend_from_slice(&(contents.len() as u64).to_le_bytes());
+        index.extend_from_slice(&0u32.to_le_bytes());
+        index.extend_from_slice(&[0; 20]);
+        index.push(0);
+        index.extend_from_slice(&0u32.to_le_bytes());
+        index.extend_from_slice(&[0; 4]);
+    }
+
+    let index_offset = data.len() as u64;
+    let index_size = index.len() as u64;
+    data.extend_from_slice(&index);
+    data.extend_from_slice(&0x5a6f12e1u32.to_le_bytes());
+    data.extend_from_slice(&4u32.to_le_bytes());
+    data.extend_from_slice(&index_offset.to_le_bytes());
+    data.extend_from_slice(&index_size.to_le_bytes());
+    data.extend_from_slice(&[0; 20]);
+
+    std::fs::write(path, data)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::{write_test_pak, Archive};
+
+    #[test]
+    fn reads_uncompressed_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.pak");
+        write_test_pak(
+            &path,
+            &[("first.txt", b"first"), ("second.txt", b"second entry")],
+        )
+        .unwrap();
+
+        let archive = Archive::new(&path).unwrap();
+        assert_eq!(archive.iter().count(), 2);
+        assert!(archive.entry("third.txt").is_none());
+
+        let mut contents = String::new();
+        archive
+            .open_entry("second.txt")
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(contents, "second entry");
+    }
+}
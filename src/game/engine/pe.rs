@@ -0,0 +1,388 @@
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Result};
+
+// A minimal reader for the version resource of PE files (EXEs and DLLs). It reads the file as
+// plain data, so that it works on any host and never runs any of the game's code.
+// Returns the key/value pairs of the string tables of the version resource.
+pub fn read_version_strings(path: &Path) -> Result<Vec<(String, String)>> {
+    let data = std::fs::read(path)?;
+    let resource = find_version_resource(&data)?;
+    parse_version_strings(resource)
+}
+
+fn find_version_resource(data: &[u8]) -> Result<&[u8]> {
+    if data.get(..2) != Some(&b"MZ"[..]) {
+        bail!(ERR_NOT_PE);
+    }
+    let pe_offset = read_u32(data, 0x3c)? as usize;
+    if data.get(pe_offset..pe_offset + 4) != Some(&b"PE\0\0"[..]) {
+        bail!(ERR_NOT_PE);
+    }
+
+    let coff = pe_offset + 4;
+    let num_sections = read_u16(data, coff + 2)? as usize;
+    let opt_header_size = read_u16(data, coff + 16)? as usize;
+    let opt_header = coff + 20;
+    let (num_dirs_offset, dirs_offset) = match read_u16(data, opt_header)? {
+        PE32_MAGIC => (92, 96),
+        PE32_PLUS_MAGIC => (108, 112),
+        magic => bail!("Unsupported PE optional header magic: {:#x}", magic),
+    };
+    if read_u32(data, opt_header + num_dirs_offset)? as usize <= RESOURCE_DIR_IDX {
+        bail!(ERR_NO_VERSION);
+    }
+    let resource_rva = read_u32(data, opt_header + dirs_offset + RESOURCE_DIR_IDX * 8)?;
+    if resource_rva == 0 {
+        bail!(ERR_NO_VERSION);
+    }
+
+    let sections = (0..num_sections)
+        .map(|idx| {
+            let header = opt_header + opt_header_size + idx * 40;
+            Ok(Section {
+                virtual_address: read_u32(data, header + 12)?,
+                raw_size: read_u32(data, header + 16)?,
+                raw_offset: read_u32(data, header + 20)?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let rva_to_offset = |rva: u32| -> Result<usize> {
+        sections
+            .iter()
+            .find(|section| {
+                rva >= section.virtual_address && rva - section.virtual_address < section.raw_size
+            })
+            .map(|section| (rva - section.virtual_address + section.raw_offset) as usize)
+            .ok_or_else(|| anyhow!("Address {:#x} is outside of all sections", rva))
+    };
+
+    // The resource tree has three levels: type, name and language. Any name and language will do.
+    let root = rva_to_offset(resource_rva)?;
+    let types = resource_entry(data, root, root, Some(RT_VERSION))?;
+    let names = resource_entry(data, root, types, None)?;
+    let entry = resource_entry(data, root, names, None)?;
+
+    let data_rva = read_u32(data, entry)?;
+    let data_size = read_u32(data, entry + 4)? as usize;
+    let data_offset = rva_to_offset(data_rva)?;
+    data.get(data_offset..data_offset + data_size)
+        .ok_or_else(|| anyhow!(ERR_MALFORMED))
+}
+
+// Returns the offset of the subdirectory or data entry of the entry with the given ID, or of the
+// first entry if no ID is given.
+fn resource_entry(data: &[u8], root: usize, dir: usize, id: Option<u32>) -> Result<usize> {
+    let num_named = read_u16(data, dir + 12)? as usize;
+    let num_ids = read_u16(data, dir + 14)? as usize;
+    for idx in 0..(num_named + num_ids) {
+        let entry = dir + 16 + idx * 8;
+        let name = read_u32(data, entry)?;
+        if id.map(|id| id == name).unwrap_or(true) {
+            let offset = read_u32(data, entry + 4)?;
+            return Ok(root + (offset & 0x7fff_ffff) as usize);
+        }
+    }
+    bail!(ERR_NO_VERSION)
+}
+
+fn parse_version_strings(data: &[u8]) -> Result<Vec<(String, String)>> {
+    let root = Block::parse(data)?;
+    if root.key != "VS_VERSION_INFO" {
+        bail!(ERR_MALFORMED);
+    }
+
+    let mut strings = Vec::new();
+    for file_info in root.children()? {
+        if file_info.key != "StringFileInfo" {
+            continue;
+        }
+        for table in file_info.children()? {
+            for string in table.children()? {
+                let value = decode_utf16(string.value);
+                strings.push((string.key, value.trim_end_matches('\0').to_string()));
+            }
+        }
+    }
+
+    Ok(strings)
+}
+
+struct Section {
+    virtual_address: u32,
+    raw_size: u32,
+    raw_offset: u32,
+}
+
+// A node of the VS_VERSIONINFO tree: a length, the length of the value, a type, a null-terminated
+// UTF-16 key, then the value and the children, each aligned to 4 bytes.
+struct Block<'d> {
+    key: String,
+    value: &'d [u8],
+    children: &'d [u8],
+}
+
+impl<'d> Block<'d> {
+    fn parse(data: &'d [u8]) -> Result<Self> {
+        let length = read_u16(data, 0)? as usize;
+        let value_length = read_u16(data, 2)? as usize;
+        let is_text = read_u16(data, 4)? == 1;
+        let data = data.get(..length).ok_or_else(|| anyhow!(ERR_MALFORMED))?;
+
+        let key_end = (6..data.len())
+            .step_by(2)
+            .find(|&pos| read_u16(data, pos).ok() == Some(0))
+            .ok_or_else(|| anyhow!(ERR_MALFORMED))?;
+        let key = decode_utf16(&data[6..key_end]);
+
+        let value_start = align4(key_end + 2).min(data.len());
+        let value_size = if is_text { value_length * 2 } else { value_length };
+        let value_end = (value_start + value_size).min(data.len());
+        let children_start = align4(value_end).min(data.len());
+
+        Ok(Self {
+            key,
+            value: &data[value_start..value_end],
+            children: &data[children_start..],
+        })
+    }
+
+    fn children(&self) -> Result<Vec<Block<'d>>> {
+        let mut children = Vec::new();
+        let mut rest = self.children;
+        while rest.len() >= 6 {
+            let length = read_u16(rest, 0)? as usize;
+            if length == 0 {
+                break;
+            }
+            children.push(Block::parse(rest)?);
+            rest = &rest[align4(length).min(rest.len())..];
+        }
+        Ok(children)
+    }
+}
+
+fn align4(offset: usize) -> usize {
+    (offset + 3) & !3
+}
+
+fn decode_utf16(data: &[u8]) -> String {
+    let units: Vec<u16> = data
+        .chunks_exact(2)
+        .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| anyhow!(ERR_MALFORMED))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or_else(|| anyhow!(ERR_MALFORMED))
+}
+
+const PE32_MAGIC: u16 = 0x10b;
+const PE32_PLUS_MAGIC: u16 = 0x20b;
+const RESOURCE_DIR_IDX: usize = 2;
+const RT_VERSION: u32 = 16;
+
+const ERR_NOT_PE: &str = "Not a PE file";
+const ERR_NO_VERSION: &str = "The PE file has no version resource";
+const ERR_MALFORMED: &str = "Malformed PE file";
+
+// Builds a version info block, for tests that need version resources.
+#[cfg(test)]
+fn version_block(key: &str, value: &[u8], is_text: bool, children: &[Vec<u8>]) -> Vec<u8> {
+    let mut data = vec![0u8; 6];
+    for unit in key.encode_utf16().chain(Some(0)) {
+        data.extend_from_slice(&unit.to_le_bytes());
+    }
+    data.resize((data.len() + 3) & !3, 0);
+    data.extend_from_slice(value);
+    for child in children {
+        data.resize((data.len() + 3) & !3, 0);
+        data.extend_from_slice(child);
+    }
+    let value_length = if is_text { value.len() / 2 } else { value.len() };
+    let length = data.len() as u16;
+    data[0..2].copy_from_slice(&length.to_le_bytes());
+    data[2..4].copy_from_slice(&(value_length as u16).to_le_bytes());
+    data[4..6].copy_from_slice(&(is_text as u16).to_le_bytes());
+    data
+}
+
+#[cfg(test)]
+fn utf16_text(value: &str) -> Vec<u8> {
+    value
+        .encode_utf16()
+        .chain(Some(0))
+        .flat_map(|unit| unit.to_le_bytes())
+        .collect()
+}
+
+// Builds a PE file with a single resource section, whose version resource holds the given strings.
+// The resource tree also has an icon entry that leads nowhere, which has to be skipped.
+#[cfg(test)]
+pub(super) fn test_pe_file(pe32_plus: bool, strings: &[(&str, &str)]) -> Vec<u8> {
+    fn put_u16(data: &mut [u8], offset: usize, value: u16) {
+        data[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+    }
+    fn put_u32(data: &mut [u8], offset: usize, value: u32) {
+        data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+    fn resource_dir(rsrc: &mut Vec<u8>, entries: &[(u32, u32)]) {
+        rsrc.extend_from_slice(&[0; 14]);
+        rsrc.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        for (id, offset) in entries {
+            rsrc.extend_from_slice(&id.to_le_bytes());
+            rsrc.extend_from_slice(&offset.to_le_bytes());
+        }
+    }
+
+    const SECTION_RVA: u32 = 0x1000;
+    const SECTION_OFFSET: usize = 0x200;
+    const SUBDIR: u32 = 0x8000_0000;
+
+    let mut fixed = vec![0u8; 52];
+    fixed[0..4].copy_from_slice(&0xfeef04bdu32.to_le_bytes());
+    let strings: Vec<Vec<u8>> = strings
+        .iter()
+        .map(|(key, value)| version_block(key, &utf16_text(value), true, &[]))
+        .collect();
+    let table = version_block("040904b0", &[], true, &strings);
+    let version = version_block(
+        "VS_VERSION_INFO",
+        &fixed,
+        false,
+        &[version_block("StringFileInfo", &[], true, &[table])],
+    );
+
+    // Type directory at 0, name directory at 32, language directory at 56, data entry at 80 and
+    // the version info itself at 96.
+    let mut rsrc = Vec::new();
+    resource_dir(
+        &mut rsrc,
+        &[(3, SUBDIR | 0xfff0), (RT_VERSION, SUBDIR | 32)],
+    );
+    resource_dir(&mut rsrc, &[(1, SUBDIR | 56)]);
+    resource_dir(&mut rsrc, &[(0x409, 80)]);
+    rsrc.extend_from_slice(&(SECTION_RVA + 96).to_le_bytes());
+    rsrc.extend_from_slice(&(version.len() as u32).to_le_bytes());
+    rsrc.extend_from_slice(&[0; 8]);
+    rsrc.extend_from_slice(&version);
+
+    let mut data = vec![0u8; SECTION_OFFSET];
+    data[0..2].copy_from_slice(b"MZ");
+    put_u32(&mut data, 0x3c, 0x40);
+    data[0x40..0x44].copy_from_slice(b"PE\0\0");
+    let coff = 0x44;
+    let opt_header = coff + 20;
+    let (magic, num_dirs_offset, dirs_offset) =
+        if pe32_plus { (PE32_PLUS_MAGIC, 108, 112) } else { (PE32_MAGIC, 92, 96) };
+    let opt_header_size = dirs_offset + 16 * 8;
+    put_u16(&mut data, coff + 2, 1);
+    put_u16(&mut data, coff + 16, opt_header_size as u16);
+    put_u16(&mut data, opt_header, magic);
+    put_u32(&mut data, opt_header + num_dirs_offset, 16);
+    let resource_dir_entry = opt_header + dirs_offset + RESOURCE_DIR_IDX * 8;
+    put_u32(&mut data, resource_dir_entry, SECTION_RVA);
+    put_u32(&mut data, resource_dir_entry + 4, rsrc.len() as u32);
+    let section = opt_header + opt_header_size;
+    data[section..section + 5].copy_from_slice(b".rsrc");
+    put_u32(&mut data, section + 8, rsrc.len() as u32);
+    put_u32(&mut data, section + 12, SECTION_RVA);
+    put_u32(&mut data, section + 16, rsrc.len() as u32);
+    put_u32(&mut data, section + 20, SECTION_OFFSET as u32);
+
+    data.extend_from_slice(&rsrc);
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        find_version_resource, parse_version_strings, read_version_strings, test_pe_file,
+        utf16_text, version_block, ERR_NO_VERSION, RESOURCE_DIR_IDX,
+    };
+
+    #[test]
+    fn parses_version_strings() {
+        let mut fixed = vec![0u8; 52];
+        fixed[0..4].copy_from_slice(&0xfeef04bdu32.to_le_bytes());
+
+        let strings = version_block(
+            "040904b0",
+            &[],
+            true,
+            &[
+                version_block("FileVersion", &utf16_text("3.1.2.7"), true, &[]),
+                version_block("ProductVersion", &utf16_text("283316/15866"), true, &[]),
+            ],
+        );
+        let data = version_block(
+            "VS_VERSION_INFO",
+            &fixed,
+            false,
+            &[version_block("StringFileInfo", &[], true, &[strings])],
+        );
+
+        assert_eq!(
+            parse_version_strings(&data).unwrap(),
+            vec![
+                ("FileVersion".to_string(), "3.1.2.7".to_string()),
+                ("ProductVersion".to_string(), "283316/15866".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_version_resource() {
+        for pe32_plus in [false, true] {
+            let data = test_pe_file(pe32_plus, &[("ProductVersion", "283316/15866")]);
+            let resource = find_version_resource(&data).unwrap();
+            assert_eq!(
+                parse_version_strings(resource).unwrap(),
+                vec![("ProductVersion".to_string(), "283316/15866".to_string())],
+                "pe32_plus: {}",
+                pe32_plus
+            );
+        }
+    }
+
+    #[test]
+    fn reads_version_strings_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Test.dll");
+        let data = test_pe_file(true, &[("FileVersion", "1.0"), ("ProductVersion", "1/2")]);
+        std::fs::write(&path, data).unwrap();
+
+        assert_eq!(
+            read_version_strings(&path).unwrap(),
+            vec![
+                ("FileVersion".to_string(), "1.0".to_string()),
+                ("ProductVersion".to_string(), "1/2".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_files_without_version_resource() {
+        assert!(find_version_resource(b"not a PE file").is_err());
+
+        let data = test_pe_file(true, &[]);
+        assert!(find_version_resource(&data[..0x100]).is_err());
+
+        let mut data = test_pe_file(true, &[]);
+        let resource_dir_entry = 0x58 + 112 + RESOURCE_DIR_IDX * 8;
+        data[resource_dir_entry..resource_dir_entry + 4].copy_from_slice(&[0; 4]);
+        assert_eq!(
+            find_version_resource(&data).unwrap_err().to_string(),
+            ERR_NO_VERSION
+        );
+    }
+}
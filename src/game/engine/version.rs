@@ -1,11 +1,55 @@
-use std::ffi::c_void;
 use std::path::Path;
 
-use anyhow::Result;
-use dlopen::symbor::Library;
+use anyhow::{anyhow, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use slog::{debug, Logger};
 
-pub fn get_game_version(game_path: &Path) -> Result<(u32, u16)> {
-    let dw_lib = Library::open(game_path.join("ConanSandbox/Binaries/Win64/Dreamworld.dll"))?;
+use super::pe::read_version_strings;
+
+// The version is read from the version resource of Dreamworld.dll whenever it records the
+// revision and snapshot, because that doesn't run any of the game's code. Loading the DLL and
+// asking it for the version is only a fallback, and only possible on Windows.
+pub fn get_game_version(logger: &Logger, game_path: &Path) -> Result<(u32, u16)> {
+    let dll_path = game_path.join(DREAMWORLD_DLL);
+    let err = match read_version_resource(&dll_path) {
+        Ok(version) => return Ok(version),
+        Err(err) => err,
+    };
+    debug!(
+        logger,
+        "Cannot read the game version from the version resource";
+        "error" => %err,
+    );
+
+    load_game_version(&dll_path).map_err(|dl_err| anyhow!("{} ({})", err, dl_err))
+}
+
+fn read_version_resource(dll_path: &Path) -> Result<(u32, u16)> {
+    let strings = read_version_strings(dll_path)?;
+    strings
+        .iter()
+        .find(|(name, _)| name == VERSION_KEY)
+        .and_then(|(_, value)| parse_version_string(value))
+        .ok_or_else(|| anyhow!("The version resource does not contain the revision"))
+}
+
+fn parse_version_string(value: &str) -> Option<(u32, u16)> {
+    let captures = VERSION_REGEX.captures(value)?;
+    Some((captures[1].parse().ok()?, captures[2].parse().ok()?))
+}
+
+#[cfg(windows)]
+fn load_game_version(dll_path: &Path) -> Result<(u32, u16)> {
+    use std::ffi::c_void;
+
+    use dlopen::symbor::Library;
+
+    type DwFnGetVersion = unsafe extern "C" fn() -> *mut c_void;
+    type DwFnGetRevision = unsafe extern "C" fn(*mut c_void) -> u32;
+    type DwFnGetSnapshot = unsafe extern "C" fn(*mut c_void) -> u16;
+
+    let dw_lib = Library::open(dll_path)?;
     let get_version = unsafe { dw_lib.symbol::<DwFnGetVersion>(SYM_GET_VERSION)? };
     let get_revision = unsafe { dw_lib.symbol::<DwFnGetRevision>(SYM_GET_REVISION)? };
     let get_snapshot = unsafe { dw_lib.symbol::<DwFnGetSnapshot>(SYM_GET_SNAPSHOT)? };
@@ -16,10 +60,64 @@ pub fn get_game_version(game_path: &Path) -> Result<(u32, u16)> {
     }
 }
 
-type DwFnGetVersion = unsafe extern "C" fn() -> *mut c_void;
-type DwFnGetRevision = unsafe extern "C" fn(*mut c_void) -> u32;
-type DwFnGetSnapshot = unsafe extern "C" fn(*mut c_void) -> u16;
+#[cfg(not(windows))]
+fn load_game_version(_: &Path) -> Result<(u32, u16)> {
+    Err(anyhow!("Loading the game DLL is only supported on Windows"))
+}
 
+lazy_static! {
+    // "<revision>/<snapshot>", optionally following a release number, as in "3.0.1 (462513/25513)".
+    // Dotted versions are not accepted: their last two parts are not the revision and snapshot.
+    static ref VERSION_REGEX: Regex =
+        Regex::new(r"(?:^|[\s(])(\d+)\s*/\s*(\d{1,5})\s*\)?$").unwrap();
+}
+
+const DREAMWORLD_DLL: &str = "ConanSandbox/Binaries/Win64/Dreamworld.dll";
+const VERSION_KEY: &str = "ProductVersion";
+
+#[cfg(windows)]
 const SYM_GET_VERSION: &str = "?Get@Version@dw@@SAAEAV12@XZ";
+#[cfg(windows)]
 const SYM_GET_REVISION: &str = "?GetRevision@Version@dw@@QEBA?BHXZ";
+#[cfg(windows)]
 const SYM_GET_SNAPSHOT: &str = "?GetSnapshot@Version@dw@@QEBA?BHXZ";
+
+#[cfg(test)]
+mod tests {
+    use slog::{o, Discard, Logger};
+
+    use super::super::pe::test_pe_file;
+    use super::{get_game_version, parse_version_string, DREAMWORLD_DLL};
+
+    #[test]
+    fn parses_revision_and_snapshot() {
+        assert_eq!(parse_version_string("283316/15866"), Some((283316, 15866)));
+        assert_eq!(
+            parse_version_string("3.0.1 (462513/25513)"),
+            Some((462513, 25513))
+        );
+    }
+
+    #[test]
+    fn rejects_dotted_versions() {
+        assert_eq!(parse_version_string("1.0.462513.25513"), None);
+        assert_eq!(parse_version_string("10.0.19041.1"), None);
+        assert_eq!(parse_version_string("1.0.0.1"), None);
+        assert_eq!(parse_version_string("3.0.1/2"), None);
+    }
+
+    #[test]
+    fn reads_version_from_dll_resource() {
+        let dir = tempfile::tempdir().unwrap();
+        let dll_path = dir.path().join(DREAMWORLD_DLL);
+        std::fs::create_dir_all(dll_path.parent().unwrap()).unwrap();
+        let data = test_pe_file(true, &[("ProductVersion", "3.0.1 (462513/25513)")]);
+        std::fs::write(&dll_path, data).unwrap();
+
+        let logger = Logger::root(Discard, o!());
+        assert_eq!(
+            get_game_version(&logger, dir.path()).unwrap(),
+            (462513, 25513)
+        );
+    }
+}
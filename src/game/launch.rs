@@ -9,11 +9,12 @@ use crate::workers::TaskState;
 pub struct Launch {
     logger: Logger,
     child: Child,
+    detached: bool,
     poll_impl: PollImpl,
 }
 
 impl Launch {
-    pub(super) fn new(logger: &Logger, mut cmd: Command) -> Result<Self> {
+    pub(super) fn new(logger: &Logger, mut cmd: Command, detached: bool) -> Result<Self> {
         let logger = logger.clone();
 
         let child = cmd.spawn()?;
@@ -23,6 +24,7 @@ impl Launch {
         Ok(Self {
             logger,
             child,
+            detached,
             poll_impl,
         })
     }
@@ -30,7 +32,9 @@ impl Launch {
     pub fn poll(&mut self) -> Result<TaskState<()>> {
         debug!(&self.logger, "Checking if the game is visible");
         if let Some(code) = self.child.try_wait()? {
-            bail!("Game process ended unexpectedly with status {}", code);
+            if !(self.detached && code.success()) {
+                bail!("Game process ended unexpectedly with status {}", code);
+            }
         }
         self.poll_impl.poll()
     }
@@ -52,7 +56,7 @@ impl PollImpl {
     }
 
     fn poll(&self) -> Result<TaskState<()>> {
-        TaskState::Ready(())
+        Ok(TaskState::Ready(()))
     }
 
     fn cancel(&self) {
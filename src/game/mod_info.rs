@@ -1,9 +1,10 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
-use std::io::Read;
+use std::io::{BufRead, Read, Write};
 use std::ops::Index;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
+use std::sync::Mutex;
 
 use anyhow::{anyhow, bail, Result};
 use binread::{BinReaderExt, BinResult};
@@ -49,8 +50,34 @@ pub struct ModInfo {
     #[serde(skip)]
     pub pak_path: PathBuf,
 
+    #[serde(skip)]
+    pub source: ModSource,
+
+    #[serde(skip)]
+    pub provides_maps: bool,
+
     #[serde(skip)]
     needs_update: AtomicBool,
+
+    #[serde(skip)]
+    pinned: AtomicBool,
+
+    #[serde(skip)]
+    tags: Mutex<Vec<String>>,
+}
+
+// Where the mod was found: installed through the game platform, e.g. from the Steam workshop, or
+// a pak in the game's Mods folder or another local folder that the platform doesn't know about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModSource {
+    Platform,
+    Local,
+}
+
+impl Default for ModSource {
+    fn default() -> Self {
+        Self::Platform
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -107,6 +134,10 @@ impl ModInfo {
         })
     }
 
+    pub(super) fn with_source(self, source: ModSource) -> Self {
+        Self { source, ..self }
+    }
+
     pub fn steam_file_id(&self, branch: Branch) -> Option<u64> {
         let id_str = match branch {
             Branch::Main => &self.live_steam_file_id,
@@ -123,6 +154,23 @@ impl ModInfo {
         self.needs_update
             .store(value, std::sync::atomic::Ordering::Relaxed)
     }
+
+    pub fn is_pinned(&self) -> bool {
+        self.pinned.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    pub fn set_pinned(&self, value: bool) {
+        self.pinned
+            .store(value, std::sync::atomic::Ordering::Relaxed)
+    }
+
+    pub fn tags(&self) -> Vec<String> {
+        self.tags.lock().unwrap().clone()
+    }
+
+    pub fn set_tags(&self, tags: Vec<String>) {
+        *self.tags.lock().unwrap() = tags;
+    }
 }
 
 impl ToString for ModVersion {
@@ -206,6 +254,45 @@ impl Mods {
     pub fn iter(&self) -> impl Iterator<Item = &ModInfo> {
         self.mods.iter()
     }
+
+    // Reads a modlist, which holds the path of one pak per line. Relative paths, e.g. in lists
+    // written by hand, are relative to the game's own Mods folder, like the game takes them.
+    pub(super) fn read_mod_list(
+        &self,
+        reader: impl BufRead,
+        local_mods_path: &Path,
+    ) -> Vec<ModRef> {
+        let mut mod_list = Vec::new();
+        for line in reader.lines().filter_map(|line| line.ok()) {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mod_path = Path::new(line);
+            if mod_path.is_relative() {
+                mod_list.push(self.by_pak_path(local_mods_path.join(mod_path)));
+            } else {
+                mod_list.push(self.by_pak_path(mod_path));
+            }
+        }
+        mod_list
+    }
+
+    pub(super) fn write_mod_list<'m>(
+        &self,
+        mut writer: impl Write,
+        mod_list: impl IntoIterator<Item = &'m ModRef>,
+    ) -> Result<()> {
+        for mod_ref in mod_list {
+            let pak_path = match mod_ref {
+                ModRef::Installed(_) => &self.get(mod_ref).unwrap().pak_path,
+                ModRef::UnknownPakPath(path) => path,
+                ModRef::UnknownFolder(_) => continue,
+            };
+            writeln!(writer, "{}", pak_path.display())?;
+        }
+        Ok(())
+    }
 }
 
 impl Index<usize> for Mods {
@@ -214,3 +301,123 @@ impl Index<usize> for Mods {
         &self.mods[index]
     }
 }
+
+// Builds a mod the way its modinfo.json would describe it, with the given JSON fields replacing
+// the defaults.
+#[cfg(test)]
+pub fn test_mod(fields: serde_json::Value) -> ModInfo {
+    serde_json::from_value(test_mod_json(fields)).unwrap()
+}
+
+#[cfg(test)]
+pub fn test_mods(mods: Vec<ModInfo>) -> Mods {
+    Mods::new(mods)
+}
+
+// Writes a pak with just a modinfo.json, for tests that need installed mods.
+#[cfg(test)]
+pub(crate) fn write_test_mod(pak_path: &Path, folder_name: &str, revision_number: u64) {
+    let mod_info = test_mod_json(serde_json::json!({
+        "name": folder_name,
+        "folderName": folder_name,
+        "revisionNumber": revision_number,
+        "snapshotId": revision_number * 10,
+    }));
+    let mod_info = serde_json::to_vec(&mod_info).unwrap();
+    super::engine::pak::write_test_pak(pak_path, &[("modinfo.json", &mod_info)]).unwrap();
+}
+
+#[cfg(test)]
+fn test_mod_json(fields: serde_json::Value) -> serde_json::Value {
+    let mut json = serde_json::json!({
+        "name": "Test Mod",
+        "description": "",
+        "changeNote": "",
+        "author": "",
+        "authorUrl": "",
+        "versionMajor": 1,
+        "versionMinor": 0,
+        "versionBuild": 0,
+        "bRequiresLoadOnStartup": false,
+        "steamPublishedFileId": "",
+        "folderName": "TestMod",
+        "revisionNumber": 1,
+        "snapshotId": 10,
+    });
+    if let (Some(json), serde_json::Value::Object(fields)) = (json.as_object_mut(), fields) {
+        json.extend(fields);
+    }
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use serde_json::json;
+
+    use super::{test_mod, write_test_mod, ModInfo, ModRef, Mods};
+
+    #[test]
+    fn reads_mod_info_from_pak() {
+        let dir = tempfile::tempdir().unwrap();
+        let pak_path = dir.path().join("TestMod.pak");
+        write_test_mod(&pak_path, "TestMod", 3);
+
+        let mod_info = ModInfo::new(pak_path.clone()).unwrap();
+        assert_eq!(mod_info.name, "TestMod");
+        assert_eq!(mod_info.folder_name, "TestMod");
+        assert_eq!(mod_info.revision_number, 3);
+        assert_eq!(mod_info.snapshot_id, 30);
+        assert_eq!(mod_info.pak_path, pak_path);
+        assert_eq!(mod_info.version.to_string(), "1.0.0");
+    }
+
+    #[test]
+    fn reads_and_writes_mod_lists() {
+        let root = std::env::temp_dir();
+        let local_mods_path = root.join("ConanSandbox/Mods");
+        let workshop_pak_path = root.join("workshop/880454836/Pippi.pak");
+        let missing_pak_path = root.join("elsewhere/Missing.pak");
+        let mod_at = |pak_path: PathBuf| ModInfo {
+            pak_path,
+            ..test_mod(json!({}))
+        };
+        let mods = Mods::new(vec![
+            mod_at(workshop_pak_path.clone()),
+            mod_at(local_mods_path.join("MyMod.pak")),
+        ]);
+
+        let text = format!(
+            "# Mods\n{}\nMyMod.pak\n\n  {}  \n",
+            workshop_pak_path.display(),
+            missing_pak_path.display()
+        );
+        let mod_list = mods.read_mod_list(text.as_bytes(), &local_mods_path);
+        assert_eq!(
+            mod_list,
+            [
+                ModRef::Installed(0),
+                ModRef::Installed(1),
+                ModRef::UnknownPakPath(missing_pak_path.clone())
+            ]
+        );
+
+        let mut written = Vec::new();
+        let with_unknown_folder = mod_list
+            .iter()
+            .chain(Some(&ModRef::UnknownFolder("Gone".to_string())));
+        mods.write_mod_list(&mut written, with_unknown_folder)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(written.clone()).unwrap(),
+            format!(
+                "{}\n{}\n{}\n",
+                workshop_pak_path.display(),
+                local_mods_path.join("MyMod.pak").display(),
+                missing_pak_path.display()
+            )
+        );
+        assert_eq!(mods.read_mod_list(&written[..], &local_mods_path), mod_list);
+    }
+}
@@ -0,0 +1,209 @@
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::JsonStore;
+
+use super::ModInfo;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PinnedMod {
+    pub pak_path: PathBuf,
+    pub name: String,
+    pub revision_number: u64,
+    pub snapshot_id: u64,
+    pub backup_path: Option<PathBuf>,
+}
+
+// Mods that the user wants to keep at their installed revision. Steam updates subscribed mods on
+// its own, so a copy of each pinned pak is kept in the backup folder, and put back in place
+// whenever the installed pak turns out to be a different revision. Without a backup folder, pins
+// cannot be restored.
+pub struct ModPins {
+    backup_dir: Option<PathBuf>,
+    pins: JsonStore<Vec<PinnedMod>>,
+}
+
+impl ModPins {
+    pub fn load(path: Option<PathBuf>, backup_dir: Option<PathBuf>) -> Result<Self> {
+        Ok(Self {
+            backup_dir,
+            pins: JsonStore::load(path)?,
+        })
+    }
+
+    pub fn transient() -> Self {
+        Self {
+            backup_dir: None,
+            pins: JsonStore::transient(),
+        }
+    }
+
+    pub fn pins(&self) -> &[PinnedMod] {
+        &self.pins
+    }
+
+    pub fn get(&self, pak_path: &Path) -> Option<&PinnedMod> {
+        self.pins.iter().find(|pin| pin.pak_path == pak_path)
+    }
+
+    pub fn backup_dir(&self) -> Option<&Path> {
+        self.backup_dir.as_deref()
+    }
+
+    // Copies the pak to the backup folder and describes the pin from that copy, so the pin records
+    // the revision that can actually be restored rather than the one found at startup. This copies
+    // the whole pak, so it is meant to run off the UI thread.
+    pub fn prepare_pin(backup_dir: Option<&Path>, pak_path: &Path) -> Result<PinnedMod> {
+        let backup_dir = match backup_dir {
+            Some(backup_dir) => backup_dir,
+            None => {
+                let mod_info = ModInfo::new(pak_path.to_path_buf())?;
+                return Ok(PinnedMod {
+                    pak_path: pak_path.to_path_buf(),
+                    name: mod_info.name,
+                    revision_number: mod_info.revision_number,
+                    snapshot_id: mod_info.snapshot_id,
+                    backup_path: None,
+                });
+            }
+        };
+
+        std::fs::create_dir_all(backup_dir)?;
+        let backup = tempfile::Builder::new()
+            .suffix(".pak")
+            .tempfile_in(backup_dir)?;
+        std::fs::copy(pak_path, backup.path())?;
+        let mod_info = ModInfo::new(backup.path().to_path_buf())?;
+
+        let file_name = format!(
+            "{}-{}-{}.pak",
+            &mod_info.folder_name, mod_info.revision_number, mod_info.snapshot_id
+        );
+        let backup_path = backup_dir.join(file_name);
+        backup.persist(&backup_path)?;
+
+        Ok(PinnedMod {
+            pak_path: pak_path.to_path_buf(),
+            name: mod_info.name,
+            revision_number: mod_info.revision_number,
+            snapshot_id: mod_info.snapshot_id,
+            backup_path: Some(backup_path),
+        })
+    }
+
+    pub fn add(&mut self, pin: PinnedMod) -> Result<()> {
+        if self.get(&pin.pak_path).is_some() {
+            return Ok(());
+        }
+        self.pins.push(pin);
+        self.pins.save()
+    }
+
+    pub fn unpin(&mut self, pak_path: &Path) -> Result<()> {
+        let idx = match self.pins.iter().position(|pin| pin.pak_path == pak_path) {
+            Some(idx) => idx,
+            None => return Ok(()),
+        };
+        let pin = self.pins.remove(idx);
+        self.pins.save()?;
+
+        if let Some(backup_path) = pin.backup_path {
+            match std::fs::remove_file(backup_path) {
+                Err(err) if err.kind() != ErrorKind::NotFound => return Err(err.into()),
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    // Puts the pinned revision back in place if the installed pak is a different revision. Returns
+    // whether the pak had to be restored.
+    pub fn restore(&self, pak_path: &Path) -> Result<bool> {
+        let pin = self
+            .get(pak_path)
+            .ok_or_else(|| anyhow!("The mod is not pinned"))?;
+        if let Ok(installed) = ModInfo::new(pak_path.to_path_buf()) {
+            if installed.revision_number == pin.revision_number
+                && installed.snapshot_id == pin.snapshot_id
+            {
+                return Ok(false);
+            }
+        }
+
+        let backup_path = match &pin.backup_path {
+            Some(backup_path) if backup_path.exists() => backup_path,
+            _ => bail!("There is no copy of the pinned revision of {}", &pin.name),
+        };
+        std::fs::copy(backup_path, pak_path)?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::mod_info::write_test_mod;
+
+    use super::{ModInfo, ModPins};
+
+    #[test]
+    fn pins_and_restores_backed_up_revision() {
+        let dir = tempfile::tempdir().unwrap();
+        let pak_path = dir.path().join("Mods/TestMod.pak");
+        let backup_dir = dir.path().join("Backups");
+        std::fs::create_dir_all(pak_path.parent().unwrap()).unwrap();
+        write_test_mod(&pak_path, "TestMod", 1);
+
+        let mut pins = ModPins::load(Some(dir.path().join("pins.json")), Some(backup_dir)).unwrap();
+        let pin = ModPins::prepare_pin(pins.backup_dir(), &pak_path).unwrap();
+        assert_eq!(pin.revision_number, 1);
+        assert_eq!(pin.snapshot_id, 10);
+        assert!(pin.backup_path.as_ref().unwrap().exists());
+        pins.add(pin).unwrap();
+        assert!(!pins.restore(&pak_path).unwrap());
+
+        write_test_mod(&pak_path, "TestMod", 2);
+        assert!(pins.restore(&pak_path).unwrap());
+        let restored = ModInfo::new(pak_path.clone()).unwrap();
+        assert_eq!(restored.revision_number, 1);
+
+        let pins = ModPins::load(Some(dir.path().join("pins.json")), None).unwrap();
+        assert_eq!(pins.pins().len(), 1);
+        assert_eq!(pins.pins()[0].pak_path, pak_path);
+    }
+
+    #[test]
+    fn unpin_removes_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let pak_path = dir.path().join("TestMod.pak");
+        write_test_mod(&pak_path, "TestMod", 1);
+
+        let mut pins = ModPins::load(None, Some(dir.path().join("Backups"))).unwrap();
+        let pin = ModPins::prepare_pin(pins.backup_dir(), &pak_path).unwrap();
+        let backup_path = pin.backup_path.clone().unwrap();
+        pins.add(pin).unwrap();
+
+        pins.unpin(&pak_path).unwrap();
+        assert!(pins.get(&pak_path).is_none());
+        assert!(!backup_path.exists());
+        assert!(pak_path.exists());
+        assert!(pins.restore(&pak_path).is_err());
+    }
+
+    #[test]
+    fn cannot_restore_without_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let pak_path = dir.path().join("TestMod.pak");
+        write_test_mod(&pak_path, "TestMod", 1);
+
+        let mut pins = ModPins::transient();
+        pins.add(ModPins::prepare_pin(None, &pak_path).unwrap())
+            .unwrap();
+        assert!(!pins.restore(&pak_path).unwrap());
+
+        write_test_mod(&pak_path, "TestMod", 2);
+        assert!(pins.restore(&pak_path).is_err());
+    }
+}
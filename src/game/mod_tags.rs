@@ -0,0 +1,67 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::config::JsonStore;
+
+// User-defined tags for installed mods, keyed by the mod's folder name so that they survive the mod
+// being reinstalled or moved to another folder.
+pub struct ModTags {
+    tags: JsonStore<BTreeMap<String, Vec<String>>>,
+}
+
+impl ModTags {
+    pub fn load(path: Option<PathBuf>) -> Result<Self> {
+        Ok(Self {
+            tags: JsonStore::load(path)?,
+        })
+    }
+
+    pub fn transient() -> Self {
+        Self {
+            tags: JsonStore::transient(),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[String])> {
+        self.tags
+            .iter()
+            .map(|(folder_name, tags)| (folder_name.as_str(), tags.as_slice()))
+    }
+
+    pub fn set(&mut self, folder_name: &str, tags: Vec<String>) -> Result<()> {
+        if tags.is_empty() {
+            self.tags.remove(folder_name);
+        } else {
+            self.tags.insert(folder_name.to_string(), tags);
+        }
+        self.tags.save()
+    }
+}
+
+// Turns a comma-separated list into tags, trimmed, lowercased and without duplicates.
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = input
+        .split(',')
+        .map(|tag| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_tags;
+
+    #[test]
+    fn parse_tags_normalizes_input() {
+        assert_eq!(
+            parse_tags(" Building, QoL,,building ,maps"),
+            vec!["building", "maps", "qol"]
+        );
+        assert!(parse_tags(" , ").is_empty());
+    }
+}
@@ -1,21 +1,185 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::time::SystemTime;
 
 use anyhow::Result;
+use fltk::app;
+use slog::{debug, warn, Logger};
 
+use crate::auth::PlatformUser;
 use crate::workers::TaskState;
+use crate::Message;
 
-use super::ModInfo;
+use super::{Branch, Game, LaunchMethod, ModInfo, ModSource, Mods, ProtonPrefix};
 
+pub mod manual;
 pub mod steam;
 
+// A platform the game can be installed from. It knows where the game and its mods are installed,
+// and creates the client that is used to identify the user and play online once the game has been
+// initialized.
+pub trait GamePlatform {
+    fn logger(&self) -> &Logger;
+    fn locate_game(&mut self, branch: Branch) -> Result<GameLocation>;
+    fn installed_mods(&self, location: &GameLocation) -> Result<Vec<ModInfo>>;
+    fn init_client(&self, game: &Game, tx: app::Sender<Message>) -> Rc<dyn PlatformClient>;
+
+    fn init_game(&mut self, location: GameLocation, local_mods_paths: &[PathBuf]) -> Result<Game> {
+        debug!(
+            self.logger(),
+            "Enumerating installed mods";
+            "mods_path" => ?location.mods_path
+        );
+        let mut installed_mods = self.installed_mods(&location)?;
+
+        // The game also loads paks from its own Mods folder, which is where mods that weren't
+        // installed through the platform, like mods in development, usually live.
+        let game_mods_path = location.game_path.join("ConanSandbox/Mods");
+        for path in std::iter::once(&game_mods_path).chain(local_mods_paths) {
+            collect_mods(self.logger(), path, ModSource::Local, &mut installed_mods);
+        }
+
+        Game::new(self.logger().clone(), location, installed_mods)
+    }
+}
+
+// Picks up the paks in a folder that aren't known yet, skipping those that cannot be read.
+fn collect_mods(logger: &Logger, path: &Path, source: ModSource, mods: &mut Vec<ModInfo>) {
+    debug!(logger, "Enumerating mods"; "path" => path.display());
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(err) => {
+            debug!(logger, "Cannot read mod folder"; "error" => %err);
+            return;
+        }
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let pak_path = entry.path();
+        if pak_path.extension().map(|ext| ext != "pak").unwrap_or(true)
+            || mods.iter().any(|mod_info| mod_info.pak_path == pak_path)
+        {
+            continue;
+        }
+        match ModInfo::new(pak_path.clone()) {
+            Ok(mod_info) => mods.push(mod_info.with_source(source)),
+            Err(err) => warn!(
+                logger,
+                "Skipping unreadable mod";
+                "pak_path" => pak_path.display(),
+                "error" => %err,
+            ),
+        }
+    }
+}
+
+pub trait PlatformClient {
+    fn can_launch(&self) -> bool;
+    fn can_play_online(&self) -> bool;
+    // Whether the platform signs the user in, and thereby provides the FLS account. Otherwise the
+    // game is left to deal with accounts on its own.
+    fn has_user_accounts(&self) -> bool;
+    fn user(&self) -> Option<PlatformUser>;
+    fn auth_ticket(&self) -> Option<Rc<dyn PlatformTicket>>;
+    fn run_callbacks(&self);
+    fn mod_directory(
+        self: Rc<Self>,
+        logger: Logger,
+        tx: app::Sender<Message>,
+        installed_mods: &Mods,
+    ) -> Rc<dyn ModDirectory>;
+}
+
+pub trait PlatformTicket {
+    fn data(&self) -> &[u8];
+}
+
+pub struct GameLocation {
+    pub game_path: PathBuf,
+    pub mods_path: Option<PathBuf>,
+    pub branch: Branch,
+    pub needs_update: bool,
+    pub launch_method: LaunchMethod,
+    pub proton_prefix: Option<ProtonPrefix>,
+}
+
 pub trait ModDirectory {
     fn resolve(self: Rc<Self>, mods: &mut [(u64, Option<String>)]);
     fn needs_update(self: Rc<Self>, mod_ref: &ModInfo) -> Result<bool>;
     fn can_update(self: Rc<Self>) -> bool;
     fn start_update(self: Rc<Self>, mod_ref: &ModInfo) -> Result<Rc<dyn ModUpdate>>;
+    fn query_collection(self: Rc<Self>, collection_id: u64, callback: CollectionCallback);
+    fn subscribe(self: Rc<Self>, mod_id: u64) -> Result<Rc<dyn ModUpdate>>;
+    fn mod_pak_paths(self: Rc<Self>, mod_id: u64) -> Result<Vec<PathBuf>>;
+    fn query_remote_info(self: Rc<Self>, mod_ids: Vec<u64>, callback: RemoteInfoCallback);
+    fn unsubscribe(self: Rc<Self>, mod_id: u64, callback: UnsubscribeCallback);
+    fn install_state(self: Rc<Self>, mod_info: &ModInfo) -> Result<ModInstallState>;
+    fn orphaned_folders(self: Rc<Self>) -> Result<Vec<PathBuf>>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModInstallState {
+    NotInstalled,
+    Installed,
+    Downloading,
+    NeedsUpdate,
+    // Still installed, but no longer subscribed to, so the platform will remove it
+    PendingRemoval,
+}
+
+pub type CollectionCallback = Box<dyn FnOnce(Result<Vec<u64>>)>;
+pub type UnsubscribeCallback = Box<dyn FnOnce(Result<()>)>;
+pub type RemoteInfoCallback = Box<dyn FnOnce(Result<HashMap<u64, RemoteModInfo>>)>;
+
+// What the platform knows about the latest published revision of a mod. The workshop does not
+// expose the revision number or the change notes of an item, only when it was last updated.
+#[derive(Debug, Clone)]
+pub struct RemoteModInfo {
+    pub title: String,
+    pub time_updated: SystemTime,
 }
 
 pub trait ModUpdate {
     fn state(&self) -> TaskState<Result<()>>;
     fn progress(&self) -> Option<(u64, u64)>;
 }
+
+#[cfg(test)]
+mod tests {
+    use slog::{o, Discard, Logger};
+
+    use crate::game::mod_info::write_test_mod;
+    use crate::game::ModSource;
+
+    use super::collect_mods;
+
+    #[test]
+    fn collects_readable_paks_once() {
+        let dir = tempfile::tempdir().unwrap();
+        write_test_mod(&dir.path().join("First.pak"), "First", 1);
+        write_test_mod(&dir.path().join("Second.pak"), "Second", 2);
+        std::fs::write(dir.path().join("Broken.pak"), b"not a pak").unwrap();
+        std::fs::write(dir.path().join("readme.txt"), b"").unwrap();
+
+        let logger = Logger::root(Discard, o!());
+        let mut mods = Vec::new();
+        collect_mods(&logger, dir.path(), ModSource::Local, &mut mods);
+        collect_mods(&logger, dir.path(), ModSource::Platform, &mut mods);
+        collect_mods(
+            &logger,
+            &dir.path().join("Missing"),
+            ModSource::Local,
+            &mut mods,
+        );
+
+        let mut folder_names: Vec<&str> = mods
+            .iter()
+            .map(|mod_info| mod_info.folder_name.as_str())
+            .collect();
+        folder_names.sort_unstable();
+        assert_eq!(folder_names, ["First", "Second"]);
+        assert!(mods
+            .iter()
+            .all(|mod_info| mod_info.source == ModSource::Local));
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use anyhow::{anyhow, bail, Result};
+use fltk::app;
+use slog::{debug, o, Logger};
+
+use crate::auth::PlatformUser;
+use crate::game::{Branch, Game, LaunchMethod, ModInfo, ModSource, Mods};
+use crate::Message;
+
+use super::{
+    collect_mods, CollectionCallback, GameLocation, GamePlatform, ModDirectory, ModInstallState,
+    ModUpdate, PlatformClient, PlatformTicket, RemoteInfoCallback, UnsubscribeCallback,
+};
+
+// A game installation that is not managed by any platform, e.g. a copy of the game outside of a
+// Steam library. The game and mod folders are taken from the config. There is no platform to
+// identify the user or update mods with, so signing in is left to the game, and mods are never
+// updated.
+pub struct ManualInstall {
+    logger: Logger,
+    game_path: PathBuf,
+    mods_path: Option<PathBuf>,
+    branch: Branch,
+}
+
+impl ManualInstall {
+    pub fn new(
+        logger: &Logger,
+        game_path: PathBuf,
+        mods_path: Option<PathBuf>,
+        branch: Branch,
+    ) -> Self {
+        Self {
+            logger: logger.new(o!("platform" => "manual")),
+            game_path,
+            mods_path,
+            branch,
+        }
+    }
+}
+
+impl GamePlatform for ManualInstall {
+    fn logger(&self) -> &Logger {
+        &self.logger
+    }
+
+    fn locate_game(&mut self, branch: Branch) -> Result<GameLocation> {
+        debug!(self.logger, "Checking game installation"; "path" => self.game_path.display());
+        if branch != self.branch {
+            bail!(ERR_OTHER_BRANCH);
+        }
+        if !self.game_path.join("ConanSandbox").is_dir() {
+            bail!(
+                "Cannot locate Conan Exiles installation in {}. Please verify the game path in \
+                    the BUGLE config and try again.",
+                self.game_path.display(),
+            );
+        }
+
+        Ok(GameLocation {
+            game_path: self.game_path.clone(),
+            mods_path: self.mods_path.clone(),
+            branch,
+            needs_update: false,
+            launch_method: LaunchMethod::Direct,
+            proton_prefix: None,
+        })
+    }
+
+    fn installed_mods(&self, location: &GameLocation) -> Result<Vec<ModInfo>> {
+        let mods_path = match &location.mods_path {
+            Some(path) => path,
+            None => return Ok(Vec::new()),
+        };
+        if !mods_path.is_dir() {
+            return Err(anyhow!(
+                "The mod folder {} does not exist.",
+                mods_path.display()
+            ));
+        }
+
+        // Both a flat folder of mods and a folder per mod, like in the Steam workshop, work.
+        let mut mods = Vec::new();
+        collect_mods(&self.logger, mods_path, ModSource::Platform, &mut mods);
+        for entry in std::fs::read_dir(mods_path)? {
+            let entry_path = entry?.path();
+            if entry_path.is_dir() {
+                collect_mods(&self.logger, &entry_path, ModSource::Platform, &mut mods);
+            }
+        }
+        Ok(mods)
+    }
+
+    fn init_client(&self, _: &Game, _: app::Sender<Message>) -> Rc<dyn PlatformClient> {
+        Rc::new(ManualClient)
+    }
+}
+
+struct ManualClient;
+
+impl PlatformClient for ManualClient {
+    fn can_launch(&self) -> bool {
+        true
+    }
+
+    // There is no platform that could be offline, whether the game can get online is up to it.
+    fn can_play_online(&self) -> bool {
+        true
+    }
+
+    fn has_user_accounts(&self) -> bool {
+        false
+    }
+
+    fn user(&self) -> Option<PlatformUser> {
+        None
+    }
+
+    fn auth_ticket(&self) -> Option<Rc<dyn PlatformTicket>> {
+        None
+    }
+
+    fn run_callbacks(&self) {}
+
+    fn mod_directory(
+        self: Rc<Self>,
+        _: Logger,
+        _: app::Sender<Message>,
+        installed_mods: &Mods,
+    ) -> Rc<dyn ModDirectory> {
+        let names = installed_mods
+            .iter()
+            .filter_map(|mod_info| {
+                let id = mod_info
+                    .steam_file_id(Branch::Main)
+                    .or_else(|| mod_info.steam_file_id(Branch::PublicBeta))?;
+                Some((id, mod_info.name.clone()))
+            })
+            .collect();
+        Rc::new(LocalModDirectory { names })
+    }
+}
+
+// Without a platform to query, only the names of the installed mods are known, and the mods are
+// never considered to be out of date.
+struct LocalModDirectory {
+    names: HashMap<u64, String>,
+}
+
+impl ModDirectory for LocalModDirectory {
+    fn resolve(self: Rc<Self>, mods: &mut [(u64, Option<String>)]) {
+        for (id, name) in mods.iter_mut() {
+            *name = self.names.get(id).cloned();
+        }
+    }
+
+    fn needs_update(self: Rc<Self>, _: &ModInfo) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn can_update(self: Rc<Self>) -> bool {
+        false
+    }
+
+    fn start_update(self: Rc<Self>, _: &ModInfo) -> Result<Rc<dyn ModUpdate>> {
+        bail!(ERR_NO_UPDATES)
+    }
+
+    fn query_collection(self: Rc<Self>, _: u64, callback: CollectionCallback) {
+        callback(Err(anyhow!(ERR_NO_WORKSHOP)))
+    }
+
+    fn subscribe(self: Rc<Self>, _: u64) -> Result<Rc<dyn ModUpdate>> {
+        bail!(ERR_NO_WORKSHOP)
+    }
+
+    fn mod_pak_paths(self: Rc<Self>, _: u64) -> Result<Vec<PathBuf>> {
+        bail!(ERR_NO_WORKSHOP)
+    }
+
+    fn query_remote_info(self: Rc<Self>, _: Vec<u64>, callback: RemoteInfoCallback) {
+        callback(Err(anyhow!(ERR_NO_WORKSHOP)))
+    }
+
+    fn unsubscribe(self: Rc<Self>, _: u64, callback: UnsubscribeCallback) {
+        callback(Err(anyhow!(ERR_NO_WORKSHOP)))
+    }
+
+    fn install_state(self: Rc<Self>, _: &ModInfo) -> Result<ModInstallState> {
+        Ok(ModInstallState::Installed)
+    }
+
+    fn orphaned_folders(self: Rc<Self>) -> Result<Vec<PathBuf>> {
+        Ok(Vec::new())
+    }
+}
+
+const ERR_OTHER_BRANCH: &str = "The manually configured installation is for another branch.";
+const ERR_NO_UPDATES: &str = "Mods cannot be updated without a game platform.";
+const ERR_NO_WORKSHOP: &str = "The Steam Workshop is not available without Steam.";
+
+#[cfg(test)]
+mod tests {
+    use slog::{o, Discard, Logger};
+
+    use super::ManualInstall;
+    use crate::game::mod_info::write_test_mod;
+    use crate::game::platform::GamePlatform;
+    use crate::game::{Branch, ModSource};
+
+    #[test]
+    fn locates_fixture_installation() {
+        let dir = tempfile::tempdir().unwrap();
+        let game_path = dir.path().join("Conan Exiles");
+        let mods_path = dir.path().join("Mods");
+        std::fs::create_dir_all(game_path.join("ConanSandbox")).unwrap();
+        std::fs::create_dir_all(mods_path.join("123")).unwrap();
+        std::fs::write(mods_path.join("123/broken.pak"), b"not a pak").unwrap();
+        std::fs::write(mods_path.join("readme.txt"), b"").unwrap();
+        write_test_mod(&mods_path.join("Flat.pak"), "Flat", 1);
+        std::fs::create_dir_all(mods_path.join("456/nested")).unwrap();
+        write_test_mod(&mods_path.join("456/Nested.pak"), "Nested", 1);
+        write_test_mod(&mods_path.join("456/nested/TooDeep.pak"), "TooDeep", 1);
+
+        let logger = Logger::root(Discard, o!());
+        let mut platform =
+            ManualInstall::new(&logger, game_path.clone(), Some(mods_path), Branch::Main);
+        assert!(platform.locate_game(Branch::PublicBeta).is_err());
+
+        let location = platform.locate_game(Branch::Main).unwrap();
+        assert_eq!(location.game_path, game_path);
+        let mods = platform.installed_mods(&location).unwrap();
+        let mut folder_names: Vec<&str> = mods
+            .iter()
+            .map(|mod_info| mod_info.folder_name.as_str())
+            .collect();
+        folder_names.sort_unstable();
+        assert_eq!(folder_names, ["Flat", "Nested"]);
+        assert!(mods
+            .iter()
+            .all(|mod_info| mod_info.source == ModSource::Platform));
+
+        let mut platform =
+            ManualInstall::new(&logger, dir.path().join("Missing"), None, Branch::Main);
+        assert!(platform.locate_game(Branch::Main).is_err());
+    }
+}
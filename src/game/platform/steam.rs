@@ -1,7 +1,8 @@
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use fltk::app;
 use keyvalues_parser::Vdf;
 use slog::{debug, o, Logger};
@@ -12,7 +13,8 @@ mod mod_directory;
 
 pub use self::client::{SteamClient, SteamTicket};
 pub use self::mod_directory::SteamModDirectory;
-use crate::game::{Branch, Game, ModInfo};
+use crate::game::platform::{GameLocation, GamePlatform, PlatformClient};
+use crate::game::{Branch, Game, LaunchMethod, ModInfo, ProtonPrefix};
 use crate::Message;
 
 pub struct Steam {
@@ -20,13 +22,6 @@ pub struct Steam {
     installation: SteamDir,
 }
 
-pub struct SteamGameLocation {
-    game_path: PathBuf,
-    workshop_path: Option<PathBuf>,
-    branch: Branch,
-    needs_update: bool,
-}
-
 impl Steam {
     pub fn locate(logger: &Logger) -> Option<Self> {
         debug!(logger, "Locating Steam installation");
@@ -37,8 +32,14 @@ impl Steam {
             installation,
         })
     }
+}
 
-    pub fn locate_game(&mut self, branch: Branch) -> Result<SteamGameLocation> {
+impl GamePlatform for Steam {
+    fn logger(&self) -> &Logger {
+        &self.logger
+    }
+
+    fn locate_game(&mut self, branch: Branch) -> Result<GameLocation> {
         debug!(self.logger, "Locating game installation");
         let app = self.installation.app(&app_id(branch)).ok_or_else(|| {
             anyhow!(
@@ -56,48 +57,69 @@ impl Steam {
         };
 
         debug!(self.logger, "Determining the workshop path");
-        let workshop_path = self
+        let library_path = self
             .installation
             .libraryfolders()
             .paths
             .iter()
             .find(|path| game_path.starts_with(path))
-            .map(|path| path.join("workshop"));
+            .cloned();
+        let workshop_path = library_path.as_ref().map(|path| path.join("workshop"));
+
+        // Outside of Windows, the game runs through Proton, which keeps a Wine prefix per app in
+        // the library's compatdata folder. Only Steam knows how to set up Proton for the game, so
+        // it is asked to launch the game.
+        let (launch_method, proton_prefix) = if cfg!(windows) {
+            (LaunchMethod::Direct, None)
+        } else {
+            let proton_prefix = library_path
+                .map(|path| path.join("compatdata").join(app_id(branch).to_string()))
+                .filter(|path| path.is_dir())
+                .map(|compat_data_path| ProtonPrefix {
+                    compat_data_path,
+                    steam_path: Some(self.installation.path.clone()),
+                });
+            debug!(self.logger, "Determined the Proton prefix"; "prefix" => ?proton_prefix);
+            let launch_method = LaunchMethod::Steam {
+                app_id: app_id(branch),
+            };
+            (launch_method, proton_prefix)
+        };
 
-        Ok(SteamGameLocation {
+        Ok(GameLocation {
             game_path,
-            workshop_path,
+            mods_path: workshop_path,
             branch,
             needs_update,
+            launch_method,
+            proton_prefix,
         })
     }
 
-    pub fn init_game(&mut self, location: SteamGameLocation) -> Result<Game> {
-        debug!(
-            self.logger,
-            "Enumerating installed mods";
-            "workshop_path" => ?location.workshop_path
-        );
-        let installed_mods = if let Some(workshop_path) = location.workshop_path {
-            collect_mods(&workshop_path, location.branch)?
-        } else {
-            Vec::new()
-        };
-
-        let game = Game::new(
-            self.logger.clone(),
-            location.game_path,
-            location.branch,
-            location.needs_update,
-            installed_mods,
-        )?;
+    fn installed_mods(&self, location: &GameLocation) -> Result<Vec<ModInfo>> {
+        match &location.mods_path {
+            Some(workshop_path) => collect_mods(workshop_path, location.branch),
+            None => Ok(Vec::new()),
+        }
+    }
 
-        Ok(game)
+    fn init_client(&self, game: &Game, tx: app::Sender<Message>) -> Rc<dyn PlatformClient> {
+        let workshop_path = game.mods_path().map(Path::to_path_buf);
+        SteamClient::new(self.logger.clone(), game.branch(), workshop_path, tx)
     }
+}
 
-    pub fn init_client(&self, game: &Game, tx: app::Sender<Message>) -> Rc<SteamClient> {
-        SteamClient::new(self.logger.clone(), game.branch(), tx)
+// Accepts either a bare workshop item ID, or the URL of the item's workshop page.
+pub fn parse_workshop_id(input: &str) -> Option<u64> {
+    let input = input.trim();
+    if let Ok(id) = input.parse() {
+        return Some(id);
     }
+    let query = input.split_once('?')?.1;
+    query
+        .split(&['&', '#'][..])
+        .find_map(|param| param.strip_prefix("id="))
+        .and_then(|id| id.parse().ok())
 }
 
 fn app_id(branch: Branch) -> u32 {
@@ -110,19 +132,12 @@ fn app_id(branch: Branch) -> u32 {
 fn collect_mods(workshop_path: &Path, branch: Branch) -> Result<Vec<ModInfo>> {
     // TODO: Log warnings for recoverable errors
 
-    let manifest_path = workshop_path.join(format!("appworkshop_{}.acf", app_id(branch)));
-    if !manifest_path.exists() {
-        return Ok(Vec::new());
-    }
+    let mod_ids = read_manifest(workshop_path, branch)?;
 
-    let manifest = std::fs::read_to_string(manifest_path)?;
-    let manifest = Vdf::parse(&manifest)?;
-    let mod_ids = collect_mod_ids(&manifest).ok_or(anyhow!("Malformed workshop manifest"))?;
-
-    let mut path = workshop_path.join(format!("content/{}", app_id(branch)));
+    let mut path = content_path(workshop_path, branch);
     let mut mods = Vec::with_capacity(mod_ids.len());
     for mod_id in mod_ids {
-        path.push(mod_id);
+        path.push(&mod_id);
         for pak_path in std::fs::read_dir(&path)? {
             let pak_path = pak_path?.path();
             match pak_path.extension() {
@@ -138,6 +153,66 @@ fn collect_mods(workshop_path: &Path, branch: Branch) -> Result<Vec<ModInfo>> {
     Ok(mods)
 }
 
+// Content folders of mods that Steam no longer tracks in the workshop manifest, e.g. left behind
+// when Steam could not remove an unsubscribed mod. The game never loads these. Folders of mods the
+// Steam client still reports as subscribed or being downloaded are kept, since the manifest can lag
+// behind the client.
+fn find_orphaned_folders(
+    workshop_path: &Path,
+    branch: Branch,
+    is_active: impl Fn(u64) -> bool,
+) -> Result<Vec<PathBuf>> {
+    let content_path = content_path(workshop_path, branch);
+    if !content_path.is_dir() {
+        return Ok(Vec::new());
+    }
+    // Without the manifest, every folder would look orphaned.
+    let manifest_path = manifest_path(workshop_path, branch);
+    if !manifest_path.exists() {
+        bail!("Workshop manifest not found: {}", manifest_path.display());
+    }
+    let mod_ids: HashSet<String> = read_manifest(workshop_path, branch)?.into_iter().collect();
+
+    let mut orphans = Vec::new();
+    for entry in std::fs::read_dir(content_path)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let tracked = entry
+            .file_name()
+            .to_str()
+            .map(|name| {
+                mod_ids.contains(name) || name.parse::<u64>().map(&is_active).unwrap_or_default()
+            })
+            .unwrap_or_default();
+        if !tracked {
+            orphans.push(entry.path());
+        }
+    }
+    Ok(orphans)
+}
+
+fn read_manifest(workshop_path: &Path, branch: Branch) -> Result<Vec<String>> {
+    let manifest_path = manifest_path(workshop_path, branch);
+    if !manifest_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let manifest = std::fs::read_to_string(manifest_path)?;
+    let manifest = Vdf::parse(&manifest)?;
+    let mod_ids = collect_mod_ids(&manifest).ok_or(anyhow!("Malformed workshop manifest"))?;
+    Ok(mod_ids.into_iter().map(str::to_string).collect())
+}
+
+fn manifest_path(workshop_path: &Path, branch: Branch) -> PathBuf {
+    workshop_path.join(format!("appworkshop_{}.acf", app_id(branch)))
+}
+
+fn content_path(workshop_path: &Path, branch: Branch) -> PathBuf {
+    workshop_path.join(format!("content/{}", app_id(branch)))
+}
+
 fn collect_mod_ids<'m>(manifest: &'m Vdf) -> Option<Vec<&'m str>> {
     Some(
         manifest
@@ -153,3 +228,90 @@ fn collect_mod_ids<'m>(manifest: &'m Vdf) -> Option<Vec<&'m str>> {
             .collect(),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{collect_mods, find_orphaned_folders, parse_workshop_id};
+    use crate::game::Branch;
+
+    #[test]
+    fn parses_workshop_ids() {
+        assert_eq!(parse_workshop_id(" 2886779102 "), Some(2886779102));
+        assert_eq!(
+            parse_workshop_id(
+                "https://steamcommunity.com/sharedfiles/filedetails/?id=2886779102&searchtext="
+            ),
+            Some(2886779102)
+        );
+        assert_eq!(
+            parse_workshop_id("https://steamcommunity.com/workshop/filedetails/?l=en&id=123#x"),
+            Some(123)
+        );
+        assert_eq!(parse_workshop_id("https://steamcommunity.com/"), None);
+        assert_eq!(parse_workshop_id("collection"), None);
+    }
+
+    #[test]
+    fn finds_orphaned_folders() {
+        let dir = tempfile::tempdir().unwrap();
+        let content_path = dir.path().join("content/440900");
+        for mod_id in ["111", "222", "333"] {
+            std::fs::create_dir_all(content_path.join(mod_id)).unwrap();
+        }
+        std::fs::write(
+            dir.path().join("appworkshop_440900.acf"),
+            r#"
+"AppWorkshop"
+{
+    "appid"     "440900"
+    "WorkshopItemsInstalled"
+    {
+        "111"
+        {
+            "size"      "1024"
+        }
+        "333"
+        {
+            "size"      "2048"
+        }
+    }
+}
+"#,
+        )
+        .unwrap();
+
+        let orphans = find_orphaned_folders(dir.path(), Branch::Main, |_| false).unwrap();
+        assert_eq!(orphans, vec![content_path.join("222")]);
+        let orphans = find_orphaned_folders(dir.path(), Branch::Main, |id| id == 222).unwrap();
+        assert!(orphans.is_empty());
+        assert!(
+            find_orphaned_folders(dir.path(), Branch::PublicBeta, |_| false)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn fails_without_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("content/440900/111")).unwrap();
+
+        assert!(find_orphaned_folders(dir.path(), Branch::Main, |_| false).is_err());
+
+        std::fs::write(
+            dir.path().join("appworkshop_440900.acf"),
+            "\"AppWorkshop\" {",
+        )
+        .unwrap();
+        assert!(find_orphaned_folders(dir.path(), Branch::Main, |_| false).is_err());
+    }
+
+    #[test]
+    fn no_mods_without_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(collect_mods(dir.path(), Branch::Main).unwrap().is_empty());
+
+        std::fs::create_dir_all(dir.path().join("content/440900/111")).unwrap();
+        assert!(collect_mods(dir.path(), Branch::Main).unwrap().is_empty());
+    }
+}
@@ -1,8 +1,11 @@
 use std::cell::{RefCell, RefMut};
 use std::collections::HashMap;
 use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::time::{Duration, UNIX_EPOCH};
 
+use anyhow::{anyhow, Result};
 use fltk::app::{self, TimeoutHandle};
 use slog::{debug, o, trace, warn, Logger};
 use steamworks::{
@@ -11,15 +14,17 @@ use steamworks::{
 };
 
 use crate::auth::PlatformUser;
-use crate::game::Branch;
+use crate::game::platform::{ModDirectory, PlatformClient, PlatformTicket, RemoteModInfo};
+use crate::game::{Branch, Mods};
 use crate::logger::IteratorFormatter;
 use crate::Message;
 
-use super::app_id;
+use super::{app_id, SteamModDirectory};
 
 pub struct SteamClient {
     logger: Logger,
     branch: Branch,
+    workshop_path: Option<PathBuf>,
     api: RefCell<Option<SteamAPI>>,
     tx: app::Sender<Message>,
     ticket: RefCell<Option<Rc<SteamTicket>>>,
@@ -43,12 +48,18 @@ struct Downloads {
 }
 
 impl SteamClient {
-    pub(super) fn new(logger: Logger, branch: Branch, tx: app::Sender<Message>) -> Rc<Self> {
+    pub(super) fn new(
+        logger: Logger,
+        branch: Branch,
+        workshop_path: Option<PathBuf>,
+        tx: app::Sender<Message>,
+    ) -> Rc<Self> {
         let logger = logger.new(o!("branch" => format!("{:?}", branch)));
         let callback_timer = Rc::new(RefCell::new(CallbackTimer::new(logger.clone())));
         Rc::new(Self {
             logger,
             branch,
+            workshop_path,
             api: RefCell::new(init_client(branch)),
             tx,
             ticket: RefCell::new(None),
@@ -62,38 +73,8 @@ impl SteamClient {
         self.branch
     }
 
-    pub fn can_launch(&self) -> bool {
-        let client = self.check_client();
-        client.is_some()
-    }
-
-    pub fn can_play_online(&self) -> bool {
-        match self.check_client() {
-            Some(client) => client.user().logged_on(),
-            None => false,
-        }
-    }
-
-    pub fn user(&self) -> Option<PlatformUser> {
-        self.check_client().as_ref().map(|client| PlatformUser {
-            id: client.user().steam_id().raw().to_string(),
-            display_name: client.friends().name(),
-        })
-    }
-
-    pub fn auth_ticket(&self) -> Option<Rc<SteamTicket>> {
-        let mut ticket = self.ticket.borrow_mut();
-        if ticket.is_none() {
-            *ticket = self.check_client().as_ref().and_then(|client| {
-                let user = client.user();
-                if user.logged_on() {
-                    Some(Rc::new(SteamTicket::new(user)))
-                } else {
-                    None
-                }
-            });
-        }
-        ticket.clone()
+    pub fn workshop_path(&self) -> Option<&Path> {
+        self.workshop_path.as_deref()
     }
 
     pub fn query_mods(
@@ -154,13 +135,121 @@ impl SteamClient {
         self.callback_timer.borrow_mut().callback_pending();
     }
 
-    pub fn mod_needs_update(&self, mod_id: u64) -> Option<bool> {
-        self.check_client().map(|client| {
-            client
+    pub fn query_collection(
+        &self,
+        collection_id: u64,
+        callback: impl FnOnce(Result<Vec<u64>>) + 'static,
+    ) {
+        debug!(self.logger, "Querying workshop collection"; "collection_id" => collection_id);
+        let query = match self.check_client() {
+            Some(client) => client
                 .ugc()
-                .item_state(PublishedFileId(mod_id))
-                .contains(ItemState::NEEDS_UPDATE)
-        })
+                .query_item(PublishedFileId(collection_id))
+                .map(|query| query.include_children(true))
+                .map_err(|err| anyhow!("Error creating UGC query: {}", err)),
+            None => Err(anyhow!("Steam not running")),
+        };
+        let query = match query {
+            Ok(query) => query,
+            Err(err) => return callback(Err(err)),
+        };
+
+        let callback = {
+            let callback_timer = Rc::clone(&self.callback_timer);
+            move |result| {
+                callback(result);
+                callback_timer.borrow_mut().callback_completed();
+            }
+        };
+        query.fetch({
+            let logger = self.logger.clone();
+            let callback = CallbackWrapper(callback);
+            move |results| {
+                trace!(logger, "Received collection query results");
+                let result = results
+                    .map_err(|err| anyhow!("Error querying the collection: {}", err))
+                    .and_then(|results| {
+                        results
+                            .get_children(0)
+                            .ok_or_else(|| anyhow!("The workshop item is not a collection"))
+                    })
+                    .map(|children| children.into_iter().map(|id| id.0).collect());
+                callback.call_once(result);
+            }
+        });
+        self.callback_timer.borrow_mut().callback_pending();
+    }
+
+    pub fn query_mod_details(
+        &self,
+        mod_ids: Vec<u64>,
+        callback: impl FnOnce(Result<HashMap<u64, RemoteModInfo>>) + 'static,
+    ) {
+        debug!(
+            self.logger,
+            "Querying mod details";
+            "mod_ids" => %IteratorFormatter(mod_ids.iter())
+        );
+        let file_ids = mod_ids.into_iter().map(PublishedFileId).collect();
+        let query = match self.check_client() {
+            Some(client) => client
+                .ugc()
+                .query_items(file_ids)
+                .map_err(|err| anyhow!("Error creating UGC query: {}", err)),
+            None => Err(anyhow!("Steam not running")),
+        };
+        let query = match query {
+            Ok(query) => query,
+            Err(err) => return callback(Err(err)),
+        };
+
+        let callback = {
+            let callback_timer = Rc::clone(&self.callback_timer);
+            move |result| {
+                callback(result);
+                callback_timer.borrow_mut().callback_completed();
+            }
+        };
+        query.fetch({
+            let logger = self.logger.clone();
+            let callback = CallbackWrapper(callback);
+            move |results| {
+                trace!(logger, "Received mod details query results");
+                let results = match results {
+                    Ok(results) => results,
+                    Err(err) => {
+                        return callback.call_once(Err(anyhow!("Error querying mods: {}", err)))
+                    }
+                };
+                let mut details = HashMap::with_capacity(results.returned_results() as _);
+                for result in results.iter() {
+                    let result = match result {
+                        Some(result) => result,
+                        None => continue,
+                    };
+                    let time_updated = UNIX_EPOCH + Duration::from_secs(result.time_updated as u64);
+                    details.insert(
+                        result.published_file_id.0,
+                        RemoteModInfo {
+                            title: result.title.clone(),
+                            time_updated,
+                        },
+                    );
+                }
+                callback.call_once(Ok(details));
+            }
+        });
+        self.callback_timer.borrow_mut().callback_pending();
+    }
+
+    pub fn mod_needs_update(&self, mod_id: u64) -> Option<bool> {
+        self.mod_item_state(mod_id)
+            .map(|state| state.contains(ItemState::NEEDS_UPDATE))
+    }
+
+    pub fn mod_item_state(&self, mod_id: u64) -> Option<ItemState> {
+        self.check_client()
+            .map(|client| client.ugc().item_state(PublishedFileId(mod_id)))
     }
 
     pub fn start_mod_update(
@@ -191,18 +280,75 @@ impl SteamClient {
         Some(success)
     }
 
+    // Subscribing makes Steam keep the mod installed and up to date. The download is started
+    // right away rather than left for Steam to schedule. The subscription result arrives while
+    // the Steam callbacks are being run, so the download is started from the event loop instead.
+    pub fn subscribe_mod(self: &Rc<Self>, mod_id: u64, callback: DownloadCallback) -> Option<()> {
+        let client = self.check_client()?;
+        let subscribed = CallbackWrapper({
+            let this = Rc::downgrade(self);
+            let callback_timer = Rc::clone(&self.callback_timer);
+            move |result: std::result::Result<(), SteamError>| {
+                callback_timer.borrow_mut().callback_completed();
+                if let Err(err) = result {
+                    return callback(Some(err));
+                }
+                app::add_timeout3(0.0, move |_| {
+                    let started = this
+                        .upgrade()
+                        .and_then(|this| this.start_mod_update(mod_id, Rc::clone(&callback)));
+                    if started != Some(true) {
+                        callback(Some(SteamError::Fail));
+                    }
+                });
+            }
+        });
+        client
+            .ugc()
+            .subscribe_item(PublishedFileId(mod_id), move |result| {
+                subscribed.call_once(result)
+            });
+        drop(client);
+        self.callback_timer.borrow_mut().callback_pending();
+        Some(())
+    }
+
+    // Steam removes the mod files once the game is no longer running.
+    pub fn unsubscribe_mod(&self, mod_id: u64, callback: impl FnOnce(Result<()>) + 'static) {
+        debug!(self.logger, "Unsubscribing from mod"; "mod_id" => mod_id);
+        let client = match self.check_client() {
+            Some(client) => client,
+            None => return callback(Err(anyhow!("Steam not running"))),
+        };
+        let callback = CallbackWrapper({
+            let callback_timer = Rc::clone(&self.callback_timer);
+            move |result: std::result::Result<(), SteamError>| {
+                callback(result.map_err(|err| anyhow!("Error unsubscribing: {}", err)));
+                callback_timer.borrow_mut().callback_completed();
+            }
+        });
+        client
+            .ugc()
+            .unsubscribe_item(PublishedFileId(mod_id), move |result| {
+                callback.call_once(result)
+            });
+        drop(client);
+        self.callback_timer.borrow_mut().callback_pending();
+    }
+
+    pub fn mod_folder(&self, mod_id: u64) -> Option<PathBuf> {
+        self.check_client()?
+            .ugc()
+            .item_install_info(PublishedFileId(mod_id))
+            .map(|info| PathBuf::from(info.folder))
+    }
+
     pub fn download_progress(&self, mod_id: u64) -> Option<(u64, u64)> {
         let file_id = PublishedFileId(mod_id);
         self.check_client()
             .and_then(|client| client.ugc().item_download_info(file_id))
     }
 
-    pub fn run_callbacks(&self) {
-        if let Some(api) = &*self.api.borrow() {
-            api.cb_runner.run_callbacks();
-        }
-    }
-
     fn check_client(&self) -> Option<RefMut<Client>> {
         let mut api = self.api.borrow_mut();
         if api.is_none() {
@@ -226,6 +372,63 @@ impl SteamClient {
     }
 }
 
+impl PlatformClient for SteamClient {
+    fn can_launch(&self) -> bool {
+        let client = self.check_client();
+        client.is_some()
+    }
+
+    fn can_play_online(&self) -> bool {
+        match self.check_client() {
+            Some(client) => client.user().logged_on(),
+            None => false,
+        }
+    }
+
+    fn has_user_accounts(&self) -> bool {
+        true
+    }
+
+    fn user(&self) -> Option<PlatformUser> {
+        self.check_client().as_ref().map(|client| PlatformUser {
+            id: client.user().steam_id().raw().to_string(),
+            display_name: client.friends().name(),
+        })
+    }
+
+    fn auth_ticket(&self) -> Option<Rc<dyn PlatformTicket>> {
+        let mut ticket = self.ticket.borrow_mut();
+        if ticket.is_none() {
+            *ticket = self.check_client().as_ref().and_then(|client| {
+                let user = client.user();
+                if user.logged_on() {
+                    Some(Rc::new(SteamTicket::new(user)))
+                } else {
+                    None
+                }
+            });
+        }
+        ticket
+            .clone()
+            .map(|ticket| ticket as Rc<dyn PlatformTicket>)
+    }
+
+    fn run_callbacks(&self) {
+        if let Some(api) = &*self.api.borrow() {
+            api.cb_runner.run_callbacks();
+        }
+    }
+
+    fn mod_directory(
+        self: Rc<Self>,
+        logger: Logger,
+        tx: app::Sender<Message>,
+        installed_mods: &Mods,
+    ) -> Rc<dyn ModDirectory> {
+        SteamModDirectory::new(logger, self, tx, installed_mods)
+    }
+}
+
 pub struct SteamTicket {
     user: User<ClientManager>,
     ticket: AuthTicket,
@@ -237,8 +440,10 @@ impl SteamTicket {
         let (ticket, data) = user.authentication_session_ticket();
         Self { user, ticket, data }
     }
+}
 
-    pub fn data(&self) -> &[u8] {
+impl PlatformTicket for SteamTicket {
+    fn data(&self) -> &[u8] {
         &self.data
     }
 }
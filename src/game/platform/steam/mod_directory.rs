@@ -1,21 +1,25 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::rc::Rc;
 
 use anyhow::{anyhow, bail, Result};
 use fltk::app;
 use slog::{debug, Logger};
-use steamworks::SteamError;
+use steamworks::{ItemState, SteamError};
 
 use crate::game::platform::steam::client::DownloadCallback;
-use crate::game::platform::{ModDirectory, ModUpdate};
+use crate::game::platform::{
+    CollectionCallback, ModDirectory, ModInstallState, ModUpdate, PlatformClient,
+    RemoteInfoCallback, UnsubscribeCallback,
+};
 use crate::game::{ModInfo, Mods};
 use crate::gui::ServerBrowserUpdate;
 use crate::logger::IteratorFormatter;
 use crate::workers::TaskState;
 use crate::Message;
 
-use super::SteamClient;
+use super::{find_orphaned_folders, SteamClient};
 
 pub struct SteamModDirectory {
     logger: Logger,
@@ -103,34 +107,127 @@ impl ModDirectory for SteamModDirectory {
         let update = Rc::new(SteamModUpdate {
             client: Rc::clone(&self.client),
             mod_id,
+            subscribing: false,
             result: RefCell::new(TaskState::Pending),
         });
-        let success = self.client.start_mod_update(mod_id, {
-            let update = Rc::downgrade(&update);
-            let callback: DownloadCallback = Rc::new(move |result| {
-                if let Some(update) = update.upgrade() {
-                    *update.result.borrow_mut() = TaskState::Ready(result);
-                }
-            });
-            callback
-        });
+        let success = self
+            .client
+            .start_mod_update(mod_id, update_callback(&update));
         if success.ok_or_else(|| anyhow!("Steam not running"))? {
             Ok(update)
         } else {
             bail!("Error starting the mod update download");
         }
     }
+
+    fn query_collection(self: Rc<Self>, collection_id: u64, callback: CollectionCallback) {
+        self.client.query_collection(collection_id, callback);
+    }
+
+    fn subscribe(self: Rc<Self>, mod_id: u64) -> Result<Rc<dyn ModUpdate>> {
+        let update = Rc::new(SteamModUpdate {
+            client: Rc::clone(&self.client),
+            mod_id,
+            subscribing: true,
+            result: RefCell::new(TaskState::Pending),
+        });
+        self.client
+            .subscribe_mod(mod_id, update_callback(&update))
+            .ok_or_else(|| anyhow!("Steam not running"))?;
+        Ok(update)
+    }
+
+    fn mod_pak_paths(self: Rc<Self>, mod_id: u64) -> Result<Vec<PathBuf>> {
+        let folder = self
+            .client
+            .mod_folder(mod_id)
+            .ok_or_else(|| anyhow!("The mod is not installed"))?;
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(folder)? {
+            let path = entry?.path();
+            if path.extension().map(|ext| ext == "pak").unwrap_or_default() {
+                paths.push(path);
+            }
+        }
+        Ok(paths)
+    }
+
+    fn query_remote_info(self: Rc<Self>, mod_ids: Vec<u64>, callback: RemoteInfoCallback) {
+        self.client.query_mod_details(mod_ids, callback);
+    }
+
+    fn unsubscribe(self: Rc<Self>, mod_id: u64, callback: UnsubscribeCallback) {
+        self.client.unsubscribe_mod(mod_id, callback);
+    }
+
+    fn install_state(self: Rc<Self>, mod_info: &ModInfo) -> Result<ModInstallState> {
+        let mod_id = mod_info
+            .steam_file_id(self.client.branch())
+            .ok_or_else(|| anyhow!("Mod does not have a Steam file ID"))?;
+        let state = self
+            .client
+            .mod_item_state(mod_id)
+            .ok_or_else(|| anyhow!("Steam not running"))?;
+
+        Ok(
+            if state.intersects(ItemState::DOWNLOADING | ItemState::DOWNLOAD_PENDING) {
+                ModInstallState::Downloading
+            } else if state.contains(ItemState::NEEDS_UPDATE) {
+                ModInstallState::NeedsUpdate
+            } else if !state.contains(ItemState::INSTALLED) {
+                ModInstallState::NotInstalled
+            } else if !state.contains(ItemState::SUBSCRIBED) {
+                ModInstallState::PendingRemoval
+            } else {
+                ModInstallState::Installed
+            },
+        )
+    }
+
+    fn orphaned_folders(self: Rc<Self>) -> Result<Vec<PathBuf>> {
+        match self.client.workshop_path() {
+            Some(workshop_path) => {
+                find_orphaned_folders(workshop_path, self.client.branch(), |mod_id| {
+                    self.client
+                        .mod_item_state(mod_id)
+                        .map(|state| {
+                            state.intersects(
+                                ItemState::SUBSCRIBED
+                                    | ItemState::DOWNLOADING
+                                    | ItemState::DOWNLOAD_PENDING,
+                            )
+                        })
+                        .unwrap_or_default()
+                })
+            }
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+fn update_callback(update: &Rc<SteamModUpdate>) -> DownloadCallback {
+    let update = Rc::downgrade(update);
+    Rc::new(move |result| {
+        if let Some(update) = update.upgrade() {
+            *update.result.borrow_mut() = TaskState::Ready(result);
+        }
+    })
 }
 
 struct SteamModUpdate {
     client: Rc<SteamClient>,
     mod_id: u64,
+    subscribing: bool,
     result: RefCell<TaskState<Option<SteamError>>>,
 }
 
 impl ModUpdate for SteamModUpdate {
     fn progress(&self) -> Option<(u64, u64)> {
-        self.client.download_progress(self.mod_id)
+        // A new subscription has no download progress until Steam starts the download
+        match self.client.download_progress(self.mod_id) {
+            None if self.subscribing => Some((0, 0)),
+            progress => progress,
+        }
     }
 
     fn state(&self) -> TaskState<Result<()>> {
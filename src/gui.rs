@@ -25,6 +25,7 @@ mod mod_update;
 mod prelude;
 mod server_browser;
 mod single_player;
+mod storage_report;
 pub mod theme;
 mod widgets;
 
@@ -35,6 +36,7 @@ pub use self::mod_manager::{ModManagerAction, ModManagerUpdate};
 pub use self::mod_update::{ModUpdateProgressDialog, ModUpdateSelectionDialog};
 pub use self::server_browser::{ServerBrowserAction, ServerBrowserUpdate};
 pub use self::single_player::{SinglePlayerAction, SinglePlayerUpdate};
+pub use self::storage_report::{ModUsage, OrphanedFolder, StorageReportDialog};
 
 pub enum Action {
     HomeAction(HomeAction),
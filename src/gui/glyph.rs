@@ -4,6 +4,9 @@ pub const FLAG: &str = "\u{1f3f4}";
 pub const EYE: &str = "\u{1f441}";
 pub const HEART: &str = "\u{1f5a4}";
 pub const TOOLS: &str = "\u{1f6e0}";
+pub const NEW: &str = "\u{2605}";
+pub const GONE: &str = "\u{2717}";
+pub const CHANGED: &str = "\u{270e}";
 pub const UNSORTED: &str = "\u{25bd}";
 pub const ASC: &str = "\u{25b2}";
 pub const DESC: &str = "\u{25bc}";
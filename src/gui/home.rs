@@ -127,15 +127,22 @@ impl Home {
             .unwrap()
             .wrap(create_info_label("Conan Exiles Revision:"));
         grid.cell().unwrap().wrap(ReadOnlyText::new({
-            let (revision, snapshot) = game.version();
-            format!("#{}/{} ({})", revision, snapshot, branch_name)
+            match game.version() {
+                Some((revision, snapshot)) => {
+                    format!("#{}/{} ({})", revision, snapshot, branch_name)
+                }
+                None => format!("Unknown ({})", branch_name),
+            }
         }));
         grid.cell()
             .unwrap()
             .wrap(create_info_label("Conan Exiles Build ID:"));
         grid.span(1, 2)
             .unwrap()
-            .wrap(ReadOnlyText::new(format!("{}", game.build_id())));
+            .wrap(ReadOnlyText::new(match game.build_id() {
+                Some(build_id) => build_id.to_string(),
+                None => "Unknown".to_string(),
+            }));
 
         grid.row().add();
         grid.cell()
@@ -14,6 +14,7 @@ use slog::Logger;
 use crate::config::Config;
 use crate::game::platform::ModDirectory;
 use crate::game::Game;
+use crate::servers::PasswordVault;
 
 use super::home::Home;
 use super::main_menu::MainMenu;
@@ -39,6 +40,7 @@ impl LauncherWindow {
         game: Arc<Game>,
         config: &Config,
         mod_resolver: Rc<dyn ModDirectory>,
+        vault: Rc<RefCell<PasswordVault>>,
         log_level_overridden: bool,
         can_switch_branch: bool,
     ) -> Self {
@@ -97,7 +99,8 @@ impl LauncherWindow {
                 logger.clone(),
                 Arc::clone(game.maps()),
                 &config.server_browser,
-                mod_resolver,
+                Rc::clone(&mod_resolver),
+                vault,
                 move |browser_action| on_action.borrow()(Action::ServerBrowser(browser_action)),
             )
         };
@@ -114,6 +117,7 @@ impl LauncherWindow {
             ModManager::new(
                 logger.clone(),
                 Arc::clone(game.installed_mods()),
+                mod_resolver,
                 move |mod_mgr_action| on_action.borrow()(Action::ModManager(mod_mgr_action)),
             )
         };
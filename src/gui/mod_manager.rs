@@ -5,9 +5,12 @@ use std::sync::Arc;
 use bbscope::{BBCode, BBCodeTagConfig};
 use bit_vec::BitVec;
 use fltk::app;
-use fltk::button::Button;
-use fltk::enums::{Align, FrameType, Shortcut};
+use fltk::button::{Button, ToggleButton};
+use fltk::dialog;
+use fltk::enums::{Align, CallbackTrigger, FrameType, Shortcut};
+use fltk::frame::Frame;
 use fltk::group::{Group, Tile};
+use fltk::input::Input;
 use fltk::menu::{MenuButton, MenuFlag};
 use fltk::prelude::*;
 use fltk::table::TableContext;
@@ -18,7 +21,9 @@ use fltk_webview::Webview;
 use lazy_static::lazy_static;
 use slog::{error, Logger};
 
-use crate::game::{ModInfo, ModRef, Mods};
+use crate::game::platform::steam::parse_workshop_id;
+use crate::game::platform::{ModDirectory, ModInstallState};
+use crate::game::{parse_tags, ModInfo, ModRef, ModSource, Mods};
 
 use super::prelude::*;
 use super::widgets::{DataTable, DataTableProperties, DataTableUpdate};
@@ -29,11 +34,20 @@ pub enum ModManagerAction {
     SaveModList(Vec<ModRef>),
     ImportModList,
     ExportModList(Vec<ModRef>),
+    ImportCollection(u64),
+    PinMod(ModRef),
+    UnpinMod(ModRef),
+    SetModTags(ModRef, Vec<String>),
+    Subscribe(ModRef),
+    Unsubscribe(ModRef),
+    ShowStorageReport,
     UpdateMods,
 }
 
 pub enum ModManagerUpdate {
     PopulateModList(Vec<ModRef>),
+    Pinned(anyhow::Result<()>),
+    Unsubscribed(anyhow::Result<()>),
 }
 
 enum Selection {
@@ -41,10 +55,44 @@ enum Selection {
     Active(usize),
 }
 
+#[derive(Default)]
+struct ModFilter {
+    text: String,
+    needs_update: bool,
+    provides_maps: bool,
+    requires_load_on_startup: bool,
+    active: bool,
+}
+
+impl ModFilter {
+    fn matches(&self, mod_info: &ModInfo) -> bool {
+        if (self.needs_update && !mod_info.needs_update())
+            || (self.provides_maps && !mod_info.provides_maps)
+            || (self.requires_load_on_startup && !mod_info.requires_load_on_startup)
+        {
+            return false;
+        }
+
+        let fields = [
+            mod_info.name.to_lowercase(),
+            mod_info.author.to_lowercase(),
+            mod_info.description.to_lowercase(),
+        ];
+        let tags = mod_info.tags();
+        self.text.split_whitespace().all(|word| {
+            let word = word.to_lowercase();
+            fields.iter().any(|field| field.contains(&word))
+                || tags.iter().any(|tag| tag.contains(&word))
+        })
+    }
+}
+
 struct ModListState {
     installed: Arc<Mods>,
     available: Vec<ModRef>,
     active: Vec<ModRef>,
+    shown: Vec<ModRef>,
+    filter: ModFilter,
     selection: Option<Selection>,
 }
 
@@ -54,10 +102,25 @@ impl ModListState {
             installed: mods,
             available: Vec::new(),
             active: Vec::new(),
+            shown: Vec::new(),
+            filter: ModFilter::default(),
             selection: None,
         }
     }
 
+    // The mods listed in the available table: the inactive mods, or the active ones if the filter
+    // asks for them, that match the filter.
+    fn filtered_mods(&self) -> Vec<ModRef> {
+        let mods = if self.filter.active { &self.active } else { &self.available };
+        mods.iter()
+            .filter(|mod_ref| match self.installed.get(mod_ref) {
+                Some(mod_info) => self.filter.matches(mod_info),
+                None => false,
+            })
+            .cloned()
+            .collect()
+    }
+
     fn get_selected_available(&self) -> Option<usize> {
         if let Some(Selection::Available(idx)) = self.selection {
             Some(idx)
@@ -77,17 +140,26 @@ impl ModListState {
     fn selected_mod_info(&self) -> Option<&ModInfo> {
         match self.selection {
             None => None,
-            Some(Selection::Available(idx)) => self.installed.get(&self.available[idx]),
+            Some(Selection::Available(idx)) => self.installed.get(&self.shown[idx]),
             Some(Selection::Active(idx)) => self.installed.get(&self.active[idx]),
         }
     }
+
+    fn selected_mod_ref(&self) -> Option<&ModRef> {
+        match self.selection {
+            None => None,
+            Some(Selection::Available(idx)) => Some(&self.shown[idx]),
+            Some(Selection::Active(idx)) => Some(&self.active[idx]),
+        }
+    }
 }
 
-type ModRow = [String; 3];
+type ModRow = [String; 5];
 
 pub(super) struct ModManager {
     logger: Logger,
-    tiles: Tile,
+    root: Group,
+    mod_directory: Rc<dyn ModDirectory>,
     on_action: Box<dyn Handler<ModManagerAction>>,
     available_list: DataTable<ModRow>,
     active_list: DataTable<ModRow>,
@@ -98,6 +170,9 @@ pub(super) struct ModManager {
     move_down_button: Button,
     move_bottom_button: Button,
     more_info_button: MenuButton,
+    pin_button: ToggleButton,
+    tags_button: Button,
+    workshop_button: MenuButton,
     update_mods_button: Button,
     state: RefCell<ModListState>,
 }
@@ -106,8 +181,34 @@ impl ModManager {
     pub fn new(
         logger: Logger,
         mods: Arc<Mods>,
+        mod_directory: Rc<dyn ModDirectory>,
         on_action: impl Handler<ModManagerAction> + 'static,
     ) -> Rc<Self> {
+        let mut root = Grid::builder_with_factory(wrapper_factory())
+            .with_col_spacing(10)
+            .with_row_spacing(10);
+        root.col().with_default_align(CellAlign::End).add();
+        root.col().with_stretch(1).add();
+        root.col().add();
+
+        root.row().add();
+        root.cell()
+            .unwrap()
+            .wrap(Frame::default())
+            .with_label("Search:");
+        let mut search_input = root
+            .cell()
+            .unwrap()
+            .wrap(Input::default())
+            .with_tooltip("Search the available mods by name, author, description and tags");
+        let mut filter_button = root
+            .cell()
+            .unwrap()
+            .wrap(MenuButton::default())
+            .with_label("Filters")
+            .with_tooltip("Only show the available mods that satisfy all the checked conditions");
+
+        root.row().with_stretch(1).add();
         let mut grid = GridBuilder::with_factory(Tile::default_fill(), wrapper_factory());
         grid.row().with_stretch(1).add();
 
@@ -118,9 +219,11 @@ impl ModManager {
         grid.col().with_stretch(1).add();
         let mut available_list = DataTable::default().with_properties(DataTableProperties {
             columns: vec![
-                ("Available Mods", Align::Left).into(),
+                (TXT_AVAILABLE_MODS, Align::Left).into(),
                 ("Version", Align::Left).into(),
                 ("Author", Align::Left).into(),
+                ("State", Align::Left).into(),
+                ("Tags", Align::Left).into(),
             ],
             cell_padding: 4,
             cell_selection_color: fltk::enums::Color::Free,
@@ -173,6 +276,13 @@ impl ModManager {
             .with_label("@filesave")
             .with_tooltip("Export the mod list into a file");
         button_grid.row().add();
+        let mut import_collection_button = button_grid
+            .cell()
+            .unwrap()
+            .wrap(Button::default())
+            .with_label("@+")
+            .with_tooltip("Import the mod list from a Steam Workshop collection");
+        button_grid.row().add();
         button_grid.cell().unwrap().with_top_padding(8).skip();
         button_grid.row().add();
         let mut activate_button = button_grid
@@ -229,6 +339,30 @@ impl ModManager {
             .with_tooltip("Show information about the selected mod");
         more_info_button.deactivate();
         button_grid.row().add();
+        let mut pin_button = button_grid
+            .cell()
+            .unwrap()
+            .wrap(ToggleButton::default())
+            .with_label("\u{1f4cc}")
+            .with_tooltip("Pin the selected mod to its installed revision");
+        pin_button.deactivate();
+        button_grid.row().add();
+        let mut tags_button = button_grid
+            .cell()
+            .unwrap()
+            .wrap(Button::default())
+            .with_label("\u{1f3f7}")
+            .with_tooltip("Edit the tags of the selected mod");
+        tags_button.deactivate();
+        button_grid.row().add();
+        let mut workshop_button = button_grid
+            .cell()
+            .unwrap()
+            .wrap(MenuButton::default())
+            .with_label("\u{2601}")
+            .with_tooltip("Manage the Steam Workshop subscription of the selected mod");
+        workshop_button.deactivate();
+        button_grid.row().add();
         button_grid.cell().unwrap().with_top_padding(8).skip();
         button_grid.row().add();
         let mut update_mods_button = button_grid
@@ -238,6 +372,13 @@ impl ModManager {
             .with_label("@reload")
             .with_tooltip("Update outdated mods");
         update_mods_button.deactivate();
+        button_grid.row().add();
+        let mut storage_button = button_grid
+            .cell()
+            .unwrap()
+            .wrap(Button::default())
+            .with_label("\u{1f4be}")
+            .with_tooltip("Show the disk space used by mods and clean up orphaned mod folders");
 
         button_grid.row().with_stretch(1).add();
         button_grid.cell().unwrap().skip();
@@ -258,6 +399,8 @@ impl ModManager {
                 ("Active Mods", Align::Left).into(),
                 ("Version", Align::Left).into(),
                 ("Author", Align::Left).into(),
+                ("State", Align::Left).into(),
+                ("Tags", Align::Left).into(),
             ],
             cell_padding: 4,
             cell_selection_color: fltk::enums::Color::Free,
@@ -280,10 +423,21 @@ impl ModManager {
         let grid = grid.end();
         grid.layout_children(); // necessary for Tile
 
+        let mut tiles = grid.group();
+        root.span(1, 3)
+            .unwrap()
+            .with_vert_align(CellAlign::Stretch)
+            .add(grid);
+
+        let root = root.end();
+        root.layout_children();
+
+        let mut root = root.group();
+        root.hide();
+
         adjust_col_widths(&mut available_list);
         adjust_col_widths(&mut active_list);
 
-        let mut tiles = grid.group();
         tile_limits.resize(
             tiles.x() + button_col.width() * 2,
             tiles.y(),
@@ -291,7 +445,6 @@ impl ModManager {
             tiles.height(),
         );
         tiles.resizable(&tile_limits);
-        tiles.hide();
 
         let left_tile = available_list.as_base_widget();
         let mut mid_tile = button_col;
@@ -323,7 +476,8 @@ impl ModManager {
 
         let manager = Rc::new(Self {
             logger,
-            tiles,
+            root,
+            mod_directory,
             on_action: Box::new(on_action),
             available_list: available_list.clone(),
             active_list: active_list.clone(),
@@ -334,6 +488,9 @@ impl ModManager {
             move_down_button: move_down_button.clone(),
             move_bottom_button: move_bottom_button.clone(),
             more_info_button: more_info_button.clone(),
+            pin_button: pin_button.clone(),
+            tags_button: tags_button.clone(),
+            workshop_button: workshop_button.clone(),
             update_mods_button: update_mods_button.clone(),
             state: RefCell::new(ModListState::new(mods)),
         });
@@ -364,13 +521,17 @@ impl ModManager {
         clear_button.set_callback(manager.weak_cb(Self::clear_clicked));
         import_button.set_callback(manager.weak_cb(Self::import_clicked));
         export_button.set_callback(manager.weak_cb(Self::export_clicked));
+        import_collection_button.set_callback(manager.weak_cb(Self::import_collection_clicked));
         activate_button.set_callback(manager.weak_cb(Self::activate_clicked));
         deactivate_button.set_callback(manager.weak_cb(Self::deactivate_clicked));
         move_top_button.set_callback(manager.weak_cb(Self::move_top_clicked));
         move_up_button.set_callback(manager.weak_cb(Self::move_up_clicked));
         move_down_button.set_callback(manager.weak_cb(Self::move_down_clicked));
         move_bottom_button.set_callback(manager.weak_cb(Self::move_bottom_clicked));
+        pin_button.set_callback(manager.weak_cb(Self::pin_clicked));
+        tags_button.set_callback(manager.weak_cb(Self::tags_clicked));
         update_mods_button.set_callback(manager.weak_cb(Self::update_mods_clicked));
+        storage_button.set_callback(manager.weak_cb(Self::storage_clicked));
 
         more_info_button.add(
             "Description",
@@ -385,12 +546,60 @@ impl ModManager {
             manager.weak_cb(Self::show_change_notes),
         );
 
+        search_input.set_trigger(CallbackTrigger::Changed);
+        search_input.set_callback({
+            let this = Rc::downgrade(&manager);
+            move |input| {
+                if let Some(this) = this.upgrade() {
+                    this.state.borrow_mut().filter.text = input.value();
+                    this.apply_filter();
+                }
+            }
+        });
+
+        let filters: [(&str, fn(&mut ModFilter) -> &mut bool); 4] = [
+            ("Needs Update", |filter| &mut filter.needs_update),
+            ("Provides Maps", |filter| &mut filter.provides_maps),
+            ("Requires Load on Startup", |filter| {
+                &mut filter.requires_load_on_startup
+            }),
+            ("Active", |filter| &mut filter.active),
+        ];
+        for (label, flag) in filters {
+            filter_button.add(
+                label,
+                Shortcut::None,
+                MenuFlag::Toggle,
+                manager.weak_cb(move |this| {
+                    {
+                        let mut state = this.state.borrow_mut();
+                        let flag = flag(&mut state.filter);
+                        *flag = !*flag;
+                    }
+                    this.apply_filter();
+                }),
+            );
+        }
+
+        workshop_button.add(
+            "Subscribe",
+            Shortcut::None,
+            MenuFlag::Normal,
+            manager.weak_cb(Self::subscribe_clicked),
+        );
+        workshop_button.add(
+            "Unsubscribe",
+            Shortcut::None,
+            MenuFlag::Normal,
+            manager.weak_cb(Self::unsubscribe_clicked),
+        );
+
         manager
     }
 
     pub fn show(&self) -> CleanupFn {
-        let mut tiles = self.tiles.clone();
-        tiles.show();
+        let mut root = self.root.clone();
+        root.show();
 
         if let Err(err) = (self.on_action)(ModManagerAction::LoadModList) {
             error!(self.logger, "Error loading mod list"; "error" => %err);
@@ -398,13 +607,29 @@ impl ModManager {
         }
 
         Box::new(move || {
-            tiles.hide();
+            root.hide();
         })
     }
 
     pub fn handle_update(&self, update: ModManagerUpdate) {
         match update {
             ModManagerUpdate::PopulateModList(active_mods) => self.populate_state(active_mods),
+            ModManagerUpdate::Pinned(result) => {
+                if let Err(err) = result {
+                    error!(self.logger, "Error pinning mod"; "error" => %err);
+                    alert_error(ERR_PINNING_MOD, &err);
+                }
+                self.populate_tables();
+                self.update_actions();
+            }
+            ModManagerUpdate::Unsubscribed(result) => {
+                if let Err(err) = result {
+                    error!(self.logger, "Error unsubscribing from mod"; "error" => %err);
+                    alert_error(ERR_UNSUBSCRIBING, &err);
+                }
+                self.populate_tables();
+                self.update_actions();
+            }
         }
     }
 
@@ -437,24 +662,72 @@ impl ModManager {
     }
 
     fn populate_tables(&self) {
-        let state = self.state.borrow();
+        let mut state = self.state.borrow_mut();
+        let filter_changed = self.update_shown(&mut state);
         self.update_mods_button.clone().set_activated(
             state
                 .installed
                 .iter()
-                .any(|mod_info| mod_info.needs_update()),
+                .any(|mod_info| mod_info.needs_update() && !mod_info.is_pinned()),
         );
 
         populate_table(
             &mut self.available_list.clone(),
             &state.installed,
-            &state.available,
+            &state.shown,
+            &self.mod_directory,
         );
         populate_table(
             &mut self.active_list.clone(),
             &state.installed,
             &state.active,
+            &self.mod_directory,
+        );
+        drop(state);
+
+        if filter_changed {
+            self.update_actions();
+        }
+    }
+
+    fn apply_filter(&self) {
+        let mut state = self.state.borrow_mut();
+        if !self.update_shown(&mut state) {
+            return;
+        }
+        populate_table(
+            &mut self.available_list.clone(),
+            &state.installed,
+            &state.shown,
+            &self.mod_directory,
         );
+        drop(state);
+
+        self.update_actions();
+    }
+
+    // Returns whether the mods shown in the available table changed, in which case the selection
+    // in that table no longer applies.
+    fn update_shown(&self, state: &mut ModListState) -> bool {
+        // With the "Active" filter, the table lists active mods, which cannot be activated again
+        let header =
+            if state.filter.active { TXT_FILTERED_ACTIVE_MODS } else { TXT_AVAILABLE_MODS };
+        let props = self.available_list.properties();
+        if props.borrow().columns[0].header != header {
+            props.borrow_mut().columns[0].header = header.to_string();
+            self.available_list.updated(DataTableUpdate::PROPERTIES);
+        }
+
+        let shown = state.filtered_mods();
+        if shown == state.shown {
+            return false;
+        }
+        state.shown = shown;
+        if let Some(Selection::Available(_)) = state.selection {
+            state.selection = None;
+            self.available_list.clone().unset_selection();
+        }
+        true
     }
 
     fn available_clicked(&self) {
@@ -490,7 +763,7 @@ impl ModManager {
         let state = self.state.borrow();
         let (activate, deactivate, move_up, move_down, more_info) = match state.selection {
             None => (false, false, false, false, false),
-            Some(Selection::Available(_)) => (true, false, false, false, true),
+            Some(Selection::Available(_)) => (!state.filter.active, false, false, false, true),
             Some(Selection::Active(idx)) => {
                 let last_idx = state.active.len() - 1;
                 (false, true, idx > 0, idx < last_idx, true)
@@ -504,6 +777,38 @@ impl ModManager {
         self.move_down_button.clone().set_activated(move_down);
         self.move_bottom_button.clone().set_activated(move_down);
         self.more_info_button.clone().set_activated(more_info);
+
+        self.tags_button
+            .clone()
+            .set_activated(state.selected_mod_info().is_some());
+
+        let mut pin_button = self.pin_button.clone();
+        match state.selected_mod_info() {
+            Some(mod_info) => {
+                pin_button.activate();
+                pin_button.set_value(mod_info.is_pinned());
+            }
+            None => {
+                pin_button.deactivate();
+                pin_button.set_value(false);
+            }
+        }
+
+        let install_state = state
+            .selected_mod_info()
+            .filter(|mod_info| mod_info.source == ModSource::Platform)
+            .and_then(|mod_info| Rc::clone(&self.mod_directory).install_state(mod_info).ok());
+        let workshop_button = &self.workshop_button;
+        let (subscribe, unsubscribe) = match install_state {
+            None => (false, false),
+            Some(ModInstallState::NotInstalled | ModInstallState::PendingRemoval) => (true, false),
+            Some(_) => (false, true),
+        };
+        set_item_activated(workshop_button, "Subscribe", subscribe);
+        set_item_activated(workshop_button, "Unsubscribe", unsubscribe);
+        workshop_button
+            .clone()
+            .set_activated(subscribe || unsubscribe);
     }
 
     fn clear_clicked(&self) {
@@ -530,12 +835,31 @@ impl ModManager {
         }
     }
 
+    fn import_collection_clicked(&self) {
+        let input = match dialog::input_default(PROMPT_COLLECTION, "") {
+            Some(input) if !input.trim().is_empty() => input,
+            _ => return,
+        };
+        let collection_id = match parse_workshop_id(&input) {
+            Some(id) => id,
+            None => {
+                dialog::alert_default(ERR_INVALID_COLLECTION);
+                return;
+            }
+        };
+        if let Err(err) = (self.on_action)(ModManagerAction::ImportCollection(collection_id)) {
+            error!(self.logger, "Error importing workshop collection"; "error" => %err);
+            alert_error(ERR_IMPORTING_COLLECTION, &err);
+        }
+    }
+
     fn activate_clicked(&self) {
         let mut state = self.state.borrow_mut();
         let row_idx = state.get_selected_available().unwrap();
 
-        let mod_idx = state.available.remove(row_idx);
-        state.active.push(mod_idx);
+        let mod_ref = state.shown.remove(row_idx);
+        state.available.retain(|available| available != &mod_ref);
+        state.active.push(mod_ref);
 
         let row = mutate_table(&mut self.available_list.clone(), |data| {
             data.remove(row_idx)
@@ -559,16 +883,13 @@ impl ModManager {
                 .binary_search_by_key(mod_idx, |mod_ref| mod_ref.to_index().unwrap())
                 .unwrap_err();
             state.available.insert(dest_row_idx, mod_ref);
-
-            let row = mutate_table(&mut self.active_list.clone(), |data| data.remove(row_idx));
-            mutate_table(&mut self.available_list.clone(), |data| {
-                data.insert(dest_row_idx, row)
-            });
         }
+        mutate_table(&mut self.active_list.clone(), |data| data.remove(row_idx));
 
         drop(state);
 
         self.set_selection(None);
+        self.apply_filter();
         self.save_current_mod_list();
     }
 
@@ -587,6 +908,7 @@ impl ModManager {
         drop(state);
 
         self.set_selection(Some(Selection::Active(0)));
+        self.apply_filter();
         self.save_current_mod_list();
     }
 
@@ -603,6 +925,7 @@ impl ModManager {
         drop(state);
 
         self.set_selection(Some(Selection::Active(row_idx - 1)));
+        self.apply_filter();
         self.save_current_mod_list();
     }
 
@@ -619,6 +942,7 @@ impl ModManager {
         drop(state);
 
         self.set_selection(Some(Selection::Active(row_idx + 1)));
+        self.apply_filter();
         self.save_current_mod_list();
     }
 
@@ -636,14 +960,84 @@ impl ModManager {
         drop(state);
 
         self.set_selection(Some(Selection::Active(last_idx)));
+        self.apply_filter();
         self.save_current_mod_list();
     }
 
+    fn pin_clicked(&self) {
+        let state = self.state.borrow();
+        let mod_ref = state.selected_mod_ref().unwrap().clone();
+        let pinned = self.pin_button.value();
+        let action = if pinned {
+            ModManagerAction::PinMod(mod_ref)
+        } else {
+            ModManagerAction::UnpinMod(mod_ref)
+        };
+        if let Err(err) = (self.on_action)(action) {
+            error!(self.logger, "Error pinning mod"; "pinned" => pinned, "error" => %err);
+            alert_error(ERR_PINNING_MOD, &err);
+            self.pin_button.clone().set_value(!pinned);
+        } else if pinned {
+            // The pak is backed up in the background, the tables are refreshed once it is pinned
+            self.pin_button.clone().deactivate();
+            return;
+        }
+        drop(state);
+        self.populate_tables();
+    }
+
+    fn tags_clicked(&self) {
+        let state = self.state.borrow();
+        let mod_ref = state.selected_mod_ref().unwrap().clone();
+        let tags = state.selected_mod_info().unwrap().tags().join(", ");
+        drop(state);
+
+        let input = match dialog::input_default(PROMPT_TAGS, &tags) {
+            Some(input) => input,
+            None => return,
+        };
+        if let Err(err) =
+            (self.on_action)(ModManagerAction::SetModTags(mod_ref, parse_tags(&input)))
+        {
+            error!(self.logger, "Error saving mod tags"; "error" => %err);
+            alert_error(ERR_SAVING_TAGS, &err);
+        }
+        self.populate_tables();
+    }
+
+    fn subscribe_clicked(&self) {
+        let mod_ref = self.state.borrow().selected_mod_ref().unwrap().clone();
+        if let Err(err) = (self.on_action)(ModManagerAction::Subscribe(mod_ref)) {
+            error!(self.logger, "Error subscribing to mod"; "error" => %err);
+            alert_error(ERR_SUBSCRIBING, &err);
+        }
+        self.populate_tables();
+        self.update_actions();
+    }
+
+    fn unsubscribe_clicked(&self) {
+        if !prompt_confirm(PROMPT_UNSUBSCRIBE) {
+            return;
+        }
+        let mod_ref = self.state.borrow().selected_mod_ref().unwrap().clone();
+        if let Err(err) = (self.on_action)(ModManagerAction::Unsubscribe(mod_ref)) {
+            error!(self.logger, "Error unsubscribing from mod"; "error" => %err);
+            alert_error(ERR_UNSUBSCRIBING, &err);
+        }
+    }
+
     fn update_mods_clicked(&self) {
         (self.on_action)(ModManagerAction::UpdateMods).unwrap();
         self.populate_tables();
     }
 
+    fn storage_clicked(&self) {
+        if let Err(err) = (self.on_action)(ModManagerAction::ShowStorageReport) {
+            error!(self.logger, "Error creating the storage report"; "error" => %err);
+            alert_error(ERR_STORAGE_REPORT, &err);
+        }
+    }
+
     fn save_current_mod_list(&self) {
         let state = self.state.borrow();
         self.save_mod_list(state.active.clone());
@@ -663,7 +1057,7 @@ impl ModManager {
     fn show_description(&self) {
         let state = self.state.borrow();
         let mod_info = state.selected_mod_info().unwrap();
-        self.show_bbcode(
+        show_bbcode(
             &format!("Description: {}", &mod_info.name),
             &mod_info.description,
         );
@@ -672,36 +1066,27 @@ impl ModManager {
     fn show_change_notes(&self) {
         let state = self.state.borrow();
         let mod_info = state.selected_mod_info().unwrap();
-        self.show_bbcode(
+        show_bbcode(
             &format!("Change Notes: {}", &mod_info.name),
             &mod_info.change_notes,
         );
     }
-
-    fn show_bbcode(&self, title: &str, content: &str) {
-        let mut html = BBCODE.parse(content);
-        html = format!(
-            "<html><head><style>{}</style></head><body>{}</body></html",
-            CSS_INFO_BODY, html
-        );
-        html = urlencoding::encode(&html).to_string();
-
-        let mut popup = Window::default().with_label(title).with_size(800, 600);
-        popup.make_modal(true);
-        popup.make_resizable(true);
-        popup.end();
-        popup.show();
-
-        let webview = Webview::create(false, &mut popup);
-        webview.set_html(&html);
-
-        while popup.shown() {
-            app::wait();
-        }
-    }
 }
 
+const TXT_AVAILABLE_MODS: &str = "Available Mods";
+const TXT_FILTERED_ACTIVE_MODS: &str = "Active Mods (uncheck the \"Active\" filter to add mods)";
 const PROMPT_CLEAR_MODS: &str = "Are you sure you want to clear the mod list?";
+const PROMPT_COLLECTION: &str = "Enter the ID or the URL of the Steam Workshop collection:";
+const ERR_INVALID_COLLECTION: &str = "This is not a valid Steam Workshop collection ID or URL.";
+const ERR_IMPORTING_COLLECTION: &str = "Error while importing the Steam Workshop collection.";
+const PROMPT_UNSUBSCRIBE: &str =
+    "Are you sure you want to unsubscribe from the mod? Steam will remove it from your computer.";
+const ERR_SUBSCRIBING: &str = "Error while subscribing to the mod.";
+const ERR_UNSUBSCRIBING: &str = "Error while unsubscribing from the mod.";
+const ERR_STORAGE_REPORT: &str = "Error while determining the disk usage of mods.";
+const ERR_PINNING_MOD: &str = "Error while pinning the mod.";
+const PROMPT_TAGS: &str = "Enter the tags for the mod, separated by commas:";
+const ERR_SAVING_TAGS: &str = "Error while saving the mod tags.";
 const ERR_LOADING_MOD_LIST: &str = "Error while loading the mod list.";
 const ERR_SAVING_MOD_LIST: &str = "Error while saving the mod list.";
 const CSS_INFO_BODY: &str = include_str!("mod_info.css");
@@ -710,34 +1095,84 @@ lazy_static! {
     static ref BBCODE: BBCode = BBCode::from_config(BBCodeTagConfig::extended(), None).unwrap();
 }
 
+pub(super) fn show_bbcode(title: &str, content: &str) {
+    let mut html = BBCODE.parse(content);
+    html = format!(
+        "<html><head><style>{}</style></head><body>{}</body></html",
+        CSS_INFO_BODY, html
+    );
+    html = urlencoding::encode(&html).to_string();
+
+    let mut popup = Window::default().with_label(title).with_size(800, 600);
+    popup.make_modal(true);
+    popup.make_resizable(true);
+    popup.end();
+    popup.show();
+
+    let webview = Webview::create(false, &mut popup);
+    webview.set_html(&html);
+
+    while popup.shown() {
+        app::wait();
+    }
+}
+
 fn adjust_col_widths(table: &mut DataTable<ModRow>) {
     let scrollbar_width = table.scrollbar_size();
     let scrollbar_width =
         if scrollbar_width > 0 { scrollbar_width } else { fltk::app::scrollbar_size() };
 
-    let width = table.width() - table.col_width(1) - table.col_width(2) - scrollbar_width - 2;
+    let width = table.width()
+        - table.col_width(1)
+        - table.col_width(2)
+        - table.col_width(3)
+        - table.col_width(4)
+        - scrollbar_width
+        - 2;
     table.set_col_width(0, width);
 }
 
-fn populate_table(table: &DataTable<ModRow>, mods: &Mods, refs: &Vec<ModRef>) {
+fn populate_table(
+    table: &DataTable<ModRow>,
+    mods: &Mods,
+    refs: &Vec<ModRef>,
+    mod_directory: &Rc<dyn ModDirectory>,
+) {
     let rows = table.data();
     let mut rows = rows.borrow_mut();
     rows.clear();
 
     for mod_ref in refs {
-        rows.push(make_mod_row(&mods, mod_ref));
+        rows.push(make_mod_row(&mods, mod_ref, mod_directory));
     }
     drop(rows);
 
     table.updated(DataTableUpdate::DATA);
 }
 
-fn make_mod_row(mods: &Mods, mod_ref: &ModRef) -> ModRow {
+fn make_mod_row(mods: &Mods, mod_ref: &ModRef, mod_directory: &Rc<dyn ModDirectory>) -> ModRow {
     if let Some(mod_info) = mods.get(mod_ref) {
         let version = mod_info.version.to_string();
-        let version =
-            if mod_info.needs_update() { format!("@reload {}", version) } else { version };
-        [mod_info.name.clone(), version, mod_info.author.clone()]
+        let version = match (mod_info.is_pinned(), mod_info.needs_update()) {
+            (true, true) => format!("{} (pinned, outdated)", version),
+            (true, false) => format!("{} (pinned)", version),
+            (false, true) => format!("@reload {}", version),
+            (false, false) => version,
+        };
+        let install_state = match mod_info.source {
+            ModSource::Local => "Local",
+            ModSource::Platform => match Rc::clone(mod_directory).install_state(mod_info) {
+                Ok(install_state) => install_state_text(install_state),
+                Err(_) => "",
+            },
+        };
+        [
+            mod_info.name.clone(),
+            version,
+            mod_info.author.clone(),
+            install_state.to_string(),
+            mod_info.tags().join(", "),
+        ]
     } else {
         [
             match mod_ref {
@@ -747,10 +1182,32 @@ fn make_mod_row(mods: &Mods, mod_ref: &ModRef) -> ModRow {
             },
             "???".to_string(),
             "???".to_string(),
+            "".to_string(),
+            "".to_string(),
         ]
     }
 }
 
+fn install_state_text(install_state: ModInstallState) -> &'static str {
+    match install_state {
+        ModInstallState::NotInstalled => "Not installed",
+        ModInstallState::Installed => "Installed",
+        ModInstallState::Downloading => "Downloading",
+        ModInstallState::NeedsUpdate => "Needs update",
+        ModInstallState::PendingRemoval => "Pending removal",
+    }
+}
+
+fn set_item_activated(menu: &MenuButton, name: &str, activated: bool) {
+    if let Some(mut item) = menu.find_item(name) {
+        if activated {
+            item.activate();
+        } else {
+            item.deactivate();
+        }
+    }
+}
+
 fn mutate_table<R>(table: &DataTable<ModRow>, mutator: impl FnOnce(&mut Vec<ModRow>) -> R) -> R {
     let data = table.data();
     let mut data = data.borrow_mut();
@@ -759,3 +1216,86 @@ fn mutate_table<R>(table: &DataTable<ModRow>, mutator: impl FnOnce(&mut Vec<ModR
     table.updated(DataTableUpdate::DATA);
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use crate::game::{test_mod, test_mods, ModRef};
+
+    use super::ModListState;
+
+    fn test_state() -> ModListState {
+        let mut map_mod = test_mod(json!({
+            "name": "Savage Wilds",
+            "author": "Funcom",
+            "folderName": "SavageWilds",
+        }));
+        map_mod.provides_maps = true;
+        let tagged_mod = test_mod(json!({
+            "name": "Pippi",
+            "description": "Admin tools and more",
+            "folderName": "Pippi",
+        }));
+        tagged_mod.set_tags(vec!["admin".to_string(), "qol".to_string()]);
+        let outdated_mod = test_mod(json!({
+            "name": "Emberlight",
+            "description": "Crafting overhaul",
+            "bRequiresLoadOnStartup": true,
+            "folderName": "Emberlight",
+        }));
+        outdated_mod.set_needs_update(true);
+
+        let mods = test_mods(vec![map_mod, tagged_mod, outdated_mod]);
+        let mut state = ModListState::new(Arc::new(mods));
+        state.available = vec![ModRef::Installed(0), ModRef::Installed(1)];
+        state.active = vec![ModRef::Installed(2)];
+        state
+    }
+
+    fn filtered(state: &ModListState) -> Vec<usize> {
+        state
+            .filtered_mods()
+            .iter()
+            .filter_map(ModRef::to_index)
+            .collect()
+    }
+
+    #[test]
+    fn searches_names_authors_descriptions_and_tags() {
+        let mut state = test_state();
+        assert_eq!(filtered(&state), [0, 1]);
+
+        for (text, expected) in [
+            ("savage", &[0][..]),
+            ("FUNCOM", &[0]),
+            ("tools", &[1]),
+            ("qol admin", &[1]),
+            ("qol savage", &[]),
+            ("crafting", &[]),
+        ] {
+            state.filter.text = text.to_string();
+            assert_eq!(filtered(&state), expected, "searching for {:?}", text);
+        }
+    }
+
+    #[test]
+    fn filters_by_flags() {
+        let mut state = test_state();
+        state.filter.provides_maps = true;
+        assert_eq!(filtered(&state), [0]);
+
+        state.filter.provides_maps = false;
+        state.filter.needs_update = true;
+        assert!(filtered(&state).is_empty());
+
+        state.filter.active = true;
+        assert_eq!(filtered(&state), [2]);
+        state.filter.requires_load_on_startup = true;
+        assert_eq!(filtered(&state), [2]);
+        state.filter.provides_maps = true;
+        assert!(filtered(&state).is_empty());
+    }
+}
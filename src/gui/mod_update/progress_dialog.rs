@@ -12,7 +12,7 @@ use fltk_float::SimpleWrapper;
 use humansize::SizeFormatter;
 
 use crate::game::platform::{ModDirectory, ModUpdate};
-use crate::game::{ModInfo, ModRef, Mods};
+use crate::game::{ModRef, Mods};
 use crate::gui::widgets::{draw_table_cell, DataTable, DataTableProperties, DataTableUpdate};
 use crate::gui::wrapper_factory;
 use crate::workers::TaskState;
@@ -47,9 +47,7 @@ struct ProgressRow {
 }
 
 impl ProgressRow {
-    fn new(mod_info: &ModInfo, mod_directory: Rc<dyn ModDirectory>) -> Self {
-        let name = mod_info.name.clone();
-        let update = mod_directory.start_update(mod_info);
+    fn new(name: String, update: Result<Rc<dyn ModUpdate>>) -> Self {
         let (status, display_text) = match &update {
             Ok(_) => (ProgressStatus::Pending, name.clone()),
             Err(_) => (ProgressStatus::Error, format!("{} [error]", &name)),
@@ -105,9 +103,30 @@ impl ModUpdateProgressDialog {
         mods_to_update: Vec<ModRef>,
         mod_directory: Rc<dyn ModDirectory>,
     ) -> Self {
-        let mut window = Window::default()
-            .with_size(480, 480)
-            .with_label("Updating Mods");
+        let rows = mods_to_update
+            .iter()
+            .map(|mod_ref| {
+                let mod_info = mods.get(mod_ref).unwrap();
+                let update = Rc::clone(&mod_directory).start_update(mod_info);
+                ProgressRow::new(mod_info.name.clone(), update)
+            })
+            .collect();
+        Self::create(parent, "Updating Mods", MSG_UPDATING_MODS, rows)
+    }
+
+    pub fn for_downloads(
+        parent: &Window,
+        downloads: Vec<(String, Result<Rc<dyn ModUpdate>>)>,
+    ) -> Self {
+        let rows = downloads
+            .into_iter()
+            .map(|(name, update)| ProgressRow::new(name, update))
+            .collect();
+        Self::create(parent, "Downloading Mods", MSG_DOWNLOADING_MODS, rows)
+    }
+
+    fn create(parent: &Window, title: &str, message: &str, rows: Vec<ProgressRow>) -> Self {
+        let mut window = Window::default().with_size(480, 480).with_label(title);
 
         let mut grid = GridBuilder::with_factory(window.clone(), wrapper_factory())
             .with_col_spacing(10)
@@ -119,7 +138,7 @@ impl ModUpdateProgressDialog {
         grid.cell()
             .unwrap()
             .wrap(Frame::default_fill())
-            .with_label(MSG_UPDATING_MODS);
+            .with_label(message);
 
         grid.row()
             .with_stretch(1)
@@ -138,13 +157,7 @@ impl ModUpdateProgressDialog {
         progress_table.set_col_header(true);
         progress_table.set_col_resize(true);
         progress_table.end();
-        let data = progress_table.data();
-        let mut data = data.borrow_mut();
-        for mod_ref in mods_to_update.iter() {
-            let mod_info = mods.get(mod_ref).unwrap();
-            data.push(ProgressRow::new(mod_info, Rc::clone(&mod_directory)));
-        }
-        drop(data);
+        *progress_table.data().borrow_mut() = rows;
         progress_table.updated(DataTableUpdate::DATA);
         grid.cell().unwrap().add(SimpleWrapper::new(
             progress_table.as_base_widget(),
@@ -208,6 +221,7 @@ impl ModUpdateProgressDialog {
 }
 
 const MSG_UPDATING_MODS: &str = "Please wait while the following mods are being updated:";
+const MSG_DOWNLOADING_MODS: &str = "Please wait while the following mods are being downloaded:";
 
 fn update_progress(table: &DataTable<ProgressRow>) -> ProgressStatus {
     let data = table.data();
@@ -1,5 +1,9 @@
+use std::collections::HashMap;
+use std::rc::Rc;
 use std::sync::Arc;
 
+use chrono::{DateTime, Local};
+
 use fltk::browser::CheckBrowser;
 use fltk::button::{Button, ReturnButton};
 use fltk::frame::Frame;
@@ -8,7 +12,9 @@ use fltk::window::Window;
 use fltk_float::grid::{CellAlign, Grid, GridBuilder};
 use fltk_float::SimpleWrapper;
 
-use crate::game::{ModRef, Mods};
+use crate::game::platform::RemoteModInfo;
+use crate::game::{Branch, ModInfo, ModRef, Mods};
+use crate::gui::mod_manager::show_bbcode;
 use crate::gui::wrapper_factory;
 
 pub struct ModUpdateSelectionDialog {
@@ -18,7 +24,13 @@ pub struct ModUpdateSelectionDialog {
 }
 
 impl ModUpdateSelectionDialog {
-    pub fn new(parent: &Window, mods: &Arc<Mods>, outdated_mods: Vec<ModRef>) -> Self {
+    pub fn new(
+        parent: &Window,
+        mods: &Arc<Mods>,
+        outdated_mods: Vec<ModRef>,
+        remote_info: HashMap<ModRef, RemoteModInfo>,
+        branch: Branch,
+    ) -> Self {
         let mut window = Window::default()
             .with_size(480, 480)
             .with_label("Update Mods");
@@ -42,7 +54,13 @@ impl ModUpdateSelectionDialog {
         let mut mod_selection = CheckBrowser::default();
         for mod_ref in outdated_mods.iter() {
             let mod_info = mods.get(mod_ref).unwrap();
-            mod_selection.add(&mod_info.name, true);
+            let label = format!(
+                "{} [{} -> {}]",
+                &mod_info.name,
+                format_installed(mod_info),
+                format_available(remote_info.get(mod_ref)),
+            );
+            mod_selection.add(&label, true);
         }
         grid.cell().unwrap().add(SimpleWrapper::new(
             mod_selection.clone(),
@@ -67,6 +85,14 @@ impl ModUpdateSelectionDialog {
             .wrap(Button::default())
             .with_label("Select None");
 
+        btn_grid.extend_group(btn_group).add();
+        let mut btn_change_notes = btn_grid
+            .cell()
+            .unwrap()
+            .wrap(Button::default())
+            .with_label("Change Notes...");
+        btn_change_notes.deactivate();
+
         btn_grid.col().with_stretch(1).add();
         btn_grid.cell().unwrap().skip();
 
@@ -91,6 +117,28 @@ impl ModUpdateSelectionDialog {
             let mut mod_selection = mod_selection.clone();
             move |_| mod_selection.check_none()
         });
+        mod_selection.set_callback({
+            let mut btn_change_notes = btn_change_notes.clone();
+            move |mod_selection| btn_change_notes.set_activated(mod_selection.value() > 0)
+        });
+        btn_change_notes.set_callback({
+            let mods = Arc::clone(mods);
+            let outdated_mods = outdated_mods.clone();
+            let remote_info = Rc::new(remote_info);
+            let mod_selection = mod_selection.clone();
+            move |_| {
+                let idx = mod_selection.value();
+                if idx <= 0 {
+                    return;
+                }
+                let mod_info = mods.get(&outdated_mods[(idx - 1) as usize]).unwrap();
+                let remote = remote_info.get(&outdated_mods[(idx - 1) as usize]);
+                show_bbcode(
+                    &format!("Change Notes: {}", &mod_info.name),
+                    &compare_change_notes(mod_info, remote, branch),
+                );
+            }
+        });
         btn_proceed.set_callback({
             let mut window = window.clone();
             move |_| window.hide()
@@ -130,3 +178,54 @@ impl ModUpdateSelectionDialog {
 }
 
 const MSG_MODS_NEED_UPDATES: &str = "The following mods in your mod list need to be updated:";
+const MSG_NO_REMOTE_NOTES: &str =
+    "[i]The change notes of the latest revision are not available.[/i]";
+
+fn format_installed(mod_info: &ModInfo) -> String {
+    format!("r{}", mod_info.revision_number)
+}
+
+fn format_available(remote: Option<&RemoteModInfo>) -> String {
+    match remote {
+        Some(remote) => format!("updated {}", format_time_updated(remote)),
+        None => "?".to_string(),
+    }
+}
+
+fn format_time_updated(remote: &RemoteModInfo) -> String {
+    let time_updated: DateTime<Local> = remote.time_updated.into();
+    time_updated.format("%Y-%m-%d %H:%M").to_string()
+}
+
+// Shows the change notes of the installed revision, followed by when the latest revision was
+// published. The workshop API does not expose the revision number or change notes of the latest
+// revision, so those are linked to on the item's changelog page instead.
+fn compare_change_notes(
+    mod_info: &ModInfo,
+    remote: Option<&RemoteModInfo>,
+    branch: Branch,
+) -> String {
+    let mut notes = format!(
+        "[b]Installed: version {}, revision {}[/b]\n\n{}\n\n",
+        mod_info.version.to_string(),
+        mod_info.revision_number,
+        &mod_info.change_notes,
+    );
+    match remote {
+        Some(remote) => notes.push_str(&format!(
+            "[b]Available: updated {}[/b]\n\n",
+            format_time_updated(remote),
+        )),
+        None => notes.push_str("[b]Available: unknown[/b]\n\n"),
+    }
+    // The workshop only publishes the change notes on the item's changelog page.
+    match mod_info.steam_file_id(branch) {
+        Some(mod_id) => notes.push_str(&format!(
+            "[url=https://steamcommunity.com/sharedfiles/filedetails/changelog/{}]\
+                Steam Workshop change notes[/url]",
+            mod_id
+        )),
+        None => notes.push_str(MSG_NO_REMOTE_NOTES),
+    }
+    notes
+}
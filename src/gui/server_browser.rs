@@ -3,31 +3,38 @@ use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::rc::Rc;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use fltk::app;
+use fltk::dialog;
 use fltk::enums::Align;
 use fltk::frame::Frame;
 use fltk::group::{Group, Tile};
 use fltk::prelude::*;
 use fltk_float::grid::{CellAlign, Grid, GridBuilder};
 use fltk_float::SimpleWrapper;
-use slog::{error, Logger};
+use slog::{error, warn, Logger};
 use strum::IntoEnumIterator;
 
-use crate::config::ServerBrowserConfig;
+use crate::config::{FilterPreset, ServerBrowserConfig};
 use crate::game::platform::ModDirectory;
 use crate::game::Maps;
-use crate::gui::data::{Reindex, RowFilter};
+use crate::gui::data::{IterableTableSource, Reindex, RowFilter};
 use crate::servers::{
-    Community, FavoriteServer, Mode, PingRequest, PingResponse, PingResult, Region, Server,
-    SortCriteria, SortKey, TypeFilter, Weekday,
+    detect_changes, Community, ExportedServer, FavoriteServer, FavoriteServers, Mode,
+    PasswordVault, PingRequest, PingResponse, PingResult, RecentServer, Region, RegionInference,
+    Server, SortKey, TypeFilter, Weekday,
 };
 
 use self::actions_pane::{Action, ActionsPane};
 use self::connect_dialog::ConnectDialog;
 use self::details_pane::DetailsPane;
+use self::favorites_dialog::FavoritesDialog;
 use self::filter_pane::{FilterHolder, FilterPane};
 use self::list_pane::ListPane;
+use self::queue_dialog::QueueDialog;
+use self::recent_dialog::RecentDialog;
 use self::state::{Filter, SortOrder};
 
 use super::data::IterableTableSource;
@@ -36,8 +43,11 @@ use super::{alert_error, wrapper_factory, CleanupFn, Handler};
 mod actions_pane;
 mod connect_dialog;
 mod details_pane;
+mod favorites_dialog;
 mod filter_pane;
 mod list_pane;
+mod queue_dialog;
+mod recent_dialog;
 mod state;
 
 use state::ServerBrowserState;
@@ -48,10 +58,18 @@ pub enum ServerBrowserAction {
         addr: SocketAddr,
         password: Option<String>,
         battleye_required: Option<bool>,
+        id: Option<String>,
+        name: Option<String>,
     },
     PingServer(PingRequest),
     PingServers(Vec<PingRequest>),
-    UpdateFavorites(Vec<FavoriteServer>),
+    QueryServer(String),
+    AddFavorite(FavoriteServer),
+    RemoveFavorite(FavoriteServer),
+    LoadFavorites,
+    SaveFavorites(Vec<FavoriteServer>),
+    LoadRecentServers,
+    ExportServers(Vec<ExportedServer>),
     UpdateConfig(ServerBrowserConfig),
 }
 
@@ -60,6 +78,12 @@ pub enum ServerBrowserUpdate {
     PopulateServers(Result<Vec<Server>>),
     UpdateServer(PingResponse),
     BatchUpdateServers(Vec<PingResponse>),
+    ServerQueried {
+        target: String,
+        result: Result<(SocketAddr, PingResult)>,
+    },
+    ManageFavorites(Vec<FavoriteServer>),
+    ShowRecentServers(Vec<RecentServer>),
     RefreshDetails,
 }
 
@@ -99,7 +123,11 @@ pub(super) struct ServerBrowser {
     pending_update: Rc<Cell<Option<ServerBrowserUpdate>>>,
     state: Rc<RefCell<ServerBrowserState>>,
     filter_dirty: Cell<bool>,
+    filter_presets: RefCell<Vec<FilterPreset>>,
     refreshing: Cell<bool>,
+    previous_servers: RefCell<Vec<Server>>,
+    vault: Rc<RefCell<PasswordVault>>,
+    region_inference: RefCell<RegionInference>,
 }
 
 impl ServerBrowser {
@@ -108,12 +136,13 @@ impl ServerBrowser {
         maps: Arc<Maps>,
         config: &ServerBrowserConfig,
         mod_resolver: Rc<dyn ModDirectory>,
+        vault: Rc<RefCell<PasswordVault>>,
         on_action: impl Handler<ServerBrowserAction> + 'static,
     ) -> Rc<Self> {
         let state = Rc::new(RefCell::new(ServerBrowserState::new(
             Vec::new(),
             Filter::from_config(config),
-            SortOrder::new(config.sort_criteria, region_sort_order()),
+            SortOrder::new(config.sort_criteria.clone(), region_sort_order()),
         )));
 
         let mut grid = Grid::builder_with_factory(wrapper_factory())
@@ -216,7 +245,11 @@ impl ServerBrowser {
             pending_update: Rc::new(Cell::new(None)),
             state: Rc::clone(&state),
             filter_dirty: Cell::new(false),
+            filter_presets: RefCell::new(config.filter_presets.clone()),
             refreshing: Cell::new(true),
+            previous_servers: RefCell::new(Vec::new()),
+            vault,
+            region_inference: RefCell::new(RegionInference::default()),
         });
 
         filter_pane.set_filter_holder(Rc::clone(&browser));
@@ -251,29 +284,9 @@ impl ServerBrowser {
                     match action {
                         Action::Join => {
                             if let Some(server_idx) = browser.list_pane.selected_index() {
-                                let action = {
-                                    let state = browser.state.borrow();
-                                    let server = &state[server_idx];
-                                    if server.password_protected {
-                                        let dialog =
-                                            ConnectDialog::server_password(&browser.root, server);
-
-                                        // The following line is necessary, otherwise the incoming
-                                        // server list updates panic because the state remains
-                                        // borrowed while the dialog is displayed. ¯\_(ツ)_/¯
-                                        drop(state);
-
-                                        match dialog.run() {
-                                            Some(action) => action,
-                                            None => return,
-                                        }
-                                    } else {
-                                        ServerBrowserAction::JoinServer {
-                                            addr: server.game_addr().unwrap(),
-                                            password: None,
-                                            battleye_required: Some(server.battleye_required),
-                                        }
-                                    }
+                                let action = match browser.join_action(server_idx) {
+                                    Some(action) => action,
+                                    None => return,
                                 };
                                 if let Err(err) = (browser.on_action)(action) {
                                     error!(browser.logger, "Error joining server"; "error" => %err);
@@ -283,66 +296,60 @@ impl ServerBrowser {
                         }
                         Action::Ping => {
                             if let Some(server_idx) = browser.list_pane.selected_index() {
-                                let state = browser.state.borrow();
-                                let server = &state[server_idx];
-                                let source_idx = state.to_source_index(server_idx);
-                                let request = PingRequest::for_server(source_idx, server).unwrap();
-                                let action = ServerBrowserAction::PingServer(request);
-                                drop(state);
-
-                                browser.update_servers(1, |all_servers, updated_indices, _, _| {
-                                    all_servers[source_idx].waiting_for_pong = true;
-                                    updated_indices.push(source_idx);
-                                    Reindex::Nothing
-                                });
-
+                                let source_idx = browser.state.borrow().to_source_index(server_idx);
+                                browser.ping_server(source_idx);
+                            }
+                        }
+                        Action::Queue => {
+                            if let Some(server_idx) = browser.list_pane.selected_index() {
+                                let (id, addr) = {
+                                    let state = browser.state.borrow();
+                                    (state[server_idx].id.clone(), state[server_idx].game_addr())
+                                };
+                                let action = match browser.join_action(server_idx) {
+                                    Some(action) => action,
+                                    None => return,
+                                };
+                                if !browser.wait_for_free_slot(&id, addr) {
+                                    return;
+                                }
                                 if let Err(err) = (browser.on_action)(action) {
-                                    error!(browser.logger, "Error pinging server"; "error" => %err);
-                                    alert_error(ERR_PINGING_SERVERS, &err);
+                                    error!(browser.logger, "Error joining server"; "error" => %err);
+                                    alert_error(ERR_JOINING_SERVER, &err);
                                 }
                             }
                         }
                         Action::ToggleFavorite => {
                             if let Some(server_idx) = browser.list_pane.selected_index() {
                                 let src_idx = browser.state.borrow().to_source_index(server_idx);
+                                let mut action = None;
                                 browser.update_servers(
                                     1,
                                     |all_servers, updated_indices, filter, _| {
-                                        all_servers[src_idx].favorite =
-                                            !all_servers[src_idx].favorite;
+                                        let server = &mut all_servers[src_idx];
+                                        server.favorite = !server.favorite;
+                                        let favorite = FavoriteServer::from_server(server);
+                                        action = Some(if server.favorite {
+                                            ServerBrowserAction::AddFavorite(favorite)
+                                        } else {
+                                            ServerBrowserAction::RemoveFavorite(favorite)
+                                        });
                                         updated_indices.push(src_idx);
                                         Reindex::Order
                                             .filter_if(filter.type_filter() == TypeFilter::Favorite)
                                     },
                                 );
-                                let state = browser.state.borrow_mut();
-                                let favorites = state
-                                    .source()
-                                    .iter()
-                                    .filter_map(|server| {
-                                        if server.favorite {
-                                            Some(FavoriteServer::from_server(server))
-                                        } else {
-                                            None
-                                        }
-                                    })
-                                    .collect();
-                                let action = ServerBrowserAction::UpdateFavorites(favorites);
-                                if let Err(err) = (browser.on_action)(action) {
-                                    error!(
-                                        browser.logger,
-                                        "Error updating favorites";
-                                        "error" => %err,
-                                    );
-                                    alert_error(ERR_UPDATING_FAVORITES, &err);
-                                }
+                                browser.update_favorites(action.unwrap());
                             }
                         }
                         Action::Refresh => {
                             browser.refreshing.set(true);
                             {
                                 let mut state = browser.state.borrow_mut();
-                                state.update_source(Vec::clear);
+                                state.update_source(|servers| {
+                                    // Kept to tell what changed once the new list is loaded.
+                                    *browser.previous_servers.borrow_mut() = std::mem::take(servers);
+                                });
                             }
                             browser.list_pane.mark_refreshing();
                             browser.list_pane.set_selected_index(None, false);
@@ -351,17 +358,58 @@ impl ServerBrowser {
                             total_players_text.redraw();
                             (browser.on_action)(ServerBrowserAction::LoadServers).unwrap();
                         }
+                        Action::Export => {
+                            let servers = browser
+                                .state
+                                .borrow()
+                                .iter()
+                                .map(ExportedServer::new)
+                                .collect();
+                            let action = ServerBrowserAction::ExportServers(servers);
+                            if let Err(err) = (browser.on_action)(action) {
+                                alert_error(ERR_EXPORTING_SERVERS, &err);
+                            }
+                        }
                         Action::DirectConnect => {
                             let dialog = ConnectDialog::direct_connect(&browser.root);
                             let action = match dialog.run() {
                                 Some(action) => action,
                                 None => return,
                             };
+                            if let Some(favorite) = dialog.take_favorite() {
+                                browser.add_favorite(favorite);
+                            }
                             if let Err(err) = (browser.on_action)(action) {
                                 error!(browser.logger, "Error on direct connect"; "error" => %err);
                                 alert_error(ERR_JOINING_SERVER, &err);
                             }
                         }
+                        Action::Recent => {
+                            let action = ServerBrowserAction::LoadRecentServers;
+                            if let Err(err) = (browser.on_action)(action) {
+                                error!(browser.logger, "Error loading recent servers"; "error" => %err);
+                                alert_error(ERR_LOADING_RECENT_SERVERS, &err);
+                            }
+                        }
+                        Action::ManageFavorites => {
+                            if let Err(err) =
+                                (browser.on_action)(ServerBrowserAction::LoadFavorites)
+                            {
+                                error!(browser.logger, "Error loading favorites"; "error" => %err);
+                                alert_error(ERR_LOADING_FAVORITES, &err);
+                            }
+                        }
+                        Action::Query => {
+                            let target = match dialog::input_default(PROMPT_QUERY_ADDR, "") {
+                                Some(target) if !target.trim().is_empty() => target,
+                                _ => return,
+                            };
+                            let action = ServerBrowserAction::QueryServer(target.trim().into());
+                            if let Err(err) = (browser.on_action)(action) {
+                                error!(browser.logger, "Error querying server"; "error" => %err);
+                                alert_error(ERR_QUERYING_SERVER, &err);
+                            }
+                        }
                         Action::ScrollLock(scroll_lock) => {
                             browser.list_pane.set_scroll_lock(scroll_lock);
                             browser.update_config();
@@ -418,6 +466,15 @@ impl ServerBrowser {
             ServerBrowserUpdate::BatchUpdateServers(responses) => {
                 self.update_pinged_servers(&responses);
             }
+            ServerBrowserUpdate::ServerQueried { target, result } => {
+                self.show_query_result(&target, result);
+            }
+            ServerBrowserUpdate::ManageFavorites(favorites) => {
+                self.manage_favorites(favorites);
+            }
+            ServerBrowserUpdate::ShowRecentServers(history) => {
+                self.show_recent_servers(history);
+            }
             ServerBrowserUpdate::RefreshDetails => {
                 if self.root.visible() {
                     if let Some(selected_idx) = self.list_pane.selected_index() {
@@ -429,10 +486,259 @@ impl ServerBrowser {
         }
     }
 
+    fn join_action(&self, server_idx: usize) -> Option<ServerBrowserAction> {
+        // Clone the server, otherwise the incoming server list updates panic because the state
+        // remains borrowed while the password dialog is displayed.
+        let server = self.state.borrow()[server_idx].clone();
+        self.server_join_action(&server)
+    }
+
+    fn server_join_action(&self, server: &Server) -> Option<ServerBrowserAction> {
+        if server.password_protected {
+            self.prompt_server_password(server)
+        } else {
+            Some(ServerBrowserAction::JoinServer {
+                addr: server.game_addr().unwrap(),
+                password: None,
+                battleye_required: Some(server.battleye_required),
+                id: Some(server.id.clone()),
+                name: Some(server.name.clone()),
+            })
+        }
+    }
+
+    fn prompt_server_password(&self, server: &Server) -> Option<ServerBrowserAction> {
+        let addr = server.game_addr().unwrap();
+        let saved_password = self.saved_password(server);
+        let can_remember = self.vault.borrow().is_available();
+        let dialog = ConnectDialog::server_password(
+            &self.root,
+            server,
+            saved_password.as_deref(),
+            can_remember,
+        );
+        let action = dialog.run()?;
+
+        let password = match &action {
+            ServerBrowserAction::JoinServer { password, .. } => password.clone(),
+            _ => None,
+        };
+        let result = match password {
+            Some(password) if dialog.remember_password() => {
+                if saved_password.as_deref() == Some(password.as_str()) || !self.unlock_vault(true)
+                {
+                    Ok(())
+                } else {
+                    self.vault.borrow_mut().remember(&server.id, addr, password)
+                }
+            }
+            _ if saved_password.is_some() => self.vault.borrow_mut().forget(&server.id, addr),
+            _ => Ok(()),
+        };
+        if let Err(err) = result {
+            error!(self.logger, "Error saving server password"; "error" => %err);
+            alert_error(ERR_SAVING_PASSWORD, &err);
+        }
+
+        Some(action)
+    }
+
+    fn saved_password(&self, server: &Server) -> Option<String> {
+        if !self.vault.borrow().exists() || !self.unlock_vault(false) {
+            return None;
+        }
+        let addr = server.game_addr()?;
+        self.vault
+            .borrow()
+            .password(&server.id, addr)
+            .map(str::to_string)
+    }
+
+    // Makes sure the vault is unlocked, asking the user for the master passphrase if it isn't.
+    // When there is no vault yet and `create` is set, the user chooses the passphrase for a new one.
+    fn unlock_vault(&self, create: bool) -> bool {
+        if self.vault.borrow().is_unlocked() {
+            return true;
+        }
+        if self.vault.borrow().exists() {
+            let passphrase = match dialog::password_default(PROMPT_UNLOCK_VAULT, "") {
+                Some(passphrase) => passphrase,
+                None => return false,
+            };
+            if let Err(err) = self.vault.borrow_mut().unlock(&passphrase) {
+                warn!(self.logger, "Error unlocking password vault"; "error" => %err);
+                alert_error(ERR_UNLOCKING_VAULT, &err);
+                return false;
+            }
+            return true;
+        }
+        if !create {
+            return false;
+        }
+
+        let passphrase = match dialog::password_default(PROMPT_NEW_VAULT_PASSPHRASE, "") {
+            Some(passphrase) if !passphrase.is_empty() => passphrase,
+            _ => return false,
+        };
+        match dialog::password_default(PROMPT_CONFIRM_VAULT_PASSPHRASE, "") {
+            Some(confirmation) if confirmation == passphrase => (),
+            Some(_) => {
+                dialog::alert_default(ERR_PASSPHRASE_MISMATCH);
+                return false;
+            }
+            None => return false,
+        }
+        if let Err(err) = self.vault.borrow_mut().create(&passphrase) {
+            error!(self.logger, "Error creating password vault"; "error" => %err);
+            alert_error(ERR_SAVING_PASSWORD, &err);
+            return false;
+        }
+        true
+    }
+
+    fn show_recent_servers(&self, history: Vec<RecentServer>) {
+        let entry = match RecentDialog::new(&self.root, history).run() {
+            Some(entry) => entry,
+            None => return,
+        };
+
+        // Prefer the server's current listing, in case it asks for a password or moved to another
+        // address since it was last joined.
+        let listed = self
+            .state
+            .borrow()
+            .source()
+            .iter()
+            .filter(|server| server.is_valid() && !server.changes.removed)
+            .find(|server| match &entry.id {
+                Some(id) => &server.id == id,
+                None => server.game_addr() == Some(entry.addr),
+            })
+            .cloned();
+        let action = match listed {
+            Some(server) => match self.server_join_action(&server) {
+                Some(action) => action,
+                None => return,
+            },
+            None => ServerBrowserAction::JoinServer {
+                addr: entry.addr,
+                password: None,
+                battleye_required: None,
+                id: entry.id,
+                name: entry.name,
+            },
+        };
+        if let Err(err) = (self.on_action)(action) {
+            error!(self.logger, "Error joining server"; "error" => %err);
+            alert_error(ERR_JOINING_SERVER, &err);
+        }
+    }
+
+    fn ping_server(&self, source_idx: usize) {
+        let request = {
+            let state = self.state.borrow();
+            match PingRequest::for_server(source_idx, &state.source()[source_idx]) {
+                Some(request) => request,
+                None => return,
+            }
+        };
+        let action = ServerBrowserAction::PingServer(request);
+
+        self.update_servers(1, |all_servers, updated_indices, _, _| {
+            all_servers[source_idx].waiting_for_pong = true;
+            updated_indices.push(source_idx);
+            Reindex::Nothing
+        });
+
+        if let Err(err) = (self.on_action)(action) {
+            error!(self.logger, "Error pinging server"; "error" => %err);
+            alert_error(ERR_PINGING_SERVERS, &err);
+        }
+    }
+
+    // The server is looked up again on every poll, since a refresh while queued replaces the
+    // server list and its indices.
+    fn find_source_index(&self, id: &str, addr: Option<SocketAddr>) -> Option<usize> {
+        self.state
+            .borrow()
+            .source()
+            .iter()
+            .position(|server| server.id == id && server.game_addr() == addr)
+    }
+
+    fn wait_for_free_slot(&self, id: &str, addr: Option<SocketAddr>) -> bool {
+        let dialog = {
+            let source_idx = match self.find_source_index(id, addr) {
+                Some(source_idx) => source_idx,
+                None => return false,
+            };
+            let state = self.state.borrow();
+            QueueDialog::new(&self.root, &state.source()[source_idx])
+        };
+        dialog.show();
+
+        let deadline = Instant::now() + QUEUE_TIMEOUT;
+        let mut next_ping = Instant::now();
+        let should_poll = Rc::new(Cell::new(true));
+        app::add_timeout3(1.0, {
+            let should_poll = Rc::downgrade(&should_poll);
+            move |handle| {
+                if let Some(should_poll) = should_poll.upgrade() {
+                    should_poll.set(true);
+                    app::repeat_timeout3(1.0, handle);
+                    app::awake();
+                }
+            }
+        });
+
+        loop {
+            if should_poll.replace(false) {
+                let now = Instant::now();
+                let source_idx = match self.find_source_index(id, addr) {
+                    Some(source_idx) => source_idx,
+                    None if self.refreshing.get() && now < deadline => continue,
+                    None => return false,
+                };
+                {
+                    let state = self.state.borrow();
+                    let server = &state.source()[source_idx];
+                    let pinged = !server.waiting_for_pong && server.connected_players.is_some();
+                    if pinged && !server.is_full() {
+                        return true;
+                    }
+                    if now >= deadline {
+                        drop(state);
+                        drop(dialog);
+                        fltk::dialog::alert_default(ERR_QUEUE_TIMEOUT);
+                        return false;
+                    }
+                    dialog.set_status(server, deadline - now);
+                }
+                if now >= next_ping {
+                    self.ping_server(source_idx);
+                    next_ping = now + QUEUE_PING_INTERVAL;
+                }
+            }
+            if !dialog.shown() {
+                return false;
+            }
+            if !app::wait() {
+                return false;
+            }
+        }
+    }
+
     fn populate_servers(&self, all_servers: Vec<Server>) {
         {
             let mut state = self.state.borrow_mut();
             state.update(|servers, _, _| {
+                let mut all_servers = all_servers;
+                detect_changes(&self.previous_servers.take(), &mut all_servers);
+                let mut region_inference = self.region_inference.borrow_mut();
+                region_inference.update_subnets(&all_servers);
+                for server in all_servers.iter_mut() {
+                    server.inferred_region = region_inference.infer(server);
+                }
                 *servers = all_servers;
                 Reindex::all()
             });
@@ -475,9 +781,10 @@ impl ServerBrowser {
         }
 
         let mut total_players = self.total_players.get();
+        let mut region_inference = self.region_inference.borrow_mut();
         self.update_servers(
             updates.len(),
-            |all_servers, updated_indices, filter, sort_criteria| {
+            |all_servers, updated_indices, filter, order| {
                 let mut reindex = Reindex::Nothing;
                 for update in updates {
                     let server = match all_servers.get_mut(update.server_idx) {
@@ -485,24 +792,134 @@ impl ServerBrowser {
                         None => continue,
                     };
                     updated_indices.push(update.server_idx);
-                    if Self::update_server(server, update, filter, &mut total_players) {
+                    if Self::update_server(
+                        server,
+                        update,
+                        filter,
+                        &mut region_inference,
+                        &mut total_players,
+                    ) {
                         reindex = Reindex::Filter;
                     }
                 }
                 reindex.order_if(
-                    (sort_criteria.key == SortKey::Players)
-                        || (sort_criteria.key == SortKey::Age)
-                        || (sort_criteria.key == SortKey::Ping),
+                    order.sorts_by(SortKey::Players)
+                        || order.sorts_by(SortKey::Age)
+                        || order.sorts_by(SortKey::Ping)
+                        || order.sorts_by(SortKey::Region),
                 )
             },
         );
         self.set_total_player_count(total_players);
     }
 
+    fn show_query_result(&self, target: &str, result: Result<(SocketAddr, PingResult)>) {
+        let (addr, result) = match result {
+            Ok(result) => result,
+            Err(err) => {
+                alert_error(ERR_QUERYING_SERVER, &anyhow!("{}: {}", target, err));
+                return;
+            }
+        };
+
+        let src_idx = self
+            .state
+            .borrow()
+            .source()
+            .iter()
+            .position(|server| server.game_addr() == Some(addr));
+        if let Some(src_idx) = src_idx {
+            self.update_pinged_servers(&[PingResponse {
+                server_idx: src_idx,
+                result,
+            }]);
+            if self.state.borrow().from_source_index(src_idx).is_some() {
+                self.set_selected_server_index(Some(src_idx), true);
+                return;
+            }
+            let message = {
+                let state = self.state.borrow();
+                let server = &state.source()[src_idx];
+                format!(
+                    "{}\n{}\n\n{}",
+                    server.name,
+                    query_result_summary(addr, server.connected_players, server.age, server.ping),
+                    MSG_QUERIED_SERVER_FILTERED,
+                )
+            };
+            dialog::message_default(&message);
+        } else {
+            let summary = match result {
+                PingResult::Pong {
+                    connected_players,
+                    age,
+                    round_trip,
+                } => {
+                    query_result_summary(addr, Some(connected_players), Some(age), Some(round_trip))
+                }
+                PingResult::Timeout => format!("{}\n{}", addr, MSG_QUERY_TIMEOUT),
+            };
+            dialog::message_default(&format!("{}\n\n{}", summary, MSG_QUERIED_SERVER_UNLISTED));
+        }
+    }
+
+    fn add_favorite(&self, favorite: FavoriteServer) {
+        self.update_servers(1, |all_servers, updated_indices, filter, _| {
+            for (idx, server) in all_servers.iter_mut().enumerate() {
+                if (Some(*server.ip()), Some(server.port)) == (favorite.ip, favorite.port) {
+                    server.favorite = true;
+                    server.host_name = favorite.host_name.clone();
+                    updated_indices.push(idx);
+                }
+            }
+            Reindex::Order.filter_if(filter.type_filter() == TypeFilter::Favorite)
+        });
+        self.update_favorites(ServerBrowserAction::AddFavorite(favorite));
+    }
+
+    fn update_favorites(&self, action: ServerBrowserAction) {
+        if let Err(err) = (self.on_action)(action) {
+            error!(self.logger, "Error updating favorites"; "error" => %err);
+            alert_error(ERR_UPDATING_FAVORITES, &err);
+        }
+    }
+
+    fn manage_favorites(&self, favorites: Vec<FavoriteServer>) {
+        let listed: Vec<FavoriteServer> = self
+            .state
+            .borrow()
+            .source()
+            .iter()
+            .filter(|server| !server.changes.removed)
+            .map(FavoriteServer::from_server)
+            .collect();
+        let dialog = FavoritesDialog::new(&self.root, favorites, listed);
+        let favorites = match dialog.run() {
+            Some(favorites) => favorites,
+            None => return,
+        };
+
+        self.update_favorites(ServerBrowserAction::SaveFavorites(favorites.clone()));
+
+        let favorites: FavoriteServers = favorites.into_iter().collect();
+        self.update_servers(0, |all_servers, updated_indices, filter, _| {
+            for (idx, server) in all_servers.iter_mut().enumerate() {
+                let favorite = favorites.find(server);
+                let host_name = favorite.and_then(|favorite| favorite.host_name.clone());
+                if server.favorite != favorite.is_some() || server.host_name != host_name {
+                    server.favorite = favorite.is_some();
+                    server.host_name = host_name;
+                    updated_indices.push(idx);
+                }
+            }
+            Reindex::Order.filter_if(filter.type_filter() == TypeFilter::Favorite)
+        });
+    }
+
     fn update_servers(
         &self,
         count_hint: usize,
-        mutator: impl FnOnce(&mut Vec<Server>, &mut Vec<usize>, &Filter, &SortCriteria) -> Reindex,
+        mutator: impl FnOnce(&mut Vec<Server>, &mut Vec<usize>, &Filter, &SortOrder) -> Reindex,
     ) {
         let selected_idx = self.selected_server_index();
 
@@ -510,12 +927,7 @@ impl ServerBrowser {
         let repopulate = {
             let mut state = self.state.borrow_mut();
             state.update(|all_servers, filter, order| {
-                mutator(
-                    all_servers,
-                    &mut updated_indices,
-                    filter,
-                    &mut order.criteria,
-                )
+                mutator(all_servers, &mut updated_indices, filter, order)
             })
         };
 
@@ -536,6 +948,7 @@ impl ServerBrowser {
         server: &mut Server,
         update: &PingResponse,
         filter: &Filter,
+        region_inference: &mut RegionInference,
         total_players: &mut usize,
     ) -> bool {
         *total_players -= server.connected_players.unwrap_or_default();
@@ -558,6 +971,8 @@ impl ServerBrowser {
             }
         };
         server.waiting_for_pong = false;
+        region_inference.record_ping(server);
+        server.inferred_region = region_inference.infer(server);
         filter.matches(server) != matched_before
     }
 
@@ -587,7 +1002,8 @@ impl ServerBrowser {
         let order = state.order();
         let config = ServerBrowserConfig {
             filter: filter.as_ref().clone(),
-            sort_criteria: order.criteria,
+            filter_presets: self.filter_presets.borrow().clone(),
+            sort_criteria: order.criteria.clone(),
             scroll_lock: self.list_pane.scroll_lock(),
         };
         (self.on_action)(ServerBrowserAction::UpdateConfig(config)).unwrap();
@@ -612,12 +1028,90 @@ impl FilterHolder for ServerBrowser {
             self.update_config();
         }
     }
+
+    fn filter_presets(&self) -> Vec<String> {
+        self.filter_presets
+            .borrow()
+            .iter()
+            .map(|preset| preset.name.clone())
+            .collect()
+    }
+
+    fn apply_filter_preset(&self, name: &str) {
+        let values = self
+            .filter_presets
+            .borrow()
+            .iter()
+            .find(|preset| preset.name == name)
+            .map(|preset| preset.filter.clone());
+        if let Some(values) = values {
+            self.mutate_filter(|filter| *filter = Filter::new(values.clone()));
+            self.persist_filter();
+        }
+    }
+
+    fn save_filter_preset(&self, name: String) {
+        let filter = self.state.borrow().filter().as_ref().clone();
+        {
+            let mut presets = self.filter_presets.borrow_mut();
+            match presets.iter_mut().find(|preset| preset.name == name) {
+                Some(preset) => preset.filter = filter,
+                None => presets.push(FilterPreset { name, filter }),
+            }
+        }
+        self.update_config();
+    }
+
+    fn delete_filter_preset(&self, name: &str) {
+        self.filter_presets
+            .borrow_mut()
+            .retain(|preset| preset.name != name);
+        self.update_config();
+    }
 }
 
 const ERR_LOADING_SERVERS: &str = "Error while loading the server list.";
+const ERR_EXPORTING_SERVERS: &str = "Error while exporting the server list.";
 const ERR_PINGING_SERVERS: &str = "Error while pinging servers.";
 const ERR_JOINING_SERVER: &str = "Error while trying to launch the game to join the server.";
 const ERR_UPDATING_FAVORITES: &str = "Error while updating favorites.";
+const ERR_LOADING_FAVORITES: &str = "Error while loading favorites.";
+const ERR_LOADING_RECENT_SERVERS: &str = "Error while loading recent servers.";
+const ERR_SAVING_PASSWORD: &str = "Error while saving the server password.";
+const ERR_UNLOCKING_VAULT: &str = "Could not unlock the saved server passwords.";
+const ERR_PASSPHRASE_MISMATCH: &str = "The passphrases do not match.";
+const PROMPT_UNLOCK_VAULT: &str = "Enter the master passphrase for your saved server passwords:";
+const PROMPT_NEW_VAULT_PASSPHRASE: &str =
+    "Choose a master passphrase to protect your saved server passwords:";
+const PROMPT_CONFIRM_VAULT_PASSPHRASE: &str = "Enter the master passphrase again:";
+const ERR_QUERYING_SERVER: &str = "Error while querying the server.";
+const ERR_QUEUE_TIMEOUT: &str = "No slot became available on the server in time.";
+
+const PROMPT_QUERY_ADDR: &str = "Address of the server to query (host:port):";
+const MSG_QUERY_TIMEOUT: &str = "The server did not respond.";
+const MSG_QUERIED_SERVER_FILTERED: &str =
+    "The server is in the server list, but hidden by the current filter.";
+const MSG_QUERIED_SERVER_UNLISTED: &str =
+    "The server is not in the server list, so only its ping information is available.";
+
+const QUEUE_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+const QUEUE_PING_INTERVAL: Duration = Duration::from_secs(5);
+
+fn query_result_summary(
+    addr: SocketAddr,
+    connected_players: Option<usize>,
+    age: Option<Duration>,
+    ping: Option<Duration>,
+) -> String {
+    let unknown = || "?".to_string();
+    format!(
+        "{}\nPlayers: {}\nAge: {} days\nPing: {} ms",
+        addr,
+        connected_players.map_or_else(unknown, |players| players.to_string()),
+        age.map_or_else(unknown, |age| (age.as_secs() / 86400).to_string()),
+        ping.map_or_else(unknown, |ping| ping.as_millis().to_string()),
+    )
+}
 
 fn mode_name(mode: Mode) -> &'static str {
     match mode {
@@ -12,9 +12,14 @@ use crate::servers::Server;
 
 pub enum Action {
     DirectConnect,
+    Query,
     Refresh,
+    Export,
+    Recent,
+    ManageFavorites,
     ToggleFavorite,
     Ping,
+    Queue,
     Join,
     ScrollLock(bool),
 }
@@ -22,9 +27,14 @@ pub enum Action {
 pub(super) struct ActionsPane {
     grid: Grid,
     direct_conn_button: Button,
+    query_button: Button,
     refresh_button: Button,
+    export_button: Button,
+    recent_button: Button,
+    favorites_button: Button,
     toggle_favorite_button: Button,
     ping_button: Button,
+    queue_button: Button,
     join_button: Button,
     scroll_lock_check: CheckButton,
 }
@@ -44,6 +54,17 @@ impl ActionsPane {
             .with_label("Direct Connect...")
             .with_tooltip("Specify the address and port of the server to connect to");
 
+        grid.col().add();
+        let query_button = grid
+            .cell()
+            .unwrap()
+            .wrap(Button::default())
+            .with_label("Query...")
+            .with_tooltip(
+                "Ping a server by its address, even if it's not in the server list, and show its \
+            details",
+            );
+
         grid.col().add();
         let refresh_button = grid
             .cell()
@@ -52,6 +73,32 @@ impl ActionsPane {
             .with_label("Refresh")
             .with_tooltip("Reload the server list");
 
+        grid.col().add();
+        let export_button = grid
+            .cell()
+            .unwrap()
+            .wrap(Button::default())
+            .with_label("Export...")
+            .with_tooltip("Save the filtered server list to a CSV or JSON file");
+
+        grid.col().add();
+        let recent_button = grid
+            .cell()
+            .unwrap()
+            .wrap(Button::default())
+            .with_label("Recent...")
+            .with_tooltip("Show the servers you joined recently, and join one of them again");
+
+        grid.col().add();
+        let favorites_button = grid
+            .cell()
+            .unwrap()
+            .wrap(Button::default())
+            .with_label("Favorites...")
+            .with_tooltip(
+                "Organize your favorite servers with notes and tags, and share them with others",
+            );
+
         grid.col().with_stretch(1).add();
         let scroll_lock_check = grid
             .cell()
@@ -84,6 +131,17 @@ impl ActionsPane {
             );
         ping_button.deactivate();
 
+        grid.col().add();
+        let mut queue_button = grid
+            .cell()
+            .unwrap()
+            .wrap(Button::default())
+            .with_label("Queue")
+            .with_tooltip(
+                "Keep pinging the selected full server and join it as soon as a slot frees up",
+            );
+        queue_button.deactivate();
+
         grid.col().add();
         let mut join_button = grid
             .cell()
@@ -98,9 +156,14 @@ impl ActionsPane {
         Rc::new(Self {
             grid,
             direct_conn_button,
+            query_button,
             refresh_button,
+            export_button,
+            recent_button,
+            favorites_button,
             toggle_favorite_button,
             ping_button,
+            queue_button,
             join_button,
             scroll_lock_check,
         })
@@ -115,6 +178,7 @@ impl ActionsPane {
     pub fn server_selected(&self, server: Option<&Server>) {
         let mut toggle_favorite_button = self.toggle_favorite_button.clone();
         let mut ping_button = self.ping_button.clone();
+        let mut queue_button = self.queue_button.clone();
         let mut join_button = self.join_button.clone();
 
         if let Some(server) = server {
@@ -125,10 +189,12 @@ impl ActionsPane {
                 "Favorite"
             });
             ping_button.set_activated(server.is_valid());
+            queue_button.set_activated(server.is_valid() && server.is_full());
             join_button.set_activated(server.is_valid());
         } else {
             toggle_favorite_button.deactivate();
             ping_button.deactivate();
+            queue_button.deactivate();
             join_button.deactivate();
         }
     }
@@ -140,11 +206,31 @@ impl ActionsPane {
             let on_action = Rc::clone(&on_action);
             direct_conn_button.set_callback(move |_| on_action(Action::DirectConnect));
         }
+        {
+            let mut query_button = self.query_button.clone();
+            let on_action = Rc::clone(&on_action);
+            query_button.set_callback(move |_| on_action(Action::Query));
+        }
         {
             let mut refresh_button = self.refresh_button.clone();
             let on_action = Rc::clone(&on_action);
             refresh_button.set_callback(move |_| on_action(Action::Refresh));
         }
+        {
+            let mut export_button = self.export_button.clone();
+            let on_action = Rc::clone(&on_action);
+            export_button.set_callback(move |_| on_action(Action::Export));
+        }
+        {
+            let mut recent_button = self.recent_button.clone();
+            let on_action = Rc::clone(&on_action);
+            recent_button.set_callback(move |_| on_action(Action::Recent));
+        }
+        {
+            let mut favorites_button = self.favorites_button.clone();
+            let on_action = Rc::clone(&on_action);
+            favorites_button.set_callback(move |_| on_action(Action::ManageFavorites));
+        }
         {
             let mut toggle_favorite_button = self.toggle_favorite_button.clone();
             let on_action = Rc::clone(&on_action);
@@ -155,6 +241,11 @@ impl ActionsPane {
             let on_action = Rc::clone(&on_action);
             ping_button.set_callback(move |_| on_action(Action::Ping));
         }
+        {
+            let mut queue_button = self.queue_button.clone();
+            let on_action = Rc::clone(&on_action);
+            queue_button.set_callback(move |_| on_action(Action::Queue));
+        }
         {
             let mut join_button = self.join_button.clone();
             let on_action = Rc::clone(&on_action);
@@ -1,9 +1,9 @@
 use std::cell::RefCell;
 use std::net::SocketAddr;
 use std::rc::Rc;
-use std::str::FromStr;
 
-use fltk::button::{Button, ReturnButton};
+use fltk::app;
+use fltk::button::{Button, CheckButton, ReturnButton};
 use fltk::enums::Align;
 use fltk::frame::Frame;
 use fltk::group::Group;
@@ -11,23 +11,45 @@ use fltk::input::{Input, SecretInput};
 use fltk::prelude::*;
 use fltk::window::Window;
 use fltk_float::grid::{CellAlign, GridBuilder};
+use tokio::sync::oneshot;
 
 use crate::gui::{alert_error, wrapper_factory};
-use crate::servers::Server;
+use crate::net::{parse_host_name, parse_socket_addr, resolve_host};
+use crate::servers::{FavoriteServer, Server};
 
 use super::ServerBrowserAction;
 
 pub struct ConnectDialog {
     window: Window,
+    ok_button: ReturnButton,
     result: Rc<RefCell<Option<ServerBrowserAction>>>,
+    favorite: Rc<RefCell<Option<FavoriteServer>>>,
+    resolving: Rc<RefCell<Option<Resolving>>>,
+    remember_password: Option<CheckButton>,
+}
+
+struct Resolving {
+    host_name: String,
+    port: u16,
+    password: Option<String>,
+    add_to_favorites: bool,
+    rx: oneshot::Receiver<std::io::Result<Vec<SocketAddr>>>,
 }
 
 impl ConnectDialog {
     pub fn direct_connect(parent: &Group) -> Self {
-        let (window, mut server_text, password_text, mut ok_button) =
-            Self::create_gui(parent, "Direct Connect", Input::default);
+        let (window, mut server_text, password_text, favorite_check, mut ok_button) =
+            Self::create_gui(
+                parent,
+                "Direct Connect",
+                Input::default,
+                Some("Add to favorites"),
+            );
+        let favorite_check = favorite_check.unwrap();
 
         let result = Rc::new(RefCell::new(None));
+        let favorite = Rc::new(RefCell::new(None));
+        let resolving = Rc::new(RefCell::new(None));
 
         server_text.set_value("127.0.0.1:7777");
 
@@ -35,41 +57,98 @@ impl ConnectDialog {
             let server_text = server_text.clone();
             let password_text = password_text.clone();
             let result = Rc::clone(&result);
+            let favorite = Rc::clone(&favorite);
+            let resolving = Rc::clone(&resolving);
             let mut window = window.clone();
-            move |_| {
-                let addr = SocketAddr::from_str(&server_text.value()).map_err(anyhow::Error::msg);
-                match addr {
-                    Err(err) => alert_error(ERR_INVALID_ADDR, &err),
-                    Ok(addr) => {
-                        let password = password_text.value();
-                        let password = if password.is_empty() { None } else { Some(password) };
-                        *result.borrow_mut() = Some(ServerBrowserAction::JoinServer {
-                            addr,
-                            password,
-                            battleye_required: None,
-                        });
-                        window.hide();
+            move |ok_button| {
+                let password = password_text.value();
+                let password = if password.is_empty() { None } else { Some(password) };
+                let add_to_favorites = favorite_check.is_checked();
+
+                if let Some(addr) = parse_socket_addr(&server_text.value()) {
+                    if add_to_favorites {
+                        *favorite.borrow_mut() = Some(make_favorite(None, addr));
                     }
+                    *result.borrow_mut() = Some(ServerBrowserAction::JoinServer {
+                        addr,
+                        password,
+                        battleye_required: None,
+                        id: None,
+                        name: None,
+                    });
+                    window.hide();
+                    return;
                 }
+
+                let (host_name, port) = match parse_host_name(&server_text.value()) {
+                    Some(host) => host,
+                    None => {
+                        alert_error(ERR_INVALID_ADDR, &anyhow::anyhow!(ERR_ADDR_FORMAT));
+                        return;
+                    }
+                };
+                let (tx, rx) = oneshot::channel();
+                tokio::spawn({
+                    let host_name = host_name.clone();
+                    async move {
+                        let _ = tx.send(resolve_host(&host_name, port).await);
+                        app::awake();
+                    }
+                });
+                *resolving.borrow_mut() = Some(Resolving {
+                    host_name,
+                    port,
+                    password,
+                    add_to_favorites,
+                    rx,
+                });
+                ok_button.deactivate();
+                ok_button.set_label("Resolving...");
             }
         });
 
-        Self { window, result }
+        Self {
+            window,
+            ok_button,
+            result,
+            favorite,
+            resolving,
+            remember_password: None,
+        }
     }
 
-    pub fn server_password(parent: &Group, server: &Server) -> Self {
-        let (window, _, password_text, mut ok_button) =
-            Self::create_gui(parent, "Enter Server Password", || {
+    // If the password vault is available, the dialog offers to remember the password, and is
+    // prefilled with the saved one, if any.
+    pub fn server_password(
+        parent: &Group,
+        server: &Server,
+        saved_password: Option<&str>,
+        can_remember: bool,
+    ) -> Self {
+        let (window, _, mut password_text, remember_check, mut ok_button) = Self::create_gui(
+            parent,
+            "Enter Server Password",
+            || {
                 Frame::default()
                     .with_label(&server.name)
                     .with_align(Align::Left | Align::Inside)
-            });
+            },
+            if can_remember { Some("Remember password") } else { None },
+        );
+        if let Some(password) = saved_password {
+            password_text.set_value(password);
+        }
+        if let Some(remember_check) = &remember_check {
+            remember_check.set_checked(saved_password.is_some());
+        }
 
         let result = Rc::new(RefCell::new(None));
 
         ok_button.set_callback({
             let addr = server.game_addr().unwrap();
             let battleye_required = Some(server.battleye_required);
+            let id = server.id.clone();
+            let name = server.name.clone();
             let password_text = password_text.clone();
             let result = Rc::clone(&result);
             let mut window = window.clone();
@@ -80,12 +159,21 @@ impl ConnectDialog {
                     addr,
                     password,
                     battleye_required,
+                    id: Some(id.clone()),
+                    name: Some(name.clone()),
                 });
                 window.hide();
             }
         });
 
-        Self { window, result }
+        Self {
+            window,
+            ok_button,
+            result,
+            favorite: Default::default(),
+            resolving: Default::default(),
+            remember_password: remember_check,
+        }
     }
 
     pub fn run(&self) -> Option<ServerBrowserAction> {
@@ -95,18 +183,75 @@ impl ConnectDialog {
 
         while window.shown() {
             fltk::app::wait();
+            self.poll_resolving();
         }
 
         self.result.borrow_mut().take()
     }
 
+    pub fn take_favorite(&self) -> Option<FavoriteServer> {
+        self.favorite.borrow_mut().take()
+    }
+
+    pub fn remember_password(&self) -> bool {
+        self.remember_password
+            .as_ref()
+            .map(CheckButton::is_checked)
+            .unwrap_or_default()
+    }
+
+    fn poll_resolving(&self) {
+        let mut resolving_ref = self.resolving.borrow_mut();
+        let resolving = match resolving_ref.as_mut() {
+            Some(resolving) => resolving,
+            None => return,
+        };
+        let resolved = match resolving.rx.try_recv() {
+            Ok(resolved) => resolved,
+            Err(oneshot::error::TryRecvError::Empty) => return,
+            Err(oneshot::error::TryRecvError::Closed) => {
+                Err(std::io::ErrorKind::Interrupted.into())
+            }
+        };
+        let resolving = resolving_ref.take().unwrap();
+        drop(resolving_ref);
+
+        let mut ok_button = self.ok_button.clone();
+        ok_button.activate();
+        ok_button.set_label("OK");
+
+        let addr = match resolved {
+            Ok(addrs) => addrs[0],
+            Err(err) => {
+                let err = anyhow::anyhow!("{}: {}", resolving.host_name, err);
+                alert_error(ERR_RESOLVING_HOST, &err);
+                return;
+            }
+        };
+        let addr = SocketAddr::new(addr.ip(), resolving.port);
+        if resolving.add_to_favorites {
+            *self.favorite.borrow_mut() =
+                Some(make_favorite(Some(resolving.host_name.clone()), addr));
+        }
+        *self.result.borrow_mut() = Some(ServerBrowserAction::JoinServer {
+            addr,
+            password: resolving.password,
+            battleye_required: None,
+            id: None,
+            name: Some(resolving.host_name),
+        });
+        self.window.clone().hide();
+    }
+
     fn create_gui<T: WidgetExt + Clone + 'static>(
         parent: &Group,
         title: &'static str,
         make_server_text_widget: impl FnOnce() -> T,
-    ) -> (Window, T, SecretInput, ReturnButton) {
+        check_label: Option<&'static str>,
+    ) -> (Window, T, SecretInput, Option<CheckButton>, ReturnButton) {
+        let height = if check_label.is_some() { 165 } else { 135 };
         let mut window = GridBuilder::with_factory(
-            Window::default().with_size(480, 135).with_label(title),
+            Window::default().with_size(480, height).with_label(title),
             wrapper_factory(),
         )
         .with_col_spacing(10)
@@ -133,6 +278,16 @@ impl ConnectDialog {
             .with_label("Password:");
         let password_text = window.span(1, 3).unwrap().wrap(SecretInput::default());
 
+        let check = check_label.map(|label| {
+            window.row().add();
+            window.cell().unwrap().skip();
+            window
+                .span(1, 3)
+                .unwrap()
+                .wrap(CheckButton::default())
+                .with_label(label)
+        });
+
         window
             .row()
             .with_default_align(CellAlign::End)
@@ -164,8 +319,23 @@ impl ConnectDialog {
             move |_| window.hide()
         });
 
-        (window, server_text, password_text, ok_button)
+        (window, server_text, password_text, check, ok_button)
+    }
+}
+
+fn make_favorite(host_name: Option<String>, addr: SocketAddr) -> FavoriteServer {
+    FavoriteServer {
+        name: host_name.clone(),
+        host_name,
+        ip: Some(addr.ip()),
+        port: Some(addr.port() as _),
+        id: None,
+        notes: None,
+        tags: Vec::new(),
     }
 }
 
 const ERR_INVALID_ADDR: &str = "Invalid server address.";
+const ERR_RESOLVING_HOST: &str = "Could not find the server.";
+const ERR_ADDR_FORMAT: &str = "Enter the address as host:port, e.g. play.example.com:7777, \
+                               203.0.113.7:7777 or [2001:db8::7]:7777.";
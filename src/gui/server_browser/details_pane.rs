@@ -116,6 +116,76 @@ impl DetailsPane {
         }
     }
 
+    fn inspect_changes(
+        &self,
+        server: Option<&Server>,
+        row_consumer: &mut dyn FnMut(DetailRow),
+        include_empty: bool,
+    ) {
+        let mut header = "What Changed";
+
+        let changes = match server.map(|server| &server.changes) {
+            Some(changes) if !changes.is_empty() => changes,
+            _ => {
+                if include_empty {
+                    row_consumer([header.into(), "".into()]);
+                }
+                return;
+            }
+        };
+
+        let mut rows: Vec<Cow<'static, str>> = Vec::new();
+        if changes.added {
+            rows.push("new in the server list".into());
+        }
+        if changes.removed {
+            rows.push("no longer in the server list".into());
+        }
+        if let Some(old_name) = &changes.renamed_from {
+            rows.push(format!("renamed from {}", old_name).into());
+        }
+        if let Some(old_map) = &changes.map_changed_from {
+            rows.push(format!("map changed from {}", old_map).into());
+        }
+
+        let mut resolution: Vec<(u64, Option<String>)> = changes
+            .mods_added
+            .iter()
+            .chain(changes.mods_removed.iter())
+            .filter_map(|entry| entry.parse().ok())
+            .map(|id| (id, None))
+            .collect();
+        Rc::clone(&self.mod_resolver).resolve(&mut resolution);
+        let mod_name = |entry: &str| {
+            resolution
+                .iter()
+                .find(|(id, _)| entry.parse::<u64>().ok() == Some(*id))
+                .and_then(|(_, name)| name.clone())
+                .unwrap_or_else(|| entry.to_string())
+        };
+        for entry in &changes.mods_added {
+            rows.push(format!("mod added: {}", mod_name(entry)).into());
+        }
+        for entry in &changes.mods_removed {
+            rows.push(format!("mod removed: {}", mod_name(entry)).into());
+        }
+
+        for setting in &changes.settings {
+            rows.push(
+                format!(
+                    "{}: {} \u{2192} {}",
+                    setting.name, setting.old_value, setting.new_value
+                )
+                .into(),
+            );
+        }
+
+        for row in rows {
+            row_consumer([header.into(), row]);
+            header = "";
+        }
+    }
+
     fn inspect_mods(
         &self,
         server: Option<&Server>,
@@ -211,9 +281,14 @@ const SERVER_DETAILS_ROWS: &[Inspector] = &[
     inspect_attr!("ID", |server| server.id.clone().into()),
     inspect_attr!("Server Name", |server| server.name.clone().into()),
     inspect_attr!("Host", |server| server.host().into()),
+    inspect_attr!("Host Name", |server| server.host_name.clone()),
+    DetailsPane::inspect_changes,
     inspect_attr!("Map Name", |server| server.map.clone().into()),
     inspect_attr!("Mode", |server| mode_name(server.mode()).into()),
     inspect_attr!("Region", |server| region_name(server.region).into()),
+    inspect_opt_attr!("Inferred Region", |server| server
+        .inferred_region
+        .map(|region| region_name(region).into())),
     inspect_attr!("Max Clan Size", |server| {
         server
             .max_clan_size
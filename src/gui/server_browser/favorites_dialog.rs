@@ -0,0 +1,513 @@
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::rc::Rc;
+
+use anyhow::Result;
+use fltk::app;
+use fltk::button::{Button, ReturnButton};
+use fltk::dialog::{FileDialogOptions, FileDialogType, NativeFileChooser};
+use fltk::enums::{Align, CallbackTrigger, Color};
+use fltk::frame::Frame;
+use fltk::group::Group;
+use fltk::input::Input;
+use fltk::misc::InputChoice;
+use fltk::prelude::*;
+use fltk::table::TableContext;
+use fltk::window::Window;
+use fltk_float::grid::{CellAlign, GridBuilder};
+use fltk_float::SimpleWrapper;
+
+use crate::gui::widgets::{DataColumn, DataTable, DataTableProperties, DataTableUpdate};
+use crate::gui::{alert_error, is_table_nav_event, wrapper_factory};
+use crate::servers::{
+    export_favorites, import_favorites, parse_tags, upsert_favorite, FavoriteServer,
+};
+
+pub struct FavoritesDialog {
+    window: Window,
+    result: Rc<RefCell<Option<Vec<FavoriteServer>>>>,
+}
+
+type FavoriteRow = [String; 5];
+
+struct Inner {
+    table: DataTable<FavoriteRow>,
+    group_input: InputChoice,
+    tags_input: Input,
+    notes_input: Input,
+    remove_button: Button,
+    state: RefCell<DialogState>,
+}
+
+struct DialogState {
+    favorites: Vec<FavoriteServer>,
+    listed: Vec<FavoriteServer>,
+    groups: Vec<String>,
+    group_filter: GroupFilter,
+    rows: Vec<usize>,
+    selected: Option<usize>,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+enum GroupFilter {
+    All,
+    Ungrouped,
+    Group(String),
+}
+
+impl FavoritesDialog {
+    pub fn new(
+        parent: &Group,
+        favorites: Vec<FavoriteServer>,
+        listed: Vec<FavoriteServer>,
+    ) -> Self {
+        let mut grid = GridBuilder::with_factory(
+            Window::default()
+                .with_size(760, 480)
+                .with_label("Favorite Servers"),
+            wrapper_factory(),
+        )
+        .with_col_spacing(10)
+        .with_row_spacing(10)
+        .with_padding(10, 10, 10, 10);
+        grid.col().with_default_align(CellAlign::End).add();
+        grid.col().with_stretch(1).add();
+        let btn_group = grid.col_group().add();
+        grid.extend_group(btn_group).batch(3);
+
+        grid.row().add();
+        grid.cell()
+            .unwrap()
+            .wrap(Frame::default())
+            .with_label("Group:");
+        let mut group_input = grid.cell().unwrap().wrap(InputChoice::default());
+        group_input.input().set_readonly(true);
+        group_input.input().clear_visible_focus();
+        grid.cell().unwrap().skip();
+        let mut import_button = grid
+            .cell()
+            .unwrap()
+            .wrap(Button::default())
+            .with_label("Import...")
+            .with_tooltip("Add the favorites from a file shared by someone else");
+        let mut export_button = grid
+            .cell()
+            .unwrap()
+            .wrap(Button::default())
+            .with_label("Export...")
+            .with_tooltip("Save the favorites in the selected group to a file you can share");
+
+        grid.row()
+            .with_stretch(1)
+            .with_default_align(CellAlign::Stretch)
+            .add();
+        let mut table = DataTable::default().with_properties(DataTableProperties {
+            columns: vec![
+                DataColumn::from(("Name", Align::Left)).with_width(220),
+                DataColumn::from(("Address", Align::Left)).with_width(180),
+                DataColumn::from(("Tags", Align::Left)).with_width(110),
+                DataColumn::from(("Notes", Align::Left)).with_width(140),
+                DataColumn::from(("Status", Align::Left)).with_width(70),
+            ],
+            cell_padding: 4,
+            cell_selection_color: Color::Free,
+            header_font_color: Color::Gray0,
+            ..Default::default()
+        });
+        table.set_row_header(false);
+        table.set_col_header(true);
+        table.set_col_resize(true);
+        table.end();
+        grid.span(1, 5).unwrap().add(SimpleWrapper::new(
+            table.as_base_widget(),
+            Default::default(),
+        ));
+
+        grid.row().add();
+        grid.cell()
+            .unwrap()
+            .wrap(Frame::default())
+            .with_label("Tags:");
+        let mut tags_input = grid
+            .span(1, 4)
+            .unwrap()
+            .wrap(Input::default())
+            .with_tooltip(
+                "Comma-separated tags; the first one is the group the server is listed under",
+            );
+        tags_input.set_trigger(CallbackTrigger::Changed);
+
+        grid.row().add();
+        grid.cell()
+            .unwrap()
+            .wrap(Frame::default())
+            .with_label("Notes:");
+        let mut notes_input = grid.span(1, 4).unwrap().wrap(Input::default());
+        notes_input.set_trigger(CallbackTrigger::Changed);
+
+        grid.row().add();
+        grid.span(1, 2).unwrap().skip();
+        let mut remove_button = grid
+            .cell()
+            .unwrap()
+            .wrap(Button::default())
+            .with_label("Remove")
+            .with_tooltip("Remove the selected server from your favorites");
+        let mut ok_button = grid
+            .cell()
+            .unwrap()
+            .wrap(ReturnButton::default())
+            .with_label("OK");
+        let mut cancel_button = grid
+            .cell()
+            .unwrap()
+            .wrap(Button::default())
+            .with_label("Cancel");
+
+        let grid = grid.end();
+        grid.layout_children();
+
+        let mut window = grid.group();
+        window.set_pos(
+            parent.x() + (parent.w() - window.w()) / 2,
+            parent.y() + (parent.h() - window.h()) / 2,
+        );
+
+        let inner = Rc::new(Inner {
+            table: table.clone(),
+            group_input: group_input.clone(),
+            tags_input: tags_input.clone(),
+            notes_input: notes_input.clone(),
+            remove_button: remove_button.clone(),
+            state: RefCell::new(DialogState {
+                favorites,
+                listed,
+                groups: Vec::new(),
+                group_filter: GroupFilter::All,
+                rows: Vec::new(),
+                selected: None,
+            }),
+        });
+        inner.refresh();
+
+        let result = Rc::new(RefCell::new(None));
+
+        group_input.set_callback({
+            let inner = Rc::clone(&inner);
+            move |input| {
+                let idx = input.menu_button().value();
+                {
+                    let mut state = inner.state.borrow_mut();
+                    state.group_filter = match idx {
+                        0 => GroupFilter::All,
+                        1 => GroupFilter::Ungrouped,
+                        idx => match usize::try_from(idx - 2)
+                            .ok()
+                            .and_then(|idx| state.groups.get(idx))
+                        {
+                            Some(group) => GroupFilter::Group(group.clone()),
+                            None => return,
+                        },
+                    };
+                }
+                inner.refresh();
+            }
+        });
+
+        table.set_callback({
+            let inner = Rc::clone(&inner);
+            move |table| {
+                if is_table_nav_event() && table.callback_context() == TableContext::Cell {
+                    let _ = table.take_focus();
+                    inner.select(Some(table.callback_row() as _));
+                }
+            }
+        });
+
+        tags_input.set_callback({
+            let inner = Rc::clone(&inner);
+            move |input| {
+                let tags = parse_tags(&input.value());
+                inner.edit_selected(|favorite| favorite.tags = tags);
+                inner.populate_groups();
+            }
+        });
+
+        notes_input.set_callback({
+            let inner = Rc::clone(&inner);
+            move |input| {
+                let notes = input.value().trim().to_string();
+                let notes = if notes.is_empty() { None } else { Some(notes) };
+                inner.edit_selected(|favorite| favorite.notes = notes);
+            }
+        });
+
+        remove_button.set_callback({
+            let inner = Rc::clone(&inner);
+            move |_| inner.remove_selected()
+        });
+
+        import_button.set_callback({
+            let inner = Rc::clone(&inner);
+            move |_| inner.import()
+        });
+
+        export_button.set_callback({
+            let inner = Rc::clone(&inner);
+            move |_| inner.export()
+        });
+
+        ok_button.set_callback({
+            let inner = Rc::clone(&inner);
+            let result = Rc::clone(&result);
+            let mut window = window.clone();
+            move |_| {
+                *result.borrow_mut() = Some(inner.state.borrow().favorites.clone());
+                window.hide();
+            }
+        });
+
+        cancel_button.set_callback({
+            let mut window = window.clone();
+            move |_| window.hide()
+        });
+
+        Self { window, result }
+    }
+
+    pub fn run(&self) -> Option<Vec<FavoriteServer>> {
+        let mut window = self.window.clone();
+        window.make_modal(true);
+        window.show();
+
+        while window.shown() {
+            app::wait();
+        }
+
+        self.result.borrow_mut().take()
+    }
+}
+
+impl Inner {
+    fn refresh(&self) {
+        self.populate_groups();
+
+        let mut state = self.state.borrow_mut();
+        let selected = state.selected.map(|row| state.rows[row]);
+
+        let mut rows: Vec<usize> = (0..state.favorites.len())
+            .filter(|&idx| state.group_filter.matches(&state.favorites[idx]))
+            .collect();
+        rows.sort_by_cached_key(|&idx| {
+            let favorite = &state.favorites[idx];
+            (
+                favorite.group().is_none(),
+                favorite.group().map(str::to_lowercase),
+                display_name(favorite).to_lowercase(),
+            )
+        });
+        state.selected = selected.and_then(|idx| rows.iter().position(|&row| row == idx));
+        state.rows = rows;
+
+        {
+            let data = self.table.data();
+            let mut data = data.borrow_mut();
+            data.clear();
+            for &idx in state.rows.iter() {
+                data.push(make_row(&state.favorites[idx], &state.listed));
+            }
+        }
+        self.table.updated(DataTableUpdate::DATA);
+
+        let selected = state.selected;
+        drop(state);
+        self.select(selected);
+    }
+
+    fn populate_groups(&self) {
+        let mut state = self.state.borrow_mut();
+        let mut groups: Vec<String> = Vec::new();
+        for favorite_group in state.favorites.iter().filter_map(FavoriteServer::group) {
+            if !groups
+                .iter()
+                .any(|group| group.eq_ignore_ascii_case(favorite_group))
+            {
+                groups.push(favorite_group.to_string());
+            }
+        }
+        groups.sort_by_key(|group| group.to_lowercase());
+
+        if let GroupFilter::Group(selected) = &state.group_filter {
+            if !groups.iter().any(|group| group == selected) {
+                groups.push(selected.clone());
+            }
+        }
+
+        let mut group_input = self.group_input.clone();
+        group_input.clear();
+        group_input.add("All");
+        group_input.add("Ungrouped");
+        for group in groups.iter() {
+            group_input.add(&group.replace('/', "\\/"));
+        }
+        let selected_idx = match &state.group_filter {
+            GroupFilter::All => 0,
+            GroupFilter::Ungrouped => 1,
+            GroupFilter::Group(selected) => {
+                groups.iter().position(|group| group == selected).unwrap() + 2
+            }
+        };
+        group_input.set_value_index(selected_idx as _);
+        state.groups = groups;
+    }
+
+    fn select(&self, row: Option<usize>) {
+        let mut state = self.state.borrow_mut();
+        let row = row.filter(|&row| row < state.rows.len());
+        state.selected = row;
+
+        let mut table = self.table.clone();
+        let mut tags_input = self.tags_input.clone();
+        let mut notes_input = self.notes_input.clone();
+        let mut remove_button = self.remove_button.clone();
+        match row {
+            Some(row) => {
+                let favorite = &state.favorites[state.rows[row]];
+                table.set_selection(row as _, 0, row as _, 4);
+                tags_input.set_value(&favorite.tags.join(", "));
+                notes_input.set_value(favorite.notes.as_deref().unwrap_or_default());
+                tags_input.activate();
+                notes_input.activate();
+                remove_button.activate();
+            }
+            None => {
+                table.unset_selection();
+                tags_input.set_value("");
+                notes_input.set_value("");
+                tags_input.deactivate();
+                notes_input.deactivate();
+                remove_button.deactivate();
+            }
+        }
+    }
+
+    fn edit_selected(&self, edit: impl FnOnce(&mut FavoriteServer)) {
+        let mut state = self.state.borrow_mut();
+        let row = match state.selected {
+            Some(row) => row,
+            None => return,
+        };
+        let idx = state.rows[row];
+        edit(&mut state.favorites[idx]);
+
+        let updated_row = make_row(&state.favorites[idx], &state.listed);
+        self.table.data().borrow_mut()[row] = updated_row;
+        self.table.updated(DataTableUpdate::DATA);
+    }
+
+    fn remove_selected(&self) {
+        {
+            let mut state = self.state.borrow_mut();
+            let row = match state.selected {
+                Some(row) => row,
+                None => return,
+            };
+            let idx = state.rows[row];
+            state.favorites.remove(idx);
+            state.selected = None;
+        }
+        self.refresh();
+    }
+
+    fn import(&self) {
+        let mut dialog = NativeFileChooser::new(FileDialogType::BrowseFile);
+        dialog.set_filter(DLG_FILTER_FAVORITES);
+        dialog.show();
+
+        let path = dialog.filename();
+        if path.as_os_str().is_empty() {
+            return;
+        }
+
+        let imported = match File::open(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|file| import_favorites(BufReader::new(file)))
+        {
+            Ok(imported) => imported,
+            Err(err) => return alert_error(ERR_IMPORTING_FAVORITES, &err),
+        };
+        {
+            let mut state = self.state.borrow_mut();
+            for favorite in imported {
+                upsert_favorite(&mut state.favorites, favorite);
+            }
+            state.selected = None;
+        }
+        self.refresh();
+    }
+
+    fn export(&self) {
+        let mut dialog = NativeFileChooser::new(FileDialogType::BrowseSaveFile);
+        dialog.set_filter(DLG_FILTER_FAVORITES);
+        dialog.set_option(FileDialogOptions::SaveAsConfirm);
+        dialog.show();
+
+        let mut path = dialog.filename();
+        if path.as_os_str().is_empty() {
+            return;
+        }
+        if path.extension().is_none() {
+            path.set_extension("txt");
+        }
+
+        let favorites: Vec<FavoriteServer> = {
+            let state = self.state.borrow();
+            state
+                .rows
+                .iter()
+                .map(|&idx| state.favorites[idx].clone())
+                .collect()
+        };
+        let result: Result<()> = File::create(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|file| export_favorites(&favorites, BufWriter::new(file)));
+        if let Err(err) = result {
+            alert_error(ERR_EXPORTING_FAVORITES, &err);
+        }
+    }
+}
+
+impl GroupFilter {
+    fn matches(&self, favorite: &FavoriteServer) -> bool {
+        match self {
+            Self::All => true,
+            Self::Ungrouped => favorite.group().is_none(),
+            Self::Group(group) => favorite.is_in_group(group),
+        }
+    }
+}
+
+fn display_name(favorite: &FavoriteServer) -> String {
+    favorite
+        .name
+        .clone()
+        .or_else(|| favorite.address())
+        .or_else(|| favorite.id.clone())
+        .unwrap_or_default()
+}
+
+fn make_row(favorite: &FavoriteServer, listed: &[FavoriteServer]) -> FavoriteRow {
+    let is_listed = listed.iter().any(|server| server.matches(favorite));
+    [
+        display_name(favorite),
+        favorite.address().unwrap_or_else(|| "???".to_string()),
+        favorite.tags.join(", "),
+        favorite.notes.clone().unwrap_or_default(),
+        if is_listed { "Listed" } else { "Not listed" }.to_string(),
+    ]
+}
+
+const DLG_FILTER_FAVORITES: &str = "Favorite Server Lists\t*.txt";
+const ERR_IMPORTING_FAVORITES: &str = "Error while importing favorites.";
+const ERR_EXPORTING_FAVORITES: &str = "Error while exporting favorites.";
@@ -2,8 +2,9 @@ use std::borrow::Cow;
 use std::rc::Rc;
 use std::sync::Arc;
 
-use fltk::button::CheckButton;
-use fltk::enums::{CallbackTrigger, Event};
+use fltk::button::{Button, CheckButton};
+use fltk::dialog;
+use fltk::enums::{Align, CallbackTrigger, Color, Event};
 use fltk::frame::Frame;
 use fltk::input::Input;
 use fltk::misc::InputChoice;
@@ -13,7 +14,8 @@ use fltk_float::LayoutElement;
 use strum::IntoEnumIterator;
 
 use crate::game::Maps;
-use crate::gui::{glyph, wrapper_factory};
+use crate::gui::prelude::*;
+use crate::gui::{glyph, prompt_confirm, wrapper_factory};
 use crate::servers::{Mode, Region, TypeFilter};
 
 use super::state::Filter;
@@ -23,19 +25,29 @@ pub(super) trait FilterHolder {
     fn access_filter(&self, accessor: impl FnOnce(&Filter));
     fn mutate_filter(&self, mutator: impl FnMut(&mut Filter));
     fn persist_filter(&self);
+    fn filter_presets(&self) -> Vec<String>;
+    fn apply_filter_preset(&self, name: &str);
+    fn save_filter_preset(&self, name: String);
+    fn delete_filter_preset(&self, name: &str);
 }
 
 pub(super) struct FilterPane {
     grid: Grid,
+    preset_input: InputChoice,
+    save_preset_button: Button,
+    delete_preset_button: Button,
     name_input: Input,
     map_input: InputChoice,
     type_input: InputChoice,
     mode_input: InputChoice,
     region_input: InputChoice,
+    inferred_region_check: CheckButton,
     battleye_input: InputChoice,
     invalid_check: CheckButton,
     pwd_prot_check: CheckButton,
     mods_input: InputChoice,
+    expression_input: Input,
+    expression_error: Frame,
 }
 
 impl FilterPane {
@@ -51,12 +63,43 @@ impl FilterPane {
         grid.col().with_stretch(1).add();
         grid.col().add();
 
+        grid.row().add();
+        grid.cell()
+            .unwrap()
+            .wrap(Frame::default())
+            .with_label("Preset:");
+        let mut preset_input = grid.span(1, 4).unwrap().wrap(InputChoice::default());
+        preset_input.input().set_readonly(true);
+        preset_input.input().clear_visible_focus();
+        let save_preset_button = grid
+            .cell()
+            .unwrap()
+            .wrap(Button::default())
+            .with_label("Save As...")
+            .with_tooltip("Save the current filter as a named preset");
+        let delete_preset_button = grid
+            .cell()
+            .unwrap()
+            .with_horz_align(CellAlign::Start)
+            .wrap(Button::default())
+            .with_label("Delete Preset")
+            .with_tooltip("Delete the selected filter preset");
+
         grid.row().add();
         grid.cell()
             .unwrap()
             .wrap(Frame::default())
             .with_label("Server Name:");
-        let name_input = grid.span(1, 6).unwrap().wrap(Input::default());
+        let name_input = grid.span(1, 5).unwrap().wrap(Input::default());
+        let inferred_region_check = grid
+            .cell()
+            .unwrap()
+            .wrap(CheckButton::default())
+            .with_label("Use inferred region")
+            .with_tooltip(
+                "Filter by the region servers are inferred to be in from their address and ping, \
+            instead of the region they declare",
+            );
 
         grid.row().add();
         grid.cell()
@@ -138,19 +181,45 @@ impl FilterPane {
             .wrap(CheckButton::default())
             .with_label(&format!("{} Show password protected servers", glyph::LOCK));
 
+        grid.row().add();
+        grid.cell()
+            .unwrap()
+            .wrap(Frame::default())
+            .with_label("Expression:");
+        let expression_input = grid
+            .span(1, 6)
+            .unwrap()
+            .wrap(Input::default())
+            .with_tooltip(EXPRESSION_TOOLTIP);
+
+        grid.row().add();
+        grid.cell().unwrap().skip();
+        let mut expression_error = grid
+            .span(1, 6)
+            .unwrap()
+            .wrap(Frame::default())
+            .with_align(Align::Left | Align::Inside);
+        expression_error.set_label_color(Color::Red);
+
         let grid = grid.end();
 
         Rc::new(Self {
             grid,
+            preset_input,
+            save_preset_button,
+            delete_preset_button,
             name_input,
             map_input,
             type_input,
             mode_input,
             region_input,
+            inferred_region_check,
             battleye_input,
             invalid_check,
             pwd_prot_check,
             mods_input,
+            expression_input,
+            expression_error,
         })
     }
 
@@ -160,7 +229,8 @@ impl FilterPane {
         }
     }
 
-    pub fn set_filter_holder(&self, filter_holder: Rc<impl FilterHolder + 'static>) {
+    pub fn set_filter_holder(self: &Rc<Self>, filter_holder: Rc<impl FilterHolder + 'static>) {
+        self.populate_presets(&filter_holder.filter_presets(), None);
         filter_holder.access_filter(|filter| self.populate(filter));
         self.set_callbacks(filter_holder);
     }
@@ -183,6 +253,9 @@ impl FilterPane {
                 Some(region) => (region as i32) + 1,
                 None => 0,
             });
+        self.inferred_region_check
+            .clone()
+            .set_checked(filter.use_inferred_region());
         self.battleye_input
             .clone()
             .set_value_index(match filter.battleye_required() {
@@ -203,9 +276,85 @@ impl FilterPane {
                 Some(false) => 1,
                 Some(true) => 2,
             });
+        self.expression_input.clone().set_value(filter.expression());
+        self.show_expression_error(filter);
+    }
+
+    fn populate_presets(&self, presets: &[String], selected: Option<&str>) {
+        let mut preset_input = self.preset_input.clone();
+        preset_input.clear();
+        for preset in presets {
+            preset_input.add(&preset.replace('/', "\\/"));
+        }
+        preset_input.set_value(selected.unwrap_or_default());
+        self.delete_preset_button
+            .clone()
+            .set_activated(selected.is_some());
+    }
+
+    fn show_expression_error(&self, filter: &Filter) {
+        let mut expression_error = self.expression_error.clone();
+        expression_error.set_label(filter.expression_error().unwrap_or_default());
+        expression_error.redraw();
     }
 
-    fn set_callbacks(&self, filter_holder: Rc<impl FilterHolder + 'static>) {
+    fn set_callbacks(self: &Rc<Self>, filter_holder: Rc<impl FilterHolder + 'static>) {
+        {
+            let this = Rc::downgrade(self);
+            let filter_holder = Rc::downgrade(&filter_holder);
+            let mut preset_input = self.preset_input.clone();
+            preset_input.set_trigger(CallbackTrigger::Changed);
+            preset_input.set_callback(move |input| {
+                if let (Some(this), Some(filter_holder)) = (this.upgrade(), filter_holder.upgrade())
+                {
+                    let presets = filter_holder.filter_presets();
+                    let idx = input.menu_button().value();
+                    if let Some(name) = usize::try_from(idx).ok().and_then(|idx| presets.get(idx)) {
+                        filter_holder.apply_filter_preset(name);
+                        filter_holder.access_filter(|filter| this.populate(filter));
+                        this.populate_presets(&presets, Some(name));
+                    }
+                }
+            });
+        }
+        {
+            let this = Rc::downgrade(self);
+            let filter_holder = Rc::downgrade(&filter_holder);
+            self.save_preset_button.clone().set_callback(move |_| {
+                if let (Some(this), Some(filter_holder)) = (this.upgrade(), filter_holder.upgrade())
+                {
+                    let current = this.preset_input.value().unwrap_or_default();
+                    let name = match dialog::input_default(PROMPT_PRESET_NAME, &current) {
+                        Some(name) if !name.trim().is_empty() => name.trim().to_string(),
+                        _ => return,
+                    };
+                    let presets = filter_holder.filter_presets();
+                    if presets.contains(&name) && !prompt_confirm(PROMPT_REPLACE_PRESET) {
+                        return;
+                    }
+                    filter_holder.save_filter_preset(name.clone());
+                    this.populate_presets(&filter_holder.filter_presets(), Some(&name));
+                }
+            });
+        }
+        {
+            let this = Rc::downgrade(self);
+            let filter_holder = Rc::downgrade(&filter_holder);
+            self.delete_preset_button.clone().set_callback(move |_| {
+                if let (Some(this), Some(filter_holder)) = (this.upgrade(), filter_holder.upgrade())
+                {
+                    let name = match this.preset_input.value() {
+                        Some(name) if !name.is_empty() => name,
+                        _ => return,
+                    };
+                    if !prompt_confirm(PROMPT_DELETE_PRESET) {
+                        return;
+                    }
+                    filter_holder.delete_filter_preset(&name);
+                    this.populate_presets(&filter_holder.filter_presets(), None);
+                }
+            });
+        }
         {
             let mut name_input = self.name_input.clone();
             name_input.set_trigger(CallbackTrigger::Changed);
@@ -289,6 +438,18 @@ impl FilterPane {
                 }
             });
         }
+        {
+            let filter_holder = Rc::downgrade(&filter_holder);
+            let mut inferred_region_check = self.inferred_region_check.clone();
+            inferred_region_check.set_trigger(CallbackTrigger::Changed);
+            inferred_region_check.set_callback(move |input| {
+                if let Some(filter_holder) = filter_holder.upgrade() {
+                    filter_holder
+                        .mutate_filter(|filter| filter.set_use_inferred_region(input.is_checked()));
+                    filter_holder.persist_filter();
+                }
+            })
+        }
         {
             let filter_holder = Rc::downgrade(&filter_holder);
             let mut battleye_input = self.battleye_input.clone();
@@ -346,6 +507,23 @@ impl FilterPane {
                 }
             })
         }
+        {
+            let mut expression_input = self.expression_input.clone();
+            expression_input.set_trigger(CallbackTrigger::Changed);
+            {
+                let this = Rc::downgrade(self);
+                let filter_holder = Rc::downgrade(&filter_holder);
+                expression_input.set_callback(move |input| {
+                    if let (Some(this), Some(filter_holder)) =
+                        (this.upgrade(), filter_holder.upgrade())
+                    {
+                        filter_holder.mutate_filter(|filter| filter.set_expression(input.value()));
+                        filter_holder.access_filter(|filter| this.show_expression_error(filter));
+                    }
+                });
+            }
+            set_unfocus_handler(&mut expression_input, &filter_holder);
+        }
     }
 }
 
@@ -372,6 +550,16 @@ fn type_name(type_filter: TypeFilter) -> Cow<'static, str> {
     }
 }
 
+const PROMPT_PRESET_NAME: &str = "Preset name:";
+const PROMPT_REPLACE_PRESET: &str = "Are you sure you want to overwrite this preset?";
+const PROMPT_DELETE_PRESET: &str = "Are you sure you want to delete this preset?";
+
+const EXPRESSION_TOOLTIP: &str = "Filter servers with conditions such as:\n\
+    players >= 10 and xp_rate > 2\n\
+    raid_hours.sat overlaps 18:00-22:00\n\
+    not mods contains 880454836\n\
+Conditions can be combined with 'and', 'or', 'not' and parentheses.";
+
 fn set_unfocus_handler<W: WidgetBase>(
     widget: &mut W,
     filter_holder: &Rc<impl FilterHolder + 'static>,
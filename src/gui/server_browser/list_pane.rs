@@ -1,9 +1,9 @@
 use std::borrow::Cow;
 use std::cell::RefCell;
-use std::collections::HashMap;
 use std::ffi::CString;
 use std::rc::Rc;
 
+use fltk::app;
 use fltk::enums::{Align, Event};
 use fltk::frame::Frame;
 use fltk::misc::Tooltip;
@@ -23,9 +23,9 @@ type ServerRow = [Cow<'static, str>; NUM_COLS];
 pub(super) struct ListPane {
     table: DataTable<ServerRow>,
     loading_label: Frame,
-    sort_criteria: RefCell<SortCriteria>,
+    sort_criteria: RefCell<Vec<SortCriteria>>,
     server_list: RefCell<Rc<RefCell<dyn TableSource<Output = Server>>>>,
-    on_sort_changed: RefCell<Box<dyn Fn(SortCriteria)>>,
+    on_sort_changed: RefCell<Box<dyn Fn(Vec<SortCriteria>)>>,
     on_server_selected: RefCell<Box<dyn Fn(Option<&Server>)>>,
     selection: RefCell<Selection>,
 }
@@ -36,15 +36,10 @@ struct Selection {
 }
 
 impl ListPane {
-    pub fn new(initial_sort: &SortCriteria, scroll_lock: bool) -> Rc<Self> {
-        let sorted_col = sort_key_to_column(initial_sort.key);
+    pub fn new(initial_sort: &[SortCriteria], scroll_lock: bool) -> Rc<Self> {
         let columns = SERVER_LIST_COLS
             .iter()
-            .enumerate()
-            .map(|(idx, col)| {
-                let ascending = if idx == sorted_col { Some(initial_sort.ascending) } else { None };
-                col.to_data_column(ascending)
-            })
+            .map(|col| col.to_data_column(col.sort_state(initial_sort)))
             .collect();
         let mut table = DataTable::default().with_properties(DataTableProperties {
             columns,
@@ -68,7 +63,7 @@ impl ListPane {
         let list_pane = Rc::new(Self {
             table: table.clone(),
             loading_label,
-            sort_criteria: RefCell::new(*initial_sort),
+            sort_criteria: RefCell::new(initial_sort.to_vec()),
             server_list: RefCell::new(Rc::new(RefCell::new(Vec::new()))),
             on_sort_changed: RefCell::new(Box::new(|_| ())),
             on_server_selected: RefCell::new(Box::new(|_| ())),
@@ -142,7 +137,7 @@ impl ListPane {
         }
     }
 
-    pub fn set_on_sort_changed(&self, on_sort_changed: impl Fn(SortCriteria) + 'static) {
+    pub fn set_on_sort_changed(&self, on_sort_changed: impl Fn(Vec<SortCriteria>) + 'static) {
         *self.on_sort_changed.borrow_mut() = Box::new(on_sort_changed);
     }
 
@@ -225,26 +220,36 @@ impl ListPane {
             Some(key) => key,
             None => return,
         };
-        let old_criteria = *self.sort_criteria.borrow();
-        let new_criteria = if new_key == old_criteria.key {
-            old_criteria.reversed()
-        } else {
-            SortCriteria {
+        let mut new_criteria = self.sort_criteria.borrow().clone();
+        let existing_idx = new_criteria
+            .iter()
+            .position(|criteria| criteria.key == new_key);
+        match existing_idx {
+            // Shift-clicking a column adds it as a secondary sort key, or flips its direction if
+            // the list is already sorted by it.
+            Some(idx) if app::is_event_shift() || idx == 0 => {
+                new_criteria[idx] = new_criteria[idx].reversed();
+            }
+            None if app::is_event_shift() => new_criteria.push(SortCriteria {
                 key: new_key,
                 ascending: true,
+            }),
+            _ => {
+                new_criteria = vec![SortCriteria {
+                    key: new_key,
+                    ascending: true,
+                }]
             }
-        };
+        }
         {
             let props = self.table.properties();
             let mut props = props.borrow_mut();
-            if old_criteria.key != new_criteria.key {
-                let old_col = sort_key_to_column(old_criteria.key);
-                props.columns[old_col].header = SERVER_LIST_COLS[old_col].header(None);
+            for (col, column) in SERVER_LIST_COLS.iter().enumerate() {
+                props.columns[col].header = column.header(column.sort_state(&new_criteria));
             }
-            props.columns[col].header = SERVER_LIST_COLS[col].header(Some(new_criteria.ascending));
         }
         self.table.updated(DataTableUpdate::PROPERTIES);
-        *self.sort_criteria.borrow_mut() = new_criteria;
+        *self.sort_criteria.borrow_mut() = new_criteria.clone();
         self.on_sort_changed.borrow()(new_criteria);
     }
 
@@ -268,10 +273,10 @@ impl ListPane {
         match event {
             Event::Move => {
                 let mut new_pos = match self.table.cursor2rowcol() {
-                    Some((TableContext::ColHeader, row, col, _)) if col < 6 => {
+                    Some((TableContext::ColHeader, row, col, _)) if col < NUM_GLYPH_COLS => {
                         Some((TableContext::ColHeader, row, col))
                     }
-                    Some((TableContext::Cell, row, col, _)) if col < 6 => {
+                    Some((TableContext::Cell, row, col, _)) if col < NUM_GLYPH_COLS => {
                         Some((TableContext::Cell, row, col))
                     }
                     _ => None,
@@ -311,6 +316,12 @@ impl ListPane {
     }
 }
 
+#[derive(Clone, Copy)]
+struct SortState {
+    ascending: bool,
+    rank: Option<usize>,
+}
+
 struct Column {
     header: &'static str,
     width: i32,
@@ -340,25 +351,40 @@ impl Column {
         (self.value_fn)(server)
     }
 
-    fn header(&self, ascending: Option<bool>) -> String {
+    fn sort_state(&self, criteria: &[SortCriteria]) -> Option<SortState> {
+        let sort_key = self.sort_key?;
+        criteria
+            .iter()
+            .position(|criteria| criteria.key == sort_key)
+            .map(|idx| SortState {
+                ascending: criteria[idx].ascending,
+                rank: if criteria.len() > 1 { Some(idx + 1) } else { None },
+            })
+    }
+
+    fn header(&self, sort_state: Option<SortState>) -> String {
         if self.sort_key.is_none() {
             return self.header.to_string();
         }
 
-        format!(
-            "{} {}",
-            self.header,
-            match ascending {
-                None => glyph::UNSORTED,
-                Some(false) => glyph::DESC,
-                Some(true) => glyph::ASC,
-            }
-        )
+        let glyph = match sort_state {
+            None => glyph::UNSORTED,
+            Some(SortState {
+                ascending: false, ..
+            }) => glyph::DESC,
+            Some(SortState {
+                ascending: true, ..
+            }) => glyph::ASC,
+        };
+        match sort_state.and_then(|state| state.rank) {
+            Some(rank) => format!("{} {}{}", self.header, glyph, rank),
+            None => format!("{} {}", self.header, glyph),
+        }
     }
 
-    fn to_data_column(&self, ascending: Option<bool>) -> DataColumn {
+    fn to_data_column(&self, sort_state: Option<SortState>) -> DataColumn {
         DataColumn::default()
-            .with_header(self.header(ascending))
+            .with_header(self.header(sort_state))
             .with_align(self.align)
             .with_width(self.width)
     }
@@ -378,40 +404,33 @@ const SERVER_LIST_COLS: &[Column] = &[
     col!(glyph::FLAG, 20, Center, None, |server| str_if(server.is_official(), glyph::FLAG)),
     col!(glyph::EYE, 20, Center, None, |server| str_if(server.battleye_required, glyph::EYE)),
     col!(glyph::HEART, 20, Center, None, |server| str_if(server.favorite, glyph::HEART)),
-    col!("Server Name", 380, Left, Some(SortKey::Name), |server| server.name.clone().into()),
-    col!("Map", 150, Center, Some(SortKey::Map), |server| server.map.clone().into()),
+    col!(glyph::CHANGED, 20, Center, None, changes_col_value),
+    col!("Server Name", 280, Left, Some(SortKey::Name), |server| server.name.clone().into()),
+    col!("Map", 130, Center, Some(SortKey::Map), |server| server.map.clone().into()),
     col!("Mode", 80, Center, Some(SortKey::Mode), |server| mode_name(server.mode()).into()),
-    col!("Region", 80, Center, Some(SortKey::Region), |server| region_name(server.region).into()),
+    col!("Region", 80, Center, Some(SortKey::Region), region_col_value),
     col!("Players", 70, Center, Some(SortKey::Players), |server| players_col_value(server).into()),
     col!("Age", 60, Center, Some(SortKey::Age), |server| age_col_value(server).into()),
     col!("Ping", 60, Center, Some(SortKey::Ping), |server| ping_col_value(server).into()),
+    col!("XP", 50, Center, Some(SortKey::XpRate), |server| server.xp_rate_mult.to_string().into()),
+    col!("Harvest", 60, Center, Some(SortKey::HarvestAmount), |server| server.harvesting.harvest_amount_mult.to_string().into()),
+    col!("Clan", 50, Center, Some(SortKey::MaxClanSize), |server| clan_col_value(server).into()),
 ];
 const NUM_COLS: usize = SERVER_LIST_COLS.len();
+const NUM_GLYPH_COLS: i32 = 7;
 
 lazy_static! {
-    static ref SORT_KEY_TO_COLUMN: HashMap<SortKey, usize> = {
-        let mut map = HashMap::new();
-        for col in 0..SERVER_LIST_COLS.len() {
-            if let Some(sort_key) = column_to_sort_key(col) {
-                map.insert(sort_key, col);
-            }
-        }
-        map
-    };
-    static ref COL_TOOLTIPS: [CString; 6] = [
+    static ref COL_TOOLTIPS: [CString; 7] = [
         CString::new("Invalid").unwrap(),
         CString::new("Password protected").unwrap(),
         CString::new("Modded").unwrap(),
         CString::new("Official").unwrap(),
         CString::new("BattlEye required").unwrap(),
         CString::new("Favorite").unwrap(),
+        CString::new("New, removed, or changed since the last refresh").unwrap(),
     ];
 }
 
-fn sort_key_to_column(sort_key: SortKey) -> usize {
-    *SORT_KEY_TO_COLUMN.get(&sort_key).unwrap()
-}
-
 fn column_to_sort_key(col: usize) -> Option<SortKey> {
     SERVER_LIST_COLS[col].sort_key
 }
@@ -420,6 +439,26 @@ fn str_if(condition: bool, str_true: &'static str) -> Cow<'static, str> {
     (if condition { str_true } else { "" }).into()
 }
 
+fn changes_col_value(server: &Server) -> Cow<'static, str> {
+    let changes = &server.changes;
+    if changes.added {
+        glyph::NEW.into()
+    } else if changes.removed {
+        glyph::GONE.into()
+    } else {
+        str_if(!changes.is_empty(), glyph::CHANGED)
+    }
+}
+
+// Servers whose region is inferred to differ from the declared one show the inferred region,
+// marked with an asterisk.
+fn region_col_value(server: &Server) -> Cow<'static, str> {
+    match server.inferred_region {
+        Some(region) => format!("{}*", region_name(region)).into(),
+        None => region_name(server.region).into(),
+    }
+}
+
 fn players_col_value(server: &Server) -> String {
     match server.connected_players {
         Some(players) => format!("{}/{}{}", players, server.max_players, pong_suffix(server)),
@@ -441,6 +480,13 @@ fn ping_col_value(server: &Server) -> String {
     }
 }
 
+fn clan_col_value(server: &Server) -> String {
+    match server.max_clan_size {
+        Some(size) => size.to_string(),
+        None => String::new(),
+    }
+}
+
 fn pong_suffix(server: &Server) -> &str {
     if server.waiting_for_pong {
         " @-1reload"
@@ -0,0 +1,111 @@
+use std::time::Duration;
+
+use fltk::button::Button;
+use fltk::enums::Align;
+use fltk::frame::Frame;
+use fltk::group::Group;
+use fltk::prelude::*;
+use fltk::window::Window;
+use fltk_float::grid::{CellAlign, GridBuilder};
+
+use crate::gui::wrapper_factory;
+use crate::servers::Server;
+
+pub struct QueueDialog {
+    window: Window,
+    status_text: Frame,
+}
+
+impl QueueDialog {
+    pub fn new(parent: &Group, server: &Server) -> Self {
+        let mut window = GridBuilder::with_factory(
+            Window::default()
+                .with_size(480, 135)
+                .with_label("Waiting for a Free Slot"),
+            wrapper_factory(),
+        )
+        .with_col_spacing(10)
+        .with_row_spacing(10)
+        .with_padding(10, 10, 10, 10);
+        window.col().with_stretch(1).add();
+
+        window.row().add();
+        window
+            .cell()
+            .unwrap()
+            .wrap(Frame::default())
+            .with_label(&server.name)
+            .with_align(Align::Left | Align::Inside);
+
+        window.row().add();
+        let status_text = window
+            .cell()
+            .unwrap()
+            .wrap(Frame::default())
+            .with_align(Align::Left | Align::Inside);
+
+        window
+            .row()
+            .with_default_align(CellAlign::End)
+            .with_stretch(1)
+            .add();
+        let mut cancel_button = window
+            .cell()
+            .unwrap()
+            .with_horz_align(CellAlign::Center)
+            .wrap(Button::default())
+            .with_label("Cancel");
+
+        let window = window.end();
+        window.layout_children();
+
+        let mut window = window.group();
+        window.set_pos(
+            parent.x() + (parent.w() - window.w()) / 2,
+            parent.y() + (parent.h() - window.h()) / 2,
+        );
+
+        cancel_button.set_callback({
+            let mut window = window.clone();
+            move |_| window.hide()
+        });
+
+        Self {
+            window,
+            status_text,
+        }
+    }
+
+    pub fn show(&self) {
+        let mut window = self.window.clone();
+        window.make_modal(true);
+        window.show();
+    }
+
+    pub fn shown(&self) -> bool {
+        self.window.shown()
+    }
+
+    pub fn set_status(&self, server: &Server, remaining: Duration) {
+        let players = match server.connected_players {
+            Some(players) => players.to_string(),
+            None => "?".to_string(),
+        };
+        let remaining = remaining.as_secs();
+        let mut status_text = self.status_text.clone();
+        status_text.set_label(&format!(
+            "Players: {}/{}    Giving up in {}:{:02}",
+            players,
+            server.max_players,
+            remaining / 60,
+            remaining % 60,
+        ));
+        status_text.redraw();
+    }
+}
+
+impl Drop for QueueDialog {
+    fn drop(&mut self) {
+        self.window.hide();
+    }
+}
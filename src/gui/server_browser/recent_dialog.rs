@@ -0,0 +1,167 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use chrono::{DateTime, Local};
+use fltk::app;
+use fltk::button::{Button, ReturnButton};
+use fltk::enums::{Align, Color};
+use fltk::group::Group;
+use fltk::prelude::*;
+use fltk::table::TableContext;
+use fltk::window::Window;
+use fltk_float::grid::{CellAlign, GridBuilder};
+use fltk_float::SimpleWrapper;
+
+use crate::gui::is_table_nav_event;
+use crate::gui::widgets::{DataColumn, DataTable, DataTableProperties, DataTableUpdate};
+use crate::gui::wrapper_factory;
+use crate::net::format_host;
+use crate::servers::RecentServer;
+
+pub struct RecentDialog {
+    window: Window,
+    result: Rc<RefCell<Option<RecentServer>>>,
+}
+
+type RecentRow = [String; 5];
+
+impl RecentDialog {
+    pub fn new(parent: &Group, history: Vec<RecentServer>) -> Self {
+        let mut grid = GridBuilder::with_factory(
+            Window::default()
+                .with_size(760, 400)
+                .with_label("Recent Servers"),
+            wrapper_factory(),
+        )
+        .with_col_spacing(10)
+        .with_row_spacing(10)
+        .with_padding(10, 10, 10, 10);
+        grid.col().with_stretch(1).add();
+        let btn_group = grid.col_group().add();
+        grid.extend_group(btn_group).batch(2);
+
+        grid.row()
+            .with_stretch(1)
+            .with_default_align(CellAlign::Stretch)
+            .add();
+        let mut table = DataTable::default().with_properties(DataTableProperties {
+            columns: vec![
+                DataColumn::from(("Joined", Align::Left)).with_width(150),
+                DataColumn::from(("Server", Align::Left)).with_width(230),
+                DataColumn::from(("Address", Align::Left)).with_width(150),
+                DataColumn::from(("Launch", Align::Left)).with_width(70),
+                DataColumn::from(("Mods", Align::Left)).with_width(400),
+            ],
+            cell_padding: 4,
+            cell_selection_color: Color::Free,
+            header_font_color: Color::Gray0,
+            ..Default::default()
+        });
+        table.set_row_header(false);
+        table.set_col_header(true);
+        table.set_col_resize(true);
+        table.end();
+        grid.span(1, 3).unwrap().add(SimpleWrapper::new(
+            table.as_base_widget(),
+            Default::default(),
+        ));
+
+        grid.row().add();
+        grid.cell().unwrap().skip();
+        let mut join_button = grid
+            .cell()
+            .unwrap()
+            .wrap(ReturnButton::default())
+            .with_label("Join")
+            .with_tooltip("Join the selected server again");
+        let mut close_button = grid
+            .cell()
+            .unwrap()
+            .wrap(Button::default())
+            .with_label("Close");
+
+        let grid = grid.end();
+        grid.layout_children();
+
+        let mut window = grid.group();
+        window.set_pos(
+            parent.x() + (parent.w() - window.w()) / 2,
+            parent.y() + (parent.h() - window.h()) / 2,
+        );
+
+        {
+            let data = table.data();
+            let mut data = data.borrow_mut();
+            data.extend(history.iter().map(make_row));
+        }
+        table.updated(DataTableUpdate::DATA);
+        join_button.deactivate();
+
+        let history = Rc::new(history);
+        let selected = Rc::new(Cell::new(None));
+        let result = Rc::new(RefCell::new(None));
+
+        table.set_callback({
+            let history = Rc::clone(&history);
+            let selected = Rc::clone(&selected);
+            let result = Rc::clone(&result);
+            let mut join_button = join_button.clone();
+            let mut window = window.clone();
+            move |table| {
+                if !is_table_nav_event() || table.callback_context() != TableContext::Cell {
+                    return;
+                }
+                let _ = table.take_focus();
+                let row = table.callback_row() as usize;
+                table.set_selection(row as _, 0, row as _, 4);
+                selected.set(Some(row));
+                join_button.activate();
+                if app::event_clicks() {
+                    *result.borrow_mut() = history.get(row).cloned();
+                    window.hide();
+                }
+            }
+        });
+
+        join_button.set_callback({
+            let result = Rc::clone(&result);
+            let mut window = window.clone();
+            move |_| {
+                if let Some(row) = selected.get() {
+                    *result.borrow_mut() = history.get(row).cloned();
+                    window.hide();
+                }
+            }
+        });
+
+        close_button.set_callback({
+            let mut window = window.clone();
+            move |_| window.hide()
+        });
+
+        Self { window, result }
+    }
+
+    pub fn run(&self) -> Option<RecentServer> {
+        let mut window = self.window.clone();
+        window.make_modal(true);
+        window.show();
+
+        while window.shown() {
+            app::wait();
+        }
+
+        self.result.borrow_mut().take()
+    }
+}
+
+fn make_row(entry: &RecentServer) -> RecentRow {
+    let joined_at: DateTime<Local> = entry.joined_at.into();
+    [
+        joined_at.format("%Y-%m-%d %H:%M").to_string(),
+        entry.name.clone().unwrap_or_else(|| "???".to_string()),
+        format_host(&entry.addr.ip(), entry.addr.port() as _),
+        if entry.launched { "OK" } else { "Failed" }.to_string(),
+        if entry.mods.is_empty() { "None".to_string() } else { entry.mods.join(", ") },
+    ]
+}
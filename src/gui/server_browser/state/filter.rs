@@ -2,24 +2,33 @@ use regex::{Regex, RegexBuilder};
 
 use crate::config::ServerBrowserConfig;
 use crate::gui::data::RowFilter;
-use crate::servers::{Mode, Region, Server, TypeFilter};
+use crate::servers::{Mode, Query, Region, Server, TypeFilter};
 
 #[derive(Clone, Debug)]
 pub struct Filter {
     values: crate::servers::Filter,
     name_re: Regex,
     map_re: Regex,
+    query: Option<Query>,
+    query_error: Option<String>,
 }
 
 impl Filter {
-    pub fn from_config(config: &ServerBrowserConfig) -> Self {
+    pub fn new(values: crate::servers::Filter) -> Self {
+        let (query, query_error) = Self::query(&values.expression);
         Self {
-            values: config.filter.clone(),
-            name_re: Self::regex(&config.filter.name),
-            map_re: Self::regex(&config.filter.map),
+            name_re: Self::regex(&values.name),
+            map_re: Self::regex(&values.map),
+            query,
+            query_error,
+            values,
         }
     }
 
+    pub fn from_config(config: &ServerBrowserConfig) -> Self {
+        Self::new(config.filter.clone())
+    }
+
     pub fn name(&self) -> &str {
         &self.values.name
     }
@@ -62,6 +71,14 @@ impl Filter {
         self.values.region = region.into();
     }
 
+    pub fn use_inferred_region(&self) -> bool {
+        self.values.use_inferred_region
+    }
+
+    pub fn set_use_inferred_region(&mut self, use_inferred_region: bool) {
+        self.values.use_inferred_region = use_inferred_region;
+    }
+
     pub fn battleye_required(&self) -> Option<bool> {
         self.values.battleye_required
     }
@@ -94,6 +111,29 @@ impl Filter {
         self.values.mods = mods.into();
     }
 
+    pub fn expression(&self) -> &str {
+        &self.values.expression
+    }
+
+    pub fn set_expression(&mut self, expression: String) {
+        (self.query, self.query_error) = Self::query(&expression);
+        self.values.expression = expression;
+    }
+
+    pub fn expression_error(&self) -> Option<&str> {
+        self.query_error.as_deref()
+    }
+
+    fn query(expression: &str) -> (Option<Query>, Option<String>) {
+        if expression.trim().is_empty() {
+            return (None, None);
+        }
+        match Query::parse(expression) {
+            Ok(query) => (Some(query), None),
+            Err(err) => (None, Some(err.to_string())),
+        }
+    }
+
     fn regex(text: &str) -> Regex {
         RegexBuilder::new(&regex::escape(&text))
             .case_insensitive(true)
@@ -114,10 +154,13 @@ impl RowFilter<Server> for Filter {
             && self.map_re.is_match(&server.map)
             && self.values.type_filter.matches(server)
             && self.values.mode.map_or(true, |mode| server.mode() == mode)
-            && self
-                .values
-                .region
-                .map_or(true, |region| server.region == region)
+            && self.values.region.map_or(true, |region| {
+                if self.values.use_inferred_region {
+                    server.effective_region() == region
+                } else {
+                    server.region == region
+                }
+            })
             && self
                 .values
                 .battleye_required
@@ -128,5 +171,9 @@ impl RowFilter<Server> for Filter {
                 .values
                 .mods
                 .map_or(true, |mods| server.is_modded() == mods)
+            && self
+                .query
+                .as_ref()
+                .map_or(true, |query| query.matches(server))
     }
 }
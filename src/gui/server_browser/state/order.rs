@@ -15,6 +15,16 @@ macro_rules! cmp_values {
     };
 }
 
+macro_rules! cmp_floats {
+    ($ascending:expr, $($expr:tt)+) => {
+        if $ascending {
+            |lhs: &Server, rhs: &Server| lhs.$($expr)+.total_cmp(&rhs.$($expr)+)
+        } else {
+            |lhs: &Server, rhs: &Server| rhs.$($expr)+.total_cmp(&lhs.$($expr)+)
+        }
+    };
+}
+
 macro_rules! cmp_options {
     ($ascending:expr, $($expr:tt)+) => {
         if $ascending {
@@ -41,27 +51,56 @@ macro_rules! cmp_options {
 }
 
 pub struct SortOrder {
-    pub criteria: SortCriteria,
+    pub criteria: Vec<SortCriteria>,
     region_order: Rc<HashMap<Region, usize>>,
 }
 
 impl SortOrder {
-    pub fn new(criteria: SortCriteria, region_order: HashMap<Region, usize>) -> Self {
+    pub fn new(criteria: Vec<SortCriteria>, region_order: HashMap<Region, usize>) -> Self {
         Self {
             criteria,
             region_order: Rc::new(region_order),
         }
     }
 
-    fn region_comparator(&self) -> RowComparator<Server> {
+    pub fn sorts_by(&self, key: SortKey) -> bool {
+        self.criteria.iter().any(|criteria| criteria.key == key)
+    }
+
+    fn criteria_comparator(&self, criteria: SortCriteria) -> RowComparator<Server> {
+        let ascending = criteria.ascending;
+        match criteria.key {
+            SortKey::Name => Box::new(cmp_values!(ascending, name)),
+            SortKey::Map => Box::new(cmp_values!(ascending, map)),
+            SortKey::Mode => Box::new(cmp_values!(ascending, mode())),
+            SortKey::Region => self.region_comparator(ascending),
+            SortKey::Players => Box::new({
+                let connected_cmp = cmp_options!(ascending, connected_players);
+                let max_cmp = cmp_values!(ascending, max_players);
+                move |lhs: &Server, rhs: &Server| {
+                    connected_cmp(lhs, rhs).then_with(|| max_cmp(lhs, rhs))
+                }
+            }),
+            SortKey::Age => Box::new(cmp_options!(ascending, age)),
+            SortKey::Ping => Box::new(cmp_options!(ascending, ping)),
+            SortKey::XpRate => Box::new(cmp_floats!(ascending, xp_rate_mult.value())),
+            SortKey::HarvestAmount => Box::new(cmp_floats!(
+                ascending,
+                harvesting.harvest_amount_mult.value()
+            )),
+            SortKey::MaxClanSize => Box::new(cmp_options!(ascending, max_clan_size)),
+        }
+    }
+
+    fn region_comparator(&self, ascending: bool) -> RowComparator<Server> {
         let region_order = Rc::clone(&self.region_order);
-        if self.criteria.ascending {
+        if ascending {
             Box::new(move |lhs: &Server, rhs: &Server| {
-                region_order[&lhs.region].cmp(&region_order[&rhs.region])
+                region_order[&lhs.effective_region()].cmp(&region_order[&rhs.effective_region()])
             })
         } else {
             Box::new(move |lhs: &Server, rhs: &Server| {
-                region_order[&rhs.region].cmp(&region_order[&lhs.region])
+                region_order[&rhs.effective_region()].cmp(&region_order[&lhs.effective_region()])
             })
         }
     }
@@ -69,26 +108,26 @@ impl SortOrder {
 
 impl RowOrder<Server> for SortOrder {
     fn comparator(&self) -> RowComparator<Server> {
-        let cmp: RowComparator<Server> = match self.criteria.key {
-            SortKey::Name => Box::new(cmp_values!(self.criteria.ascending, name)),
-            SortKey::Map => Box::new(cmp_values!(self.criteria.ascending, map)),
-            SortKey::Mode => Box::new(cmp_values!(self.criteria.ascending, mode())),
-            SortKey::Region => self.region_comparator(),
-            SortKey::Players => Box::new({
-                let connected_cmp = cmp_options!(self.criteria.ascending, connected_players);
-                let max_cmp = cmp_values!(self.criteria.ascending, max_players);
-                move |lhs: &Server, rhs: &Server| {
-                    connected_cmp(lhs, rhs).then_with(|| max_cmp(lhs, rhs))
-                }
-            }),
-            SortKey::Age => Box::new(cmp_options!(self.criteria.ascending, age)),
-            SortKey::Ping => Box::new(cmp_options!(self.criteria.ascending, ping)),
-        };
-        let tie_breaker = cmp_values!(self.criteria.ascending, id);
+        let cmps: Vec<RowComparator<Server>> = self
+            .criteria
+            .iter()
+            .map(|criteria| self.criteria_comparator(*criteria))
+            .collect();
+        let ascending = self
+            .criteria
+            .first()
+            .map_or(true, |criteria| criteria.ascending);
+        let tie_breaker = cmp_values!(ascending, id);
         Box::new(move |lhs: &Server, rhs: &Server| {
             rhs.favorite
                 .cmp(&lhs.favorite)
-                .then_with(|| cmp(lhs, rhs).then_with(|| tie_breaker(lhs, rhs)))
+                .then_with(|| {
+                    cmps.iter()
+                        .map(|cmp| cmp(lhs, rhs))
+                        .find(|ordering| ordering.is_ne())
+                        .unwrap_or(Ordering::Equal)
+                })
+                .then_with(|| tie_breaker(lhs, rhs))
         })
     }
 }
@@ -0,0 +1,234 @@
+use std::cell::Cell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use fltk::button::Button;
+use fltk::enums::Align;
+use fltk::frame::Frame;
+use fltk::prelude::*;
+use fltk::window::Window;
+use fltk_float::grid::{CellAlign, Grid, GridBuilder};
+use fltk_float::SimpleWrapper;
+use humansize::{SizeFormatter, BINARY};
+
+use super::widgets::{DataColumn, DataTable, DataTableProperties, DataTableUpdate};
+use super::{prompt_confirm, wrapper_factory};
+
+pub struct ModUsage {
+    pub name: String,
+    pub size: u64,
+    pub references: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct OrphanedFolder {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+pub struct StorageReportDialog {
+    window: Window,
+    cleanup: Rc<Cell<bool>>,
+}
+
+impl StorageReportDialog {
+    pub fn new(parent: &Window, mut mods: Vec<ModUsage>, orphans: Vec<OrphanedFolder>) -> Self {
+        let mut window = Window::default()
+            .with_size(720, 560)
+            .with_label("Mod Storage");
+
+        let mut grid = GridBuilder::with_factory(window.clone(), wrapper_factory())
+            .with_col_spacing(10)
+            .with_row_spacing(10)
+            .with_padding(10, 10, 10, 10);
+        grid.col().with_stretch(1).add();
+
+        mods.sort_by(|lhs, rhs| rhs.size.cmp(&lhs.size));
+        let total_size: u64 = mods.iter().map(|usage| usage.size).sum();
+        grid.row().add();
+        grid.cell()
+            .unwrap()
+            .wrap(Frame::default_fill())
+            .with_align(Align::Left | Align::Inside)
+            .with_label(&format!(
+                "Installed mods use {} of disk space:",
+                SizeFormatter::new(total_size, BINARY)
+            ));
+
+        grid.row()
+            .with_stretch(3)
+            .with_default_align(CellAlign::Stretch)
+            .add();
+        let mut mod_table = make_table(vec![
+            ("Mod", Align::Left).into(),
+            ("Size", Align::Right).into(),
+            ("Referenced By", Align::Left).into(),
+        ]);
+        populate_table(
+            &mod_table,
+            mods.iter().map(|usage| {
+                let references = if usage.references.is_empty() {
+                    "(unused)".to_string()
+                } else {
+                    usage.references.join(", ")
+                };
+                vec![
+                    usage.name.clone(),
+                    SizeFormatter::new(usage.size, BINARY).to_string(),
+                    references,
+                ]
+            }),
+        );
+        grid.cell().unwrap().add(SimpleWrapper::new(
+            mod_table.as_base_widget(),
+            Default::default(),
+        ));
+
+        let orphaned_size: u64 = orphans.iter().map(|orphan| orphan.size).sum();
+        grid.row().add();
+        grid.cell()
+            .unwrap()
+            .wrap(Frame::default_fill())
+            .with_align(Align::Left | Align::Inside)
+            .with_label(&format!(
+                "Orphaned mod folders use {} of disk space:",
+                SizeFormatter::new(orphaned_size, BINARY)
+            ));
+
+        grid.row()
+            .with_stretch(1)
+            .with_default_align(CellAlign::Stretch)
+            .add();
+        let mut orphan_table = make_table(vec![
+            ("Folder", Align::Left).into(),
+            ("Size", Align::Right).into(),
+        ]);
+        populate_table(
+            &orphan_table,
+            orphans.iter().map(|orphan| {
+                vec![
+                    orphan.path.display().to_string(),
+                    SizeFormatter::new(orphan.size, BINARY).to_string(),
+                ]
+            }),
+        );
+        grid.cell().unwrap().add(SimpleWrapper::new(
+            orphan_table.as_base_widget(),
+            Default::default(),
+        ));
+
+        let mut btn_grid = Grid::builder_with_factory(wrapper_factory())
+            .with_col_spacing(10)
+            .with_row_spacing(10);
+        btn_grid.row().add();
+        let btn_group = btn_grid.col_group().add();
+
+        btn_grid.extend_group(btn_group).add();
+        let mut btn_cleanup = btn_grid
+            .cell()
+            .unwrap()
+            .wrap(Button::default())
+            .with_label("Delete Orphaned Folders");
+        btn_cleanup.set_activated(!orphans.is_empty());
+
+        btn_grid.col().with_stretch(1).add();
+        btn_grid.cell().unwrap().skip();
+
+        btn_grid.extend_group(btn_group).add();
+        let mut btn_close = btn_grid
+            .cell()
+            .unwrap()
+            .wrap(Button::default())
+            .with_label("Close");
+        let btn_grid = btn_grid.end();
+
+        grid.row().add();
+        grid.cell().unwrap().add(btn_grid);
+
+        grid.end().layout_children();
+
+        adjust_col_widths(&mut mod_table, &[0, 2]);
+        adjust_col_widths(&mut orphan_table, &[0]);
+
+        let cleanup = Rc::new(Cell::new(false));
+        btn_cleanup.set_callback({
+            let cleanup = Rc::clone(&cleanup);
+            let mut window = window.clone();
+            move |_| {
+                if prompt_confirm(PROMPT_DELETE_ORPHANS) {
+                    cleanup.set(true);
+                    window.hide();
+                }
+            }
+        });
+        btn_close.set_callback({
+            let mut window = window.clone();
+            move |_| window.hide()
+        });
+
+        window.set_pos(
+            parent.x() + (parent.w() - window.w()) / 2,
+            parent.y() + (parent.h() - window.h()) / 2,
+        );
+
+        Self { window, cleanup }
+    }
+
+    // Returns whether the user asked to delete the orphaned folders.
+    pub fn run(self) -> bool {
+        let mut window = self.window.clone();
+        window.make_modal(true);
+        window.show();
+
+        while window.shown() {
+            if !fltk::app::wait() {
+                return false;
+            }
+        }
+
+        self.cleanup.get()
+    }
+}
+
+const PROMPT_DELETE_ORPHANS: &str =
+    "Are you sure you want to permanently delete the orphaned mod folders?";
+
+fn make_table(columns: Vec<DataColumn>) -> DataTable<Vec<String>> {
+    let mut table = DataTable::default().with_properties(DataTableProperties {
+        columns,
+        cell_padding: 4,
+        cell_selection_color: fltk::enums::Color::Free,
+        header_font_color: fltk::enums::Color::Gray0,
+        ..Default::default()
+    });
+    table.set_row_header(false);
+    table.set_col_header(true);
+    table.set_col_resize(true);
+    table.end();
+    table
+}
+
+fn populate_table(table: &DataTable<Vec<String>>, rows: impl Iterator<Item = Vec<String>>) {
+    let data = table.data();
+    let mut data = data.borrow_mut();
+    data.clear();
+    data.extend(rows);
+    drop(data);
+    table.updated(DataTableUpdate::DATA);
+}
+
+// Splits the width that is not taken by the other columns evenly among the stretched ones.
+fn adjust_col_widths(table: &mut DataTable<Vec<String>>, stretched: &[i32]) {
+    let scrollbar_width = table.scrollbar_size();
+    let scrollbar_width =
+        if scrollbar_width > 0 { scrollbar_width } else { fltk::app::scrollbar_size() };
+
+    let fixed_width: i32 = (0..table.cols())
+        .filter(|col| !stretched.contains(col))
+        .map(|col| table.col_width(col))
+        .sum();
+    let width = (table.width() - fixed_width - scrollbar_width - 2) / stretched.len() as i32;
+    for &col in stretched {
+        table.set_col_width(col, width);
+    }
+}
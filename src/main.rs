@@ -3,10 +3,12 @@
 use std::cell::{Cell, Ref, RefCell};
 use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
+use std::path::Path;
 use std::rc::Rc;
 use std::str::FromStr;
 use std::sync::atomic::AtomicUsize;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 
 use anyhow::{anyhow, bail, Result};
 use auth::{CachedUser, CachedUsers};
@@ -14,8 +16,7 @@ use config::{BattlEyeUsage, Config, ConfigPersister, IniConfigPersister, Transie
 use fltk::app::{self, App};
 use fltk::dialog::{self, FileDialogOptions, FileDialogType, NativeFileChooser};
 use fltk::prelude::WindowExt;
-use game::platform::steam::{SteamClient, SteamModDirectory};
-use game::platform::ModDirectory;
+use game::platform::{GamePlatform, ModDirectory, PlatformClient, RemoteModInfo};
 use game::{LaunchOptions, MapRef};
 use regex::Regex;
 use slog::{debug, error, info, trace, warn, FilterLevel, Logger};
@@ -34,22 +35,30 @@ mod workers;
 use crate::config::ModMismatchChecks;
 
 use self::auth::{Account, AuthState, Capability, PlatformUser};
+use self::game::platform::manual::ManualInstall;
 use self::game::platform::steam::Steam;
-use self::game::{list_mod_controllers, Branch, Game, Launch, ModRef, Mods, ServerRef, Session};
+use self::game::{
+    list_mod_controllers, Branch, Game, Launch, ModInfo, ModPins, ModRef, ModSource, ModTags, Mods,
+    PinnedMod, ServerRef, Session,
+};
 use self::gui::theme::Theme;
 use self::gui::{
     prompt_confirm, Action, Dialog, HomeAction, HomeUpdate, LauncherWindow, ModManagerAction,
-    ModManagerUpdate, ModUpdateProgressDialog, ModUpdateSelectionDialog, ServerBrowserAction,
-    ServerBrowserUpdate, SinglePlayerAction, Update,
+    ModManagerUpdate, ModUpdateProgressDialog, ModUpdateSelectionDialog, ModUsage, OrphanedFolder,
+    ServerBrowserAction, ServerBrowserUpdate, SinglePlayerAction, StorageReportDialog, Update,
 };
 use self::logger::create_root_logger;
-use self::servers::Server;
+use self::servers::{
+    export_servers, remove_favorite, upsert_favorite, ExportFormat, FavoriteDetails,
+    FavoriteServer, PasswordVault, RecentServer, Server, ServerHistory,
+};
 use self::workers::{FlsWorker, SavedGamesWorker, ServerLoaderWorker, TaskState};
 
 pub enum Message {
     Update(Update),
     ServerList(Result<Vec<Server>>),
     Account(Result<Account>),
+    ModPinned(ModRef, Result<PinnedMod>),
     PlatformReady,
 }
 
@@ -59,10 +68,14 @@ struct Launcher {
     logger: Logger,
     log_level: Option<Arc<AtomicUsize>>,
     app: App,
-    steam: Rc<SteamClient>,
+    platform: Rc<dyn PlatformClient>,
     game: Arc<Game>,
     config: RefCell<Config>,
     config_persister: Box<dyn ConfigPersister + Send + Sync>,
+    server_history: RefCell<ServerHistory>,
+    favorite_details: Arc<Mutex<FavoriteDetails>>,
+    mod_pins: RefCell<ModPins>,
+    mod_tags: RefCell<ModTags>,
     tx: app::Sender<Message>,
     rx: app::Receiver<Message>,
     mod_directory: Rc<dyn ModDirectory>,
@@ -82,27 +95,38 @@ impl Launcher {
         log_level: Option<Arc<AtomicUsize>>,
         can_switch_branch: bool,
         app: App,
-        steam: Steam,
+        platform: Box<dyn GamePlatform>,
         game: Game,
         config: Config,
         config_persister: Box<dyn ConfigPersister + Send + Sync>,
+        server_history: ServerHistory,
+        favorite_details: FavoriteDetails,
+        mod_pins: ModPins,
+        mod_tags: ModTags,
+        vault: PasswordVault,
     ) -> Rc<Self> {
         let game = Arc::new(game);
+        for pin in mod_pins.pins() {
+            if let ModRef::Installed(idx) = game.installed_mods().by_pak_path(&pin.pak_path) {
+                game.installed_mods()[idx].set_pinned(true);
+            }
+        }
+        for (folder_name, tags) in mod_tags.iter() {
+            if let ModRef::Installed(idx) = game.installed_mods().by_folder(folder_name) {
+                game.installed_mods()[idx].set_tags(tags.to_vec());
+            }
+        }
         let (tx, rx) = app::channel();
-        let steam = steam.init_client(&*game, tx.clone());
-
-        let mod_directory: Rc<dyn ModDirectory> = SteamModDirectory::new(
-            logger.clone(),
-            Rc::clone(&steam),
-            tx.clone(),
-            game.installed_mods(),
-        );
+        let platform = platform.init_client(&*game, tx.clone());
+        let mod_directory =
+            Rc::clone(&platform).mod_directory(logger.clone(), tx.clone(), game.installed_mods());
 
         let main_window = LauncherWindow::new(
             logger.clone(),
             Arc::clone(&game),
             &config,
             Rc::clone(&mod_directory),
+            Rc::new(RefCell::new(vault)),
             log_level.is_none(),
             can_switch_branch,
         );
@@ -121,8 +145,13 @@ impl Launcher {
             }
         };
 
-        let server_loader_worker =
-            ServerLoaderWorker::new(logger.clone(), Arc::clone(&game), tx.clone());
+        let favorite_details = Arc::new(Mutex::new(favorite_details));
+        let server_loader_worker = ServerLoaderWorker::new(
+            logger.clone(),
+            Arc::clone(&game),
+            Arc::clone(&favorite_details),
+            tx.clone(),
+        );
 
         let saved_games_worker = SavedGamesWorker::new(Arc::clone(&game), tx.clone());
         let fls_worker = FlsWorker::new(logger.clone(), Arc::clone(&game), tx.clone());
@@ -131,10 +160,14 @@ impl Launcher {
             logger,
             log_level,
             app,
-            steam,
+            platform,
             game,
             config: RefCell::new(config),
             config_persister,
+            server_history: RefCell::new(server_history),
+            favorite_details,
+            mod_pins: RefCell::new(mod_pins),
+            mod_tags: RefCell::new(mod_tags),
             tx,
             rx,
             mod_directory,
@@ -195,7 +228,7 @@ impl Launcher {
 
     fn background_loop(&self) {
         loop {
-            self.steam.run_callbacks();
+            self.platform.run_callbacks();
 
             let mut pending_ref = self.pending_update.borrow_mut();
 
@@ -265,7 +298,7 @@ impl Launcher {
                     }
                 }
 
-                let platform_user = self.steam.user().ok_or(anyhow!("Steam not running"));
+                let platform_user = self.platform_user();
                 let fls_account = TaskState::Ready(account);
                 let online_capability = self.online_capability(&platform_user, &fls_account);
                 let sp_capability = self.sp_capability(&platform_user, &fls_account);
@@ -278,6 +311,13 @@ impl Launcher {
 
                 Some(Update::HomeUpdate(HomeUpdate::AuthState(auth_state)))
             }
+            Message::ModPinned(mod_ref, pin) => {
+                let result = pin.and_then(|pin| self.mod_pins.borrow_mut().add(pin));
+                if result.is_ok() {
+                    self.game.installed_mods()[mod_ref.to_index().unwrap()].set_pinned(true);
+                }
+                Some(Update::ModManager(ModManagerUpdate::Pinned(result)))
+            }
             Message::PlatformReady => {
                 self.check_mod_updates();
                 None
@@ -340,15 +380,68 @@ impl Launcher {
                 addr,
                 password,
                 battleye_required,
-            }) => self.join_server(addr, password, battleye_required),
+                id,
+                name,
+            }) => self.join_server(addr, password, battleye_required, id, name),
             Action::ServerBrowser(ServerBrowserAction::PingServer(request)) => {
                 self.server_loader_worker.ping_server(request)
             }
             Action::ServerBrowser(ServerBrowserAction::PingServers(requests)) => {
                 self.server_loader_worker.ping_servers(requests)
             }
-            Action::ServerBrowser(ServerBrowserAction::UpdateFavorites(favorites)) => {
-                self.game.save_favorites(favorites)
+            Action::ServerBrowser(ServerBrowserAction::QueryServer(target)) => {
+                self.server_loader_worker.query_server(target);
+                Ok(())
+            }
+            Action::ServerBrowser(ServerBrowserAction::AddFavorite(favorite)) => {
+                let mut favorites = self.load_favorites()?;
+                upsert_favorite(&mut favorites, favorite);
+                self.save_favorites(favorites)
+            }
+            Action::ServerBrowser(ServerBrowserAction::RemoveFavorite(favorite)) => {
+                let mut favorites = self.load_favorites()?;
+                remove_favorite(&mut favorites, &favorite);
+                self.save_favorites(favorites)
+            }
+            Action::ServerBrowser(ServerBrowserAction::LoadFavorites) => {
+                let favorites = self.load_favorites()?;
+                self.tx.send(Message::Update(Update::ServerBrowser(
+                    ServerBrowserUpdate::ManageFavorites(favorites),
+                )));
+                Ok(())
+            }
+            Action::ServerBrowser(ServerBrowserAction::SaveFavorites(favorites)) => {
+                self.save_favorites(favorites)
+            }
+            Action::ServerBrowser(ServerBrowserAction::LoadRecentServers) => {
+                let history = self.server_history.borrow().entries().to_vec();
+                self.tx.send(Message::Update(Update::ServerBrowser(
+                    ServerBrowserUpdate::ShowRecentServers(history),
+                )));
+                Ok(())
+            }
+            Action::ServerBrowser(ServerBrowserAction::ExportServers(servers)) => {
+                let mut dialog = NativeFileChooser::new(FileDialogType::BrowseSaveFile);
+                dialog.set_filter(DLG_FILTER_SERVER_LIST);
+                dialog.set_option(FileDialogOptions::SaveAsConfirm);
+                dialog.show();
+
+                let mut export_path = dialog.filename();
+                if export_path.as_os_str().is_empty() {
+                    return Ok(());
+                }
+                let is_json = match export_path.extension() {
+                    Some(ext) => ext.eq_ignore_ascii_case("json"),
+                    None => {
+                        let is_json = dialog.filter_value() == 1;
+                        export_path.set_extension(if is_json { "json" } else { "csv" });
+                        is_json
+                    }
+                };
+                let format = if is_json { ExportFormat::Json } else { ExportFormat::Csv };
+
+                let file = std::fs::File::create(&export_path)?;
+                export_servers(&servers, format, std::io::BufWriter::new(file))
             }
             Action::ServerBrowser(ServerBrowserAction::UpdateConfig(sb_cfg)) => {
                 self.update_config(|config| config.server_browser = sb_cfg);
@@ -423,13 +516,71 @@ impl Launcher {
                 self.game
                     .save_mod_list_to(&mod_list_path, active_mods.iter())
             }
+            Action::ModManager(ModManagerAction::ImportCollection(collection_id)) => {
+                self.import_collection(collection_id)
+            }
+            Action::ModManager(ModManagerAction::PinMod(mod_ref)) => {
+                let mod_info = self.game.installed_mods().get(&mod_ref).unwrap();
+                let backup_dir = self.mod_pins.borrow().backup_dir().map(Path::to_path_buf);
+                let pak_path = mod_info.pak_path.clone();
+                let tx = self.tx.clone();
+                tokio::task::spawn_blocking(move || {
+                    let pin = ModPins::prepare_pin(backup_dir.as_deref(), &pak_path);
+                    tx.send(Message::ModPinned(mod_ref, pin));
+                });
+                Ok(())
+            }
+            Action::ModManager(ModManagerAction::UnpinMod(mod_ref)) => {
+                let mod_info = self.game.installed_mods().get(&mod_ref).unwrap();
+                self.mod_pins.borrow_mut().unpin(&mod_info.pak_path)?;
+                mod_info.set_pinned(false);
+                Ok(())
+            }
+            Action::ModManager(ModManagerAction::SetModTags(mod_ref, tags)) => {
+                let mod_info = self.game.installed_mods().get(&mod_ref).unwrap();
+                self.mod_tags
+                    .borrow_mut()
+                    .set(&mod_info.folder_name, tags.clone())?;
+                mod_info.set_tags(tags);
+                Ok(())
+            }
+            Action::ModManager(ModManagerAction::Subscribe(mod_ref)) => {
+                let mod_info = self.game.installed_mods().get(&mod_ref).unwrap();
+                let mod_id = mod_info
+                    .steam_file_id(self.game.branch())
+                    .ok_or_else(|| anyhow!("Mod does not have a Steam file ID"))?;
+                let update = Rc::clone(&self.mod_directory).subscribe(mod_id)?;
+                let dialog = ModUpdateProgressDialog::for_downloads(
+                    self.main_window.window(),
+                    vec![(mod_info.name.clone(), Ok(update))],
+                );
+                dialog.run();
+                Ok(())
+            }
+            Action::ModManager(ModManagerAction::Unsubscribe(mod_ref)) => {
+                let mod_info = self.game.installed_mods().get(&mod_ref).unwrap();
+                let mod_id = mod_info
+                    .steam_file_id(self.game.branch())
+                    .ok_or_else(|| anyhow!("Mod does not have a Steam file ID"))?;
+                let tx = self.tx.clone();
+                Rc::clone(&self.mod_directory).unsubscribe(
+                    mod_id,
+                    Box::new(move |result| {
+                        tx.send(Message::Update(Update::ModManager(
+                            ModManagerUpdate::Unsubscribed(result),
+                        )))
+                    }),
+                );
+                Ok(())
+            }
+            Action::ModManager(ModManagerAction::ShowStorageReport) => self.show_storage_report(),
             Action::ModManager(ModManagerAction::UpdateMods) => {
                 let outdated_mods = self
                     .game
                     .installed_mods()
                     .iter()
                     .enumerate()
-                    .filter(|(_, mod_info)| mod_info.needs_update())
+                    .filter(|(_, mod_info)| mod_info.needs_update() && !mod_info.is_pinned())
                     .map(|(idx, _)| ModRef::Installed(idx))
                     .collect();
                 if self.update_mods(outdated_mods) {
@@ -473,13 +624,13 @@ impl Launcher {
             return Ok(());
         }
 
-        if !self.steam.can_play_online() {
+        if !self.platform.can_play_online() {
             match &*self.game.last_session() {
                 Some(Session::Online(_)) => bail!(ERR_STEAM_NOT_ONLINE),
                 Some(Session::SinglePlayer(_)) => {
                     let cached_users = self.cached_users();
                     let fls_account_id = self
-                        .steam
+                        .platform
                         .user()
                         .and_then(|user| cached_users.by_platform_id(&user.id))
                         .map(|user| user.account.master_id.as_str());
@@ -522,11 +673,13 @@ impl Launcher {
         addr: SocketAddr,
         password: Option<String>,
         battleye_required: Option<bool>,
+        id: Option<String>,
+        name: Option<String>,
     ) -> Result<()> {
         if !self.can_launch() {
             return Ok(());
         }
-        if !self.steam.can_play_online() {
+        if !self.platform.can_play_online() {
             bail!(ERR_STEAM_NOT_ONLINE);
         }
 
@@ -547,23 +700,68 @@ impl Launcher {
             }
         };
         let launch_opts = self.launch_options(use_battleye);
-        if self.monitor_launch(self.game.join_server(addr, password, launch_opts)?)? {
+        let launched = self
+            .game
+            .join_server(addr, password, launch_opts)
+            .and_then(|launch| self.monitor_launch(launch));
+        self.record_recent_server(RecentServer {
+            addr,
+            id,
+            name,
+            joined_at: SystemTime::now(),
+            mods: self.mod_names(&mod_list),
+            launched: matches!(launched, Ok(true)),
+        });
+        if launched? {
             app::quit();
         }
         Ok(())
     }
 
+    fn record_recent_server(&self, entry: RecentServer) {
+        debug!(self.logger, "Recording recent server"; "entry" => ?entry);
+        if let Err(err) = self.server_history.borrow_mut().record(entry) {
+            warn!(self.logger, "Error saving the server history"; "error" => %err);
+        }
+    }
+
+    fn load_favorites(&self) -> Result<Vec<FavoriteServer>> {
+        let mut favorites = self.game.load_favorite_list()?;
+        self.favorite_details.lock().unwrap().apply(&mut favorites);
+        Ok(favorites)
+    }
+
+    fn save_favorites(&self, favorites: Vec<FavoriteServer>) -> Result<()> {
+        self.favorite_details.lock().unwrap().update(&favorites)?;
+        self.game.save_favorites(favorites)
+    }
+
+    fn mod_names(&self, mod_list: &[ModRef]) -> Vec<String> {
+        let installed_mods = self.game.installed_mods();
+        mod_list
+            .iter()
+            .map(|mod_ref| match installed_mods.get(mod_ref) {
+                Some(mod_info) => mod_info.name.clone(),
+                None => match mod_ref {
+                    ModRef::Installed(_) => unreachable!(),
+                    ModRef::UnknownFolder(folder) => folder.clone(),
+                    ModRef::UnknownPakPath(path) => path.display().to_string(),
+                },
+            })
+            .collect()
+    }
+
     fn start_new_singleplayer_game(&self, map_id: usize) -> Result<()> {
         if !self.can_launch() {
             return Ok(());
         }
         let cached_users = self.cached_users();
         let fls_account_id = self
-            .steam
+            .platform
             .user()
             .and_then(|user| cached_users.by_platform_id(&user.id))
             .map(|user| user.account.master_id.as_str());
-        if !self.steam.can_play_online() {
+        if !self.platform.can_play_online() {
             if fls_account_id.is_none() {
                 bail!(ERR_FLS_ACCOUNT_NOT_CACHED);
             }
@@ -579,9 +777,9 @@ impl Launcher {
             return Ok(());
         }
         let cached_users = self.cached_users();
-        if !self.steam.can_play_online() {
+        if !self.platform.can_play_online() {
             let cached_user = self
-                .steam
+                .platform
                 .user()
                 .and_then(|user| cached_users.by_platform_id(&user.id));
             if cached_user.is_none() {
@@ -593,13 +791,13 @@ impl Launcher {
     }
 
     fn check_auth_state(&self) -> AuthState {
-        let platform_user = self.steam.user().ok_or(anyhow!("Steam not running"));
+        let platform_user = self.platform_user();
         let fls_account = match &platform_user {
             Ok(user) => {
                 if let Some(cached) = self.cached_users().by_platform_id(&user.id).as_deref() {
                     TaskState::Ready(Ok(cached.account.clone()))
                 } else {
-                    if self.steam.can_play_online() {
+                    if self.platform.can_play_online() {
                         TaskState::Pending
                     } else {
                         TaskState::Ready(Err(anyhow!("Steam in offline mode")))
@@ -612,7 +810,7 @@ impl Launcher {
         let sp_capability = self.sp_capability(&platform_user, &fls_account);
 
         if let TaskState::Pending = &fls_account {
-            Arc::clone(&self.fls_worker).login_with_steam(&*self.steam.auth_ticket().unwrap());
+            Arc::clone(&self.fls_worker).login_with_steam(&*self.platform.auth_ticket().unwrap());
         }
 
         AuthState {
@@ -623,15 +821,26 @@ impl Launcher {
         }
     }
 
+    fn platform_user(&self) -> Result<PlatformUser> {
+        match self.platform.user() {
+            Some(user) => Ok(user),
+            None if self.platform.has_user_accounts() => Err(anyhow!(ERR_STEAM_NOT_RUNNING)),
+            None => Err(anyhow!(MSG_NO_PLATFORM_ACCOUNTS)),
+        }
+    }
+
     fn online_capability(
         &self,
         platform_user: &Result<PlatformUser>,
         fls_account: &TaskState<Result<Account>>,
     ) -> TaskState<Capability> {
+        if !self.platform.has_user_accounts() {
+            return TaskState::Ready(Ok(()));
+        }
         match &platform_user {
             Err(err) => TaskState::Ready(Err(anyhow!(err.to_string()))),
             Ok(_) => {
-                if !self.steam.can_play_online() {
+                if !self.platform.can_play_online() {
                     TaskState::Ready(Err(anyhow!("Steam in offline mode")))
                 } else {
                     match &fls_account {
@@ -649,6 +858,9 @@ impl Launcher {
         platform_user: &Result<PlatformUser>,
         fls_account: &TaskState<Result<Account>>,
     ) -> TaskState<Capability> {
+        if !self.platform.has_user_accounts() {
+            return TaskState::Ready(Ok(()));
+        }
         match &platform_user {
             Err(err) => TaskState::Ready(Err(anyhow!(err.to_string()))),
             Ok(_) => match &fls_account {
@@ -662,7 +874,7 @@ impl Launcher {
     }
 
     fn can_launch(&self) -> bool {
-        if self.steam.can_launch() {
+        if self.platform.can_launch() {
             return true;
         }
 
@@ -680,7 +892,7 @@ impl Launcher {
             move |handle| {
                 if let Some(should_poll) = should_poll.upgrade() {
                     let poll_skipped = should_poll.replace(true);
-                    trace!(logger, "Firing steam poll timer"; "poll_skipped" => poll_skipped);
+                    trace!(logger, "Firing platform poll timer"; "poll_skipped" => poll_skipped);
                     app::repeat_timeout3(1.0, handle);
                     app::awake();
                 }
@@ -688,7 +900,7 @@ impl Launcher {
         });
         loop {
             if should_poll.replace(false) {
-                if self.steam.can_launch() {
+                if self.platform.can_launch() {
                     return true;
                 }
             }
@@ -936,7 +1148,9 @@ impl Launcher {
         let mut outdated_mods = Vec::new();
         for mod_ref in mod_list {
             if let Some(mod_info) = installed_mods.get(mod_ref) {
-                if mod_info.needs_update() {
+                if mod_info.is_pinned() {
+                    self.restore_pinned_mod(mod_info);
+                } else if mod_info.needs_update() {
                     outdated_mods.push(mod_ref.clone());
                 }
             }
@@ -945,15 +1159,49 @@ impl Launcher {
         outdated_mods
     }
 
+    fn restore_pinned_mod(&self, mod_info: &ModInfo) {
+        match self.mod_pins.borrow().restore(&mod_info.pak_path) {
+            Ok(true) => info!(
+                self.logger,
+                "Restored pinned mod revision";
+                "mod_name" => &mod_info.name,
+                "pak_path" => ?mod_info.pak_path,
+            ),
+            Ok(false) => (),
+            Err(err) => warn!(
+                self.logger,
+                "Error restoring pinned mod revision";
+                "mod_name" => &mod_info.name,
+                "pak_path" => ?mod_info.pak_path,
+                "error" => %err,
+            ),
+        }
+    }
+
     fn update_mods(&self, outdated_mods: Vec<ModRef>) -> bool {
+        let installed_mods = self.game.installed_mods();
+        let outdated_mods: Vec<ModRef> = outdated_mods
+            .into_iter()
+            .filter(|mod_ref| {
+                installed_mods
+                    .get(mod_ref)
+                    .map(|mod_info| !mod_info.is_pinned() && mod_info.source == ModSource::Platform)
+                    .unwrap_or(true)
+            })
+            .collect();
         if outdated_mods.is_empty() || !Rc::clone(&self.mod_directory).can_update() {
             return true;
         }
 
-        let installed_mods = self.game.installed_mods();
+        let remote_info = self.query_remote_info(&outdated_mods);
 
-        let dialog =
-            ModUpdateSelectionDialog::new(self.main_window.window(), installed_mods, outdated_mods);
+        let dialog = ModUpdateSelectionDialog::new(
+            self.main_window.window(),
+            installed_mods,
+            outdated_mods,
+            remote_info,
+            self.game.branch(),
+        );
         let mods_to_update = match dialog.run() {
             None => return false,
             Some(mods) => mods,
@@ -973,6 +1221,285 @@ impl Launcher {
         self.main_window.window().shown()
     }
 
+    fn show_storage_report(&self) -> Result<()> {
+        let installed_mods = self.game.installed_mods();
+
+        // Mod lists exported by the user end up in the save folder by default
+        let mut mod_lists = vec![(MOD_LIST_FILE_NAME.to_string(), self.game.load_mod_list()?)];
+        if let Ok(entries) = std::fs::read_dir(self.game.save_path()) {
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                let path = entry.path();
+                if path.extension().map(|ext| ext != "txt").unwrap_or(true) {
+                    continue;
+                }
+                match self.game.load_mod_list_from(&path) {
+                    Ok(mod_list) => {
+                        mod_lists.push((entry.file_name().to_string_lossy().into_owned(), mod_list))
+                    }
+                    Err(err) => warn!(
+                        self.logger,
+                        "Error reading saved mod list";
+                        "path" => path.display(),
+                        "error" => %err,
+                    ),
+                }
+            }
+        }
+
+        let mut references = vec![Vec::new(); installed_mods.len()];
+        for (list_name, mod_list) in mod_lists {
+            for mod_ref in mod_list {
+                if let ModRef::Installed(idx) = mod_ref {
+                    if !references[idx].contains(&list_name) {
+                        references[idx].push(list_name.clone());
+                    }
+                }
+            }
+        }
+        let mods = installed_mods
+            .iter()
+            .zip(references)
+            .map(|(mod_info, references)| ModUsage {
+                name: mod_info.name.clone(),
+                size: std::fs::metadata(&mod_info.pak_path)
+                    .map(|metadata| metadata.len())
+                    .unwrap_or_default(),
+                references,
+            })
+            .collect();
+
+        let orphans = Rc::clone(&self.mod_directory)
+            .orphaned_folders()?
+            .into_iter()
+            .map(|path| OrphanedFolder {
+                size: folder_size(&path),
+                path,
+            })
+            .collect::<Vec<_>>();
+
+        let dialog = StorageReportDialog::new(self.main_window.window(), mods, orphans.clone());
+        if !dialog.run() {
+            return Ok(());
+        }
+
+        let mut result = Ok(());
+        for orphan in orphans {
+            info!(self.logger, "Deleting orphaned mod folder"; "path" => orphan.path.display());
+            if let Err(err) = std::fs::remove_dir_all(&orphan.path) {
+                warn!(
+                    self.logger,
+                    "Error deleting orphaned mod folder";
+                    "path" => orphan.path.display(),
+                    "error" => %err,
+                );
+                result = Err(anyhow!("Cannot delete {}: {}", orphan.path.display(), err));
+            }
+        }
+        result
+    }
+
+    fn import_collection(&self, collection_id: u64) -> Result<()> {
+        let mod_ids = match self.query_collection(collection_id) {
+            Some(result) => result?,
+            None => return Ok(()),
+        };
+
+        let branch = self.game.branch();
+        let installed_mods = self.game.installed_mods();
+        let mut entries: Vec<(u64, Option<ModRef>)> = mod_ids
+            .into_iter()
+            .map(|mod_id| {
+                let mod_ref = installed_mods
+                    .iter()
+                    .position(|mod_info| {
+                        mod_info.source == ModSource::Platform
+                            && mod_info.steam_file_id(branch) == Some(mod_id)
+                    })
+                    .map(ModRef::Installed);
+                (mod_id, mod_ref)
+            })
+            .collect();
+
+        let missing_ids: Vec<u64> = entries
+            .iter()
+            .filter_map(|(mod_id, mod_ref)| if mod_ref.is_none() { Some(*mod_id) } else { None })
+            .collect();
+        let mut downloaded = false;
+        if !missing_ids.is_empty()
+            && Rc::clone(&self.mod_directory).can_update()
+            && prompt_confirm(&format!(
+                "{} mod(s) from the collection are not installed. Subscribe to them and \
+                    download them now?",
+                missing_ids.len()
+            ))
+        {
+            let downloads = missing_ids
+                .iter()
+                .map(|&mod_id| {
+                    let name = format!("Workshop item {}", mod_id);
+                    (name, Rc::clone(&self.mod_directory).subscribe(mod_id))
+                })
+                .collect::<Vec<_>>();
+            let updates: Vec<_> = downloads
+                .iter()
+                .map(|(_, update)| update.as_ref().ok().cloned())
+                .collect();
+            let dialog =
+                ModUpdateProgressDialog::for_downloads(self.main_window.window(), downloads);
+            dialog.run();
+
+            let mut pak_paths = HashMap::new();
+            for (mod_id, update) in missing_ids.iter().zip(updates) {
+                let update = match update {
+                    Some(update) => update,
+                    None => continue,
+                };
+                if let TaskState::Ready(Ok(())) = update.state() {
+                    match Rc::clone(&self.mod_directory).mod_pak_paths(*mod_id) {
+                        Ok(paths) => {
+                            pak_paths.insert(*mod_id, paths);
+                        }
+                        Err(err) => warn!(
+                            self.logger,
+                            "Error locating the downloaded mod";
+                            "mod_id" => *mod_id,
+                            "error" => %err,
+                        ),
+                    }
+                }
+            }
+            downloaded = !pak_paths.is_empty();
+
+            let mut resolved = Vec::with_capacity(entries.len());
+            for (mod_id, mod_ref) in entries {
+                match mod_ref {
+                    Some(mod_ref) => resolved.push((mod_id, Some(mod_ref))),
+                    None => match pak_paths.remove(&mod_id) {
+                        Some(paths) => resolved.extend(
+                            paths
+                                .into_iter()
+                                .map(|path| (mod_id, Some(ModRef::UnknownPakPath(path)))),
+                        ),
+                        None => resolved.push((mod_id, None)),
+                    },
+                }
+            }
+            entries = resolved;
+        }
+
+        let skipped = entries
+            .iter()
+            .filter(|(_, mod_ref)| mod_ref.is_none())
+            .count();
+        let active_mods: Vec<ModRef> = entries
+            .into_iter()
+            .filter_map(|(_, mod_ref)| mod_ref)
+            .collect();
+        info!(
+            self.logger,
+            "Importing workshop collection";
+            "collection_id" => collection_id,
+            "mods" => active_mods.len(),
+            "skipped" => skipped,
+        );
+        self.game.save_mod_list(&active_mods)?;
+        self.tx.send(Message::Update(Update::ModManager(
+            ModManagerUpdate::PopulateModList(active_mods),
+        )));
+
+        if skipped > 0 {
+            dialog::message_default(&format!(
+                "{} mod(s) from the collection are not installed and were left out of the mod \
+                    list.",
+                skipped
+            ));
+        }
+        if downloaded {
+            dialog::message_default(MSG_RESTART_FOR_NEW_MODS);
+        }
+
+        Ok(())
+    }
+
+    fn query_collection(&self, collection_id: u64) -> Option<Result<Vec<u64>>> {
+        self.wait_for_platform(
+            "Importing Collection",
+            "Querying the Steam Workshop for the collection...",
+            |callback| {
+                Rc::clone(&self.mod_directory).query_collection(collection_id, callback);
+            },
+        )
+    }
+
+    // Failing to get the details of the latest revisions is not fatal, the user can still decide
+    // whether to update without them.
+    fn query_remote_info(&self, mod_refs: &[ModRef]) -> HashMap<ModRef, RemoteModInfo> {
+        let branch = self.game.branch();
+        let installed_mods = self.game.installed_mods();
+        let mod_ids: HashMap<u64, ModRef> = mod_refs
+            .iter()
+            .filter_map(|mod_ref| {
+                let mod_id = installed_mods.get(mod_ref)?.steam_file_id(branch)?;
+                Some((mod_id, mod_ref.clone()))
+            })
+            .collect();
+
+        let result = self.wait_for_platform(
+            "Checking Mod Updates",
+            "Retrieving the details of the latest mod revisions...",
+            |callback| {
+                Rc::clone(&self.mod_directory)
+                    .query_remote_info(mod_ids.keys().copied().collect(), callback);
+            },
+        );
+        let remote_info = match result {
+            Some(Ok(remote_info)) => remote_info,
+            Some(Err(err)) => {
+                warn!(self.logger, "Error retrieving mod details"; "error" => %err);
+                return HashMap::new();
+            }
+            None => return HashMap::new(),
+        };
+
+        remote_info
+            .into_iter()
+            .filter_map(|(mod_id, info)| Some((mod_ids.get(&mod_id)?.clone(), info)))
+            .collect()
+    }
+
+    fn wait_for_platform<T: 'static>(
+        &self,
+        title: &str,
+        message: &str,
+        start: impl FnOnce(Box<dyn FnOnce(T)>),
+    ) -> Option<T> {
+        let result = Rc::new(RefCell::new(None));
+        start(Box::new({
+            let result = Rc::clone(&result);
+            move |value| {
+                *result.borrow_mut() = Some(value);
+                app::awake();
+            }
+        }));
+        if let Some(value) = result.borrow_mut().take() {
+            return Some(value);
+        }
+
+        let monitor = self.task_monitor(title, message, "Cancel");
+        monitor.show();
+        loop {
+            if let Some(value) = result.borrow_mut().take() {
+                return Some(value);
+            }
+            if monitor.result().is_some() {
+                return None;
+            }
+            if !self.app.wait() {
+                return None;
+            }
+        }
+    }
+
     fn config(&self) -> Ref<Config> {
         self.config.borrow()
     }
@@ -991,6 +1518,7 @@ impl Launcher {
             enable_battleye: use_battleye,
             use_all_cores: config.use_all_cores,
             extra_args: config.extra_args.clone(),
+            proton_command: config.proton_command.clone(),
         }
     }
 
@@ -1005,11 +1533,17 @@ impl Launcher {
     }
 
     fn check_mod_updates(&self) {
-        if !self.steam.can_launch() {
+        if !self.platform.can_launch() {
             return;
         }
 
-        for mod_info in self.game.installed_mods().iter() {
+        // Local mods are not managed by the platform, so they are never outdated.
+        for mod_info in self
+            .game
+            .installed_mods()
+            .iter()
+            .filter(|mod_info| mod_info.source == ModSource::Platform)
+        {
             match Rc::clone(&self.mod_directory).needs_update(mod_info) {
                 Ok(needs_update) => mod_info.set_needs_update(needs_update),
                 Err(err) => warn!(
@@ -1076,15 +1610,82 @@ const PROMPT_SP_MOD_MISMATCH: &str =
     "It looks like your mod list doesn't match this game. Launch anyway?";
 const TXT_MISSING_MODS: &str = "Missing mods:";
 const TXT_ADDED_MODS: &str = "Added mods:";
+const MSG_RESTART_FOR_NEW_MODS: &str =
+    "The downloaded mods were added to the mod list. Restart BUGLE to see their details.";
 const DLG_FILTER_MODLIST: &str = "Mod List Files\t*.txt";
+const DLG_FILTER_SERVER_LIST: &str = "CSV Files\t*.csv\nJSON Files\t*.json";
+const HISTORY_FILE_NAME: &str = "bugle-history.json";
+const FAVORITES_FILE_NAME: &str = "bugle-favorites.json";
+const VAULT_FILE_NAME: &str = "bugle-vault.json";
+const MOD_LIST_FILE_NAME: &str = "modlist.txt";
+const PINS_FILE_NAME: &str = "bugle-pins.json";
+const PINNED_MODS_DIR_NAME: &str = "bugle-pinned-mods";
+const TAGS_FILE_NAME: &str = "bugle-tags.json";
+const ERR_STEAM_NOT_RUNNING: &str = "Steam not running";
+const MSG_NO_PLATFORM_ACCOUNTS: &str = "Not used without Steam";
 const ERR_STEAM_NOT_ONLINE: &str = "Steam is in offline mode. Online play is disabled.";
 const ERR_FLS_ACCOUNT_NOT_CACHED: &str =
     "Steam is offline and the game has not stored your FLS account info. You need to start the \
     game in online mode at least once before you can play offline.";
 
+fn folder_size(path: &Path) -> u64 {
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => folder_size(&entry.path()),
+            Ok(_) => entry
+                .metadata()
+                .map(|metadata| metadata.len())
+                .unwrap_or_default(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+async fn run_fake_ping_server(logger: &Logger, mut args: pico_args::Arguments) -> Result<()> {
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::time::Duration;
+
+    use self::servers::{FakePingConfig, FakePingServer};
+
+    let ip: IpAddr = args
+        .opt_value_from_str("--ip")?
+        .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+    let port: u16 = args.opt_value_from_str("--port")?.unwrap_or(7778);
+    let config = FakePingConfig {
+        build_id: args.opt_value_from_str("--build-id")?,
+        players: args.opt_value_from_str("--players")?.unwrap_or_default(),
+        age: Duration::from_secs(
+            args.opt_value_from_str::<_, u64>("--age-days")?
+                .unwrap_or_default()
+                * 86400,
+        ),
+        latency: Duration::from_millis(
+            args.opt_value_from_str("--latency-ms")?.unwrap_or_default(),
+        ),
+        loss: args.opt_value_from_str("--loss")?.unwrap_or_default(),
+    };
+
+    let server = FakePingServer::bind(logger.clone(), SocketAddr::new(ip, port), config.clone())?;
+    info!(
+        logger,
+        "Fake ping server listening";
+        "addr" => server.local_addr(),
+        "config" => ?config,
+    );
+    tokio::signal::ctrl_c().await?;
+    info!(logger, "Fake ping server stopped"; "requests" => server.requests_received());
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() {
     let mut args = pico_args::Arguments::from_env();
+    let subcommand = args.subcommand().ok().flatten();
     let disable_prefetch = args.contains("--no-prefetch");
     let log_level_override = args
         .opt_value_from_fn(["-l", "--log-level"], |s| {
@@ -1099,6 +1700,19 @@ async fn main() {
     ));
     let (root_logger, log_guard) = create_root_logger(&log_level);
 
+    if subcommand.as_deref() == Some("fake-ping-server") {
+        if let Err(err) = run_fake_ping_server(&root_logger, args).await {
+            error!(root_logger, "Error running the fake ping server"; "error" => %err);
+        }
+        return;
+    }
+
+    let mut history_path = None;
+    let mut favorites_path = None;
+    let mut vault_path = None;
+    let mut pins_path = None;
+    let mut pinned_mods_path = None;
+    let mut tags_path = None;
     let config_persister: Box<dyn ConfigPersister + Send + Sync> = match IniConfigPersister::new() {
         Ok(persister) => {
             info!(
@@ -1106,6 +1720,12 @@ async fn main() {
                 "Opened persistent config file";
                 "path" => persister.path().display()
             );
+            history_path = Some(persister.path().with_file_name(HISTORY_FILE_NAME));
+            favorites_path = Some(persister.path().with_file_name(FAVORITES_FILE_NAME));
+            vault_path = Some(persister.path().with_file_name(VAULT_FILE_NAME));
+            pins_path = Some(persister.path().with_file_name(PINS_FILE_NAME));
+            pinned_mods_path = Some(persister.path().with_file_name(PINNED_MODS_DIR_NAME));
+            tags_path = Some(persister.path().with_file_name(TAGS_FILE_NAME));
             Box::new(persister)
         }
         Err(err) => {
@@ -1122,6 +1742,22 @@ async fn main() {
         warn!(root_logger, "Error while loading the configuration"; "error" => err.to_string());
         Config::default()
     });
+    let server_history = ServerHistory::load(history_path).unwrap_or_else(|err| {
+        warn!(root_logger, "Error while loading the server history"; "error" => %err);
+        ServerHistory::transient()
+    });
+    let favorite_details = FavoriteDetails::load(favorites_path).unwrap_or_else(|err| {
+        warn!(root_logger, "Error while loading the favorite server details"; "error" => %err);
+        FavoriteDetails::transient()
+    });
+    let mod_pins = ModPins::load(pins_path, pinned_mods_path).unwrap_or_else(|err| {
+        warn!(root_logger, "Error while loading the pinned mods"; "error" => %err);
+        ModPins::transient()
+    });
+    let mod_tags = ModTags::load(tags_path).unwrap_or_else(|err| {
+        warn!(root_logger, "Error while loading the mod tags"; "error" => %err);
+        ModTags::transient()
+    });
 
     if log_level_override.is_none() {
         log_level.store(
@@ -1133,25 +1769,34 @@ async fn main() {
     let app = App::default();
     Theme::from_config(config.theme).apply();
 
-    let mut steam = match Steam::locate(&root_logger) {
-        Some(steam) => steam,
-        None => {
-            dialog::alert_default(
-                "Cannot locate Steam installation. Please verify that you have Steam installed and\
-                 try again.",
-            );
-            return;
-        }
+    let mut platform: Box<dyn GamePlatform> = match &config.game_path {
+        Some(game_path) => Box::new(ManualInstall::new(
+            &root_logger,
+            game_path.clone(),
+            config.mods_path.clone(),
+            config.branch,
+        )),
+        None => match Steam::locate(&root_logger) {
+            Some(steam) => Box::new(steam),
+            None => {
+                dialog::alert_default(
+                    "Cannot locate Steam installation. Please verify that you have Steam \
+                     installed and try again. For a copy of Conan Exiles outside of Steam, set \
+                     GamePath in the BUGLE config to the game folder instead.",
+                );
+                return;
+            }
+        },
     };
-    let can_switch_branch = steam
+    let can_switch_branch = platform
         .locate_game(match config.branch {
             Branch::Main => Branch::PublicBeta,
             Branch::PublicBeta => Branch::Main,
         })
         .is_ok();
-    let game = steam
+    let game = platform
         .locate_game(config.branch)
-        .and_then(|loc| steam.init_game(loc));
+        .and_then(|loc| platform.init_game(loc, &config.local_mods_paths));
     let game = match game {
         Ok(game) => game,
         Err(err) => {
@@ -1209,10 +1854,15 @@ async fn main() {
         if log_level_override.is_none() { Some(log_level) } else { None },
         can_switch_branch,
         app,
-        steam,
+        platform,
         game,
         config,
         config_persister,
+        server_history,
+        favorite_details,
+        mod_pins,
+        mod_tags,
+        PasswordVault::new(vault_path),
     );
     launcher.run(disable_prefetch);
 
@@ -1,12 +1,15 @@
-use std::io::{Error, Result};
-use std::net::{IpAddr, Ipv4Addr, ToSocketAddrs, UdpSocket};
+use std::io::{Error, ErrorKind, Result};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::str::FromStr;
+use std::time::Duration;
 
 use reqwest::{Client, ClientBuilder};
+use socket2::{Domain, Protocol, Socket, Type};
 
 use crate::game::Game;
 
 pub fn is_valid_ip(ip: &IpAddr) -> bool {
-    match ip {
+    match canonical_ip(*ip) {
         IpAddr::V4(ip) => {
             !(ip.is_private()
                 || ip.is_loopback()
@@ -14,7 +17,102 @@ pub fn is_valid_ip(ip: &IpAddr) -> bool {
                 || ip.is_broadcast()
                 || ip.is_unspecified())
         }
-        _ => false,
+        IpAddr::V6(ip) => {
+            let first_segment = ip.segments()[0];
+            let is_unique_local = (first_segment & 0xfe00) == 0xfc00;
+            let is_link_local = (first_segment & 0xffc0) == 0xfe80;
+            let is_documentation = (first_segment == 0x2001) && (ip.segments()[1] == 0x0db8);
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || is_unique_local
+                || is_link_local
+                || is_documentation)
+        }
+    }
+}
+
+// Unwraps IPv4 addresses that were mapped into IPv6, so that the same server always ends up with
+// the same address, no matter how it was reported.
+pub fn canonical_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => IpAddr::V4(v4),
+            None => ip,
+        },
+        IpAddr::V4(_) => ip,
+    }
+}
+
+pub fn canonical_socket_addr(addr: SocketAddr) -> SocketAddr {
+    SocketAddr::new(canonical_ip(addr.ip()), addr.port())
+}
+
+// Accepts IPv6 addresses both with and without the surrounding brackets.
+pub fn parse_ip(s: &str) -> Option<IpAddr> {
+    let s = s.trim();
+    let s = s
+        .strip_prefix('[')
+        .and_then(|s| s.strip_suffix(']'))
+        .unwrap_or(s);
+    IpAddr::from_str(s).ok().map(canonical_ip)
+}
+
+// Parses "ip:port", where IPv6 addresses must be in brackets, e.g. "[2001:db8::1]:7777". An address
+// without a port uses the default game port.
+pub fn parse_socket_addr(s: &str) -> Option<SocketAddr> {
+    let s = s.trim();
+    if let Ok(addr) = SocketAddr::from_str(s) {
+        return Some(canonical_socket_addr(addr));
+    }
+    parse_ip(s).map(|ip| SocketAddr::new(ip, DEFAULT_GAME_PORT))
+}
+
+// Splits "host:port" into its parts, using the default game port if there isn't one. Only checks
+// that the host looks like a DNS name; whether it actually exists is up to resolve_host.
+pub fn parse_host_name(s: &str) -> Option<(String, u16)> {
+    let s = s.trim();
+    let (host, port) = match s.rsplit_once(':') {
+        Some((host, port)) => (host, u16::from_str(port).ok()?),
+        None => (s, DEFAULT_GAME_PORT),
+    };
+    let is_valid_label = |label: &str| {
+        !label.is_empty()
+            && (label.len() <= 63)
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || (c == '-'))
+    };
+    if (host.len() > 253) || !host.split('.').all(is_valid_label) || (port == 0) {
+        return None;
+    }
+    Some((host.to_ascii_lowercase(), port))
+}
+
+// Resolves a host name, giving up after a few seconds. IPv4 addresses come first in the result,
+// since that is what most servers are reachable at.
+pub async fn resolve_host(host: &str, port: u16) -> Result<Vec<SocketAddr>> {
+    let addrs = tokio::time::timeout(RESOLVE_TIMEOUT, tokio::net::lookup_host((host, port)))
+        .await
+        .map_err(|_| Error::new(ErrorKind::TimedOut, "timed out resolving host name"))??;
+    let mut addrs: Vec<SocketAddr> = addrs.map(canonical_socket_addr).collect();
+    if addrs.is_empty() {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            "host name has no addresses",
+        ));
+    }
+    addrs.sort_by_key(SocketAddr::is_ipv6);
+    addrs.dedup();
+    Ok(addrs)
+}
+
+pub fn format_host(ip: &IpAddr, port: u32) -> String {
+    match ip {
+        IpAddr::V4(ip) => format!("{}:{}", ip, port),
+        IpAddr::V6(ip) => format!("[{}]:{}", ip, port),
     }
 }
 
@@ -27,29 +125,41 @@ pub fn default_ip() -> IpAddr {
 }
 
 pub fn http_client_builder(game: &Game) -> ClientBuilder {
-    let (revision, _) = game.version();
+    let revision = game.version().map_or(0, |(revision, _)| revision);
     Client::builder().user_agent(format!(
         "game=ConanSandbox, engine=UE4, version=4.15.3-{}+ue415-dw-osl",
         revision
     ))
 }
 
+pub fn bind_udp_socket(addr: impl ToSocketAddrs) -> Result<UdpSocket> {
+    suppress_connection_reset(UdpSocket::bind(addr)?)
+}
+
+// Binds a single socket that can talk to both IPv4 and IPv6 hosts. IPv4 peers show up as
+// IPv4-mapped IPv6 addresses, so use canonical_socket_addr on the addresses it receives from.
+pub fn bind_dual_stack_udp_socket(port: u16) -> Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV6, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_only_v6(false)?;
+    socket.bind(&SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), port).into())?;
+    suppress_connection_reset(socket.into())
+}
+
 #[cfg(not(windows))]
 #[inline(always)]
-pub fn bind_udp_socket(addr: impl ToSocketAddrs) -> Result<UdpSocket> {
-    UdpSocket::bind(addr)
+fn suppress_connection_reset(socket: UdpSocket) -> Result<UdpSocket> {
+    Ok(socket)
 }
 
 // Suppress WSAECONNRESET on UDP sockets, see the link below:
 // https://stackoverflow.com/questions/34242622/windows-udp-sockets-recvfrom-fails-with-error-10054
 #[cfg(windows)]
-pub fn bind_udp_socket(addr: impl ToSocketAddrs) -> Result<UdpSocket> {
+fn suppress_connection_reset(socket: UdpSocket) -> Result<UdpSocket> {
     use std::os::windows::prelude::AsRawSocket;
     use winapi::shared::minwindef::{BOOL, DWORD, FALSE, LPDWORD, LPVOID};
     use winapi::um::mswsock::SIO_UDP_CONNRESET;
     use winapi::um::winsock2::{WSAGetLastError, WSAIoctl, SOCKET, SOCKET_ERROR};
 
-    let socket = UdpSocket::bind(addr)?;
     let handle = socket.as_raw_socket() as SOCKET;
 
     let ret = unsafe {
@@ -75,3 +185,6 @@ pub fn bind_udp_socket(addr: impl ToSocketAddrs) -> Result<UdpSocket> {
         Err(Error::from_raw_os_error(code))
     }
 }
+
+const DEFAULT_GAME_PORT: u16 = 7777;
+const RESOLVE_TIMEOUT: Duration = Duration::from_secs(5);
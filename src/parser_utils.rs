@@ -37,6 +37,10 @@ impl ParserError {
         };
         Self::from_error(input, err)
     }
+
+    pub fn offset(&self) -> isize {
+        self.offset
+    }
 }
 
 impl Display for ParserError {
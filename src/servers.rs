@@ -1,10 +1,30 @@
+mod changes;
+mod export;
+mod favorite_details;
 mod favorites;
+mod history;
 mod model;
 mod net;
+mod query;
+mod region;
+mod vault;
 
-pub use self::favorites::{FavoriteServer, FavoriteServers};
+pub use self::changes::{detect_changes, ServerChanges, SettingChange};
+pub use self::export::{export_servers, ExportFormat, ExportedServer};
+pub use self::favorite_details::FavoriteDetails;
+pub use self::favorites::{
+    export_favorites, import_favorites, parse_tags, remove_favorite, upsert_favorite,
+    FavoriteServer, FavoriteServers,
+};
+pub use self::history::{RecentServer, ServerHistory};
 pub use self::model::{
-    Community, DeserializationContext, DropOnDeath, Filter, Kind, Mode, Ownership, Region, Server,
-    SortCriteria, SortKey, TypeFilter, Validity, Weekday,
+    Community, DeserializationContext, DropOnDeath, Filter, Kind, Mode, Ownership, RaidHours,
+    Region, Server, SortCriteria, SortKey, TypeFilter, Validity, Weekday,
+};
+pub use self::net::{
+    fetch_server_list, query_server, FakePingConfig, FakePingServer, PingClient, PingRequest,
+    PingResponse, PingResult,
 };
-pub use self::net::{fetch_server_list, PingClient, PingRequest, PingResponse, PingResult};
+pub use self::query::{Query, QueryError};
+pub use self::region::RegionInference;
+pub use self::vault::PasswordVault;
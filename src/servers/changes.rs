@@ -0,0 +1,248 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+
+use super::Server;
+
+#[derive(Clone, Debug, Default)]
+pub struct ServerChanges {
+    pub added: bool,
+    pub removed: bool,
+    pub renamed_from: Option<String>,
+    pub map_changed_from: Option<String>,
+    pub mods_added: Vec<String>,
+    pub mods_removed: Vec<String>,
+    pub settings: Vec<SettingChange>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SettingChange {
+    pub name: &'static str,
+    pub old_value: String,
+    pub new_value: String,
+}
+
+impl ServerChanges {
+    pub fn is_empty(&self) -> bool {
+        !self.added
+            && !self.removed
+            && self.renamed_from.is_none()
+            && self.map_changed_from.is_none()
+            && self.mods_added.is_empty()
+            && self.mods_removed.is_empty()
+            && self.settings.is_empty()
+    }
+
+    fn between(old: &Server, new: &Server) -> Self {
+        let mut changes = Self::default();
+        if old.name != new.name {
+            changes.renamed_from = Some(old.name.clone());
+        }
+        if old.map != new.map {
+            changes.map_changed_from = Some(old.map.clone());
+        }
+
+        let old_mods: Vec<&str> = old.mod_entries().collect();
+        let new_mods: Vec<&str> = new.mod_entries().collect();
+        let old_set: HashSet<&str> = old_mods.iter().copied().collect();
+        let new_set: HashSet<&str> = new_mods.iter().copied().collect();
+        changes.mods_added = new_mods
+            .iter()
+            .filter(|entry| !old_set.contains(*entry))
+            .map(|entry| entry.to_string())
+            .collect();
+        changes.mods_removed = old_mods
+            .iter()
+            .filter(|entry| !new_set.contains(*entry))
+            .map(|entry| entry.to_string())
+            .collect();
+
+        for (name, value_fn) in TRACKED_SETTINGS {
+            let old_value = value_fn(old);
+            let new_value = value_fn(new);
+            if old_value != new_value {
+                changes.settings.push(SettingChange {
+                    name: *name,
+                    old_value,
+                    new_value,
+                });
+            }
+        }
+
+        changes
+    }
+}
+
+// Compares a freshly loaded server list to the previous one, matching servers by their ID. Servers
+// that are no longer listed are carried over for one refresh, marked as removed, so that they can
+// still be seen in the list. They keep their last ping results and are not pinged again, since
+// their address may well belong to a server that is still listed.
+pub fn detect_changes(previous: &[Server], current: &mut Vec<Server>) {
+    if previous.is_empty() {
+        return;
+    }
+
+    let previous_by_id: HashMap<&str, &Server> = previous
+        .iter()
+        .filter(|server| !server.changes.removed)
+        .map(|server| (server.id.as_str(), server))
+        .collect();
+    let mut current_ids = HashSet::with_capacity(current.len());
+
+    for server in current.iter_mut() {
+        current_ids.insert(server.id.clone());
+        server.changes = match previous_by_id.get(server.id.as_str()) {
+            Some(old) => ServerChanges::between(old, server),
+            None => ServerChanges {
+                added: true,
+                ..Default::default()
+            },
+        };
+    }
+
+    for (id, old) in previous_by_id {
+        if current_ids.contains(id) {
+            continue;
+        }
+        let mut server = old.clone();
+        server.changes = ServerChanges {
+            removed: true,
+            ..Default::default()
+        };
+        server.waiting_for_pong = false;
+        current.push(server);
+    }
+}
+
+fn optional<T: Display>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+type SettingValueFn = fn(&Server) -> String;
+
+// Server settings worth pointing out when they change. The name, map and mods are tracked
+// separately, and things like the player count or ping are left out since they change constantly.
+#[rustfmt::skip]
+const TRACKED_SETTINGS: &[(&str, SettingValueFn)] = &[
+    ("Host", |s| s.host()),
+    ("Mode", |s| s.mode().as_ref().to_string()),
+    ("Region", |s| s.region.as_ref().to_string()),
+    ("Community", |s| s.community.as_ref().to_string()),
+    ("Official", |s| s.is_official().to_string()),
+    ("Password Protected", |s| s.password_protected.to_string()),
+    ("BattlEye Required", |s| s.battleye_required.to_string()),
+    ("Max Players", |s| s.max_players.to_string()),
+    ("Max Ping", |s| optional(s.max_ping)),
+    ("Max Clan Size", |s| optional(s.max_clan_size)),
+    ("XP Rate Multiplier", |s| s.xp_rate_mult.to_string()),
+    ("Day Cycle Speed", |s| s.daylight.day_cycle_speed_mult.to_string()),
+    ("Dawn/Dusk Time Speed", |s| s.daylight.dawn_dusk_speed_mult.to_string()),
+    ("Use Catch Up Time", |s| s.daylight.use_catch_up_time.to_string()),
+    ("Stamina Cost Multiplier", |s| s.survival.stamina_cost_mult.to_string()),
+    ("Idle Thirst Multiplier", |s| s.survival.idle_thirst_mult.to_string()),
+    ("Active Thirst Multiplier", |s| s.survival.active_thirst_mult.to_string()),
+    ("Idle Hunger Multiplier", |s| s.survival.idle_hunger_mult.to_string()),
+    ("Active Hunger Multiplier", |s| s.survival.active_hunger_mult.to_string()),
+    ("On Death", |s| s.survival.drop_items_on_death.as_ref().to_string()),
+    ("Anyone Can Loot Corpse", |s| s.survival.anyone_can_loot_corpse.to_string()),
+    ("Offline Characters", |s| s.survival.offline_chars_in_world.to_string()),
+    ("Durability Multiplier", |s| s.combat.durability_mult.to_string()),
+    ("Thrall Wakeup Time", |s| s.combat.thrall_wakeup_time_secs().to_string()),
+    ("Harvest Amount Multiplier", |s| s.harvesting.harvest_amount_mult.to_string()),
+    ("Item Spoil Rate Scale", |s| s.harvesting.item_spoil_rate_mult.to_string()),
+    ("Resource Respawn Speed Multiplier", |s| s.harvesting.rsrc_respawn_speed_mult.to_string()),
+    ("Crafting Time Multiplier", |s| s.crafting.crafting_time_mult.to_string()),
+    ("Thrall Crafting Time Multiplier", |s| s.crafting.thrall_crafting_time_mult.to_string()),
+    ("Raid Hours", |s| s.raid_hours.describe().join("; ")),
+];
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::super::model::test_server;
+    use super::detect_changes;
+
+    #[test]
+    fn first_load_has_no_changes() {
+        let mut current = vec![test_server(json!({}))];
+        detect_changes(&[], &mut current);
+        assert!(current[0].changes.is_empty());
+    }
+
+    #[test]
+    fn added_servers() {
+        let previous = vec![test_server(json!({}))];
+        let mut current = vec![
+            test_server(json!({})),
+            test_server(json!({ "EXTERNAL_SERVER_UID": "new-server" })),
+        ];
+        detect_changes(&previous, &mut current);
+
+        assert_eq!(current.len(), 2);
+        assert!(current[0].changes.is_empty());
+        assert!(current[1].changes.added);
+    }
+
+    #[test]
+    fn removed_servers_are_carried_over_once() {
+        let previous = vec![
+            test_server(json!({})),
+            test_server(json!({ "EXTERNAL_SERVER_UID": "gone-server" })),
+        ];
+        let mut current = vec![test_server(json!({}))];
+        detect_changes(&previous, &mut current);
+
+        assert_eq!(current.len(), 2);
+        assert_eq!(current[1].id, "gone-server");
+        assert!(current[1].changes.removed);
+        assert!(!current[1].waiting_for_pong);
+
+        let previous = current;
+        let mut current = vec![test_server(json!({}))];
+        detect_changes(&previous, &mut current);
+
+        assert_eq!(current.len(), 1);
+        assert!(current[0].changes.is_empty());
+    }
+
+    #[test]
+    fn changed_servers() {
+        let previous = vec![test_server(
+            json!({ "S17": "2:0\n880454836\n1113901982\n" }),
+        )];
+        let mut current = vec![test_server(json!({
+            "Name": "Renamed Server",
+            "MapName": "Isle of Siptah",
+            "maxplayers": 60,
+            "Sz": 2.0,
+            "S17": "2:0\n880454836\n1369743238\n",
+        }))];
+        detect_changes(&previous, &mut current);
+
+        let changes = &current[0].changes;
+        assert!(!changes.added && !changes.removed);
+        assert_eq!(changes.renamed_from.as_deref(), Some("Test Server"));
+        assert_eq!(changes.map_changed_from.as_deref(), Some("Exiled Lands"));
+        assert_eq!(changes.mods_added, ["1369743238"]);
+        assert_eq!(changes.mods_removed, ["1113901982"]);
+
+        let settings: Vec<_> = changes
+            .settings
+            .iter()
+            .map(|setting| {
+                (
+                    setting.name,
+                    setting.old_value.as_str(),
+                    setting.new_value.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            settings,
+            [
+                ("Max Players", "40", "60"),
+                ("XP Rate Multiplier", "1.00", "2.00")
+            ]
+        );
+    }
+}
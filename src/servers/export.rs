@@ -0,0 +1,201 @@
+use std::io::Write;
+
+use super::Server;
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+// The exported fields of a server, in the order of `EXPORT_FIELDS`.
+pub struct ExportedServer {
+    values: Vec<Value>,
+}
+
+impl ExportedServer {
+    pub fn new(server: &Server) -> Self {
+        Self {
+            values: EXPORT_FIELDS
+                .iter()
+                .map(|(_, value_fn)| value_fn(server))
+                .collect(),
+        }
+    }
+}
+
+pub fn export_servers(
+    servers: &[ExportedServer],
+    format: ExportFormat,
+    mut writer: impl Write,
+) -> Result<()> {
+    match format {
+        ExportFormat::Csv => {
+            let header: Vec<String> = EXPORT_FIELDS
+                .iter()
+                .map(|(name, _)| csv_escape(name))
+                .collect();
+            writeln!(writer, "{}", header.join(","))?;
+            for server in servers {
+                let row: Vec<String> = server
+                    .values
+                    .iter()
+                    .map(|value| csv_escape(&field_text(value.clone())))
+                    .collect();
+                writeln!(writer, "{}", row.join(","))?;
+            }
+        }
+        ExportFormat::Json => {
+            let records: Vec<Value> = servers
+                .iter()
+                .map(|server| {
+                    let record: Map<String, Value> = EXPORT_FIELDS
+                        .iter()
+                        .zip(&server.values)
+                        .map(|((name, _), value)| (name.to_string(), value.clone()))
+                        .collect();
+                    Value::Object(record)
+                })
+                .collect();
+            serde_json::to_writer_pretty(&mut writer, &records)?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+type FieldValueFn = fn(&Server) -> Value;
+
+#[rustfmt::skip]
+const EXPORT_FIELDS: &[(&str, FieldValueFn)] = &[
+    ("id", |s| json!(s.id)),
+    ("name", |s| json!(s.name)),
+    ("map", |s| json!(s.map)),
+    ("host", |s| json!(s.host())),
+    ("mode", |s| json!(s.mode().as_ref())),
+    ("region", |s| json!(s.region.as_ref())),
+    ("community", |s| json!(s.community.as_ref())),
+    ("official", |s| json!(s.is_official())),
+    ("password_protected", |s| json!(s.password_protected)),
+    ("battleye_required", |s| json!(s.battleye_required)),
+    ("favorite", |s| json!(s.favorite)),
+    ("players", |s| json!(s.connected_players)),
+    ("max_players", |s| json!(s.max_players)),
+    ("age_days", |s| json!(s.age.map(|age| age.as_secs() / 86400))),
+    ("ping_ms", |s| json!(s.ping.map(|ping| ping.as_millis() as u64))),
+    ("build_id", |s| json!(s.build_id)),
+    ("max_ping", |s| json!(s.max_ping)),
+    ("max_clan_size", |s| json!(s.max_clan_size)),
+    ("xp_rate", |s| json!(s.xp_rate_mult.value())),
+    ("day_cycle_speed", |s| json!(s.daylight.day_cycle_speed_mult.value())),
+    ("dawn_dusk_speed", |s| json!(s.daylight.dawn_dusk_speed_mult.value())),
+    ("use_catch_up_time", |s| json!(s.daylight.use_catch_up_time)),
+    ("stamina_cost", |s| json!(s.survival.stamina_cost_mult.value())),
+    ("idle_thirst", |s| json!(s.survival.idle_thirst_mult.value())),
+    ("active_thirst", |s| json!(s.survival.active_thirst_mult.value())),
+    ("idle_hunger", |s| json!(s.survival.idle_hunger_mult.value())),
+    ("active_hunger", |s| json!(s.survival.active_hunger_mult.value())),
+    ("drop_items_on_death", |s| json!(s.survival.drop_items_on_death.as_ref())),
+    ("anyone_can_loot_corpse", |s| json!(s.survival.anyone_can_loot_corpse)),
+    ("offline_chars_in_world", |s| json!(s.survival.offline_chars_in_world)),
+    ("durability", |s| json!(s.combat.durability_mult.value())),
+    ("thrall_wakeup_time_secs", |s| json!(s.combat.thrall_wakeup_time_secs())),
+    ("harvest_amount", |s| json!(s.harvesting.harvest_amount_mult.value())),
+    ("item_spoil_rate", |s| json!(s.harvesting.item_spoil_rate_mult.value())),
+    ("rsrc_respawn_speed", |s| json!(s.harvesting.rsrc_respawn_speed_mult.value())),
+    ("crafting_time", |s| json!(s.crafting.crafting_time_mult.value())),
+    ("thrall_crafting_time", |s| json!(s.crafting.thrall_crafting_time_mult.value())),
+    ("raid_hours", |s| json!(s.raid_hours.describe())),
+    ("mods", |s| json!(s.mod_entries().collect::<Vec<_>>())),
+];
+
+fn field_text(value: Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s,
+        Value::Array(values) => values
+            .into_iter()
+            .map(field_text)
+            .collect::<Vec<_>>()
+            .join("; "),
+        other => other.to_string(),
+    }
+}
+
+fn csv_escape(value: &str) -> String {
+    if value.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::super::model::test_server;
+    use super::{csv_escape, export_servers, ExportFormat, ExportedServer};
+
+    #[test]
+    fn csv_escape_quotes_special_characters() {
+        assert_eq!(csv_escape("plain"), "plain");
+        assert_eq!(csv_escape("a, b"), "\"a, b\"");
+        assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_escape("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_escape("cr\r"), "\"cr\r\"");
+    }
+
+    #[test]
+    fn csv_output() {
+        let mut server = test_server(json!({
+            "Name": "Raid, \"Trade\" & Chill",
+            "S17": "2:0\n880454836\n1369743238\n",
+        }));
+        server.connected_players = Some(12);
+
+        let mut output = Vec::new();
+        export_servers(
+            &[ExportedServer::new(&server)],
+            ExportFormat::Csv,
+            &mut output,
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let mut lines = output.lines();
+
+        let header: Vec<&str> = lines.next().unwrap().split(',').collect();
+        assert_eq!(&header[..4], &["id", "name", "map", "host"]);
+        assert_eq!(header.last(), Some(&"mods"));
+
+        let row = lines.next().unwrap();
+        assert!(row.starts_with("test-server,\"Raid, \"\"Trade\"\" & Chill\",Exiled Lands,"));
+        assert!(row.ends_with(",880454836; 1369743238"));
+        assert!(lines.next().is_none());
+    }
+
+    #[test]
+    fn json_output() {
+        let mut server = test_server(json!({ "Sz": 2.5, "S97": 1800, "S104": 2200, "S111": true }));
+        server.connected_players = Some(12);
+
+        let mut output = Vec::new();
+        export_servers(
+            &[ExportedServer::new(&server)],
+            ExportFormat::Json,
+            &mut output,
+        )
+        .unwrap();
+        let records: Value = serde_json::from_slice(&output).unwrap();
+        let record = &records.as_array().unwrap()[0];
+
+        assert_eq!(record["id"], json!("test-server"));
+        assert_eq!(record["players"], json!(12));
+        assert_eq!(record["ping_ms"], Value::Null);
+        assert_eq!(record["xp_rate"], json!(2.5));
+        assert_eq!(record["raid_hours"], json!(["Sat 18:00-22:00"]));
+        assert_eq!(record["mods"], json!([]));
+    }
+}
@@ -0,0 +1,97 @@
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::config::JsonStore;
+
+use super::FavoriteServer;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Details {
+    id: Option<String>,
+    ip: Option<IpAddr>,
+    port: Option<u32>,
+    host_name: Option<String>,
+    notes: Option<String>,
+    tags: Vec<String>,
+}
+
+impl Details {
+    fn from_favorite(favorite: &FavoriteServer) -> Option<Self> {
+        if favorite.host_name.is_none() && favorite.notes.is_none() && favorite.tags.is_empty() {
+            return None;
+        }
+        Some(Self {
+            id: favorite.id.clone(),
+            ip: favorite.ip,
+            port: favorite.port,
+            host_name: favorite.host_name.clone(),
+            notes: favorite.notes.clone(),
+            tags: favorite.tags.clone(),
+        })
+    }
+
+    fn key(&self) -> FavoriteServer {
+        FavoriteServer {
+            name: None,
+            host_name: None,
+            ip: self.ip,
+            port: self.port,
+            id: self.id.clone(),
+            notes: None,
+            tags: Vec::new(),
+        }
+    }
+}
+
+// Game.ini only holds what the game itself understands about favorite servers, so everything BUGLE
+// adds on top of that is kept in a file of its own, keyed by the server's ID and address.
+pub struct FavoriteDetails {
+    entries: JsonStore<Vec<Details>>,
+}
+
+impl FavoriteDetails {
+    pub fn load(path: Option<PathBuf>) -> Result<Self> {
+        Ok(Self {
+            entries: JsonStore::load(path)?,
+        })
+    }
+
+    pub fn transient() -> Self {
+        Self {
+            entries: JsonStore::transient(),
+        }
+    }
+
+    // Fills in the details of favorites loaded from Game.ini.
+    pub fn apply(&self, favorites: &mut [FavoriteServer]) {
+        for favorite in favorites {
+            if let Some(details) = self
+                .entries
+                .iter()
+                .find(|details| details.key().matches(favorite))
+            {
+                if favorite.host_name.is_none() {
+                    favorite.host_name = details.host_name.clone();
+                }
+                if favorite.notes.is_none() {
+                    favorite.notes = details.notes.clone();
+                }
+                if favorite.tags.is_empty() {
+                    favorite.tags = details.tags.clone();
+                }
+            }
+        }
+    }
+
+    // Replaces the stored details with those of the favorites about to be saved to Game.ini.
+    pub fn update(&mut self, favorites: &[FavoriteServer]) -> Result<()> {
+        *self.entries = favorites
+            .iter()
+            .filter_map(Details::from_favorite)
+            .collect();
+        self.entries.save()
+    }
+}
@@ -1,39 +1,97 @@
-use std::collections::HashSet;
+use std::collections::hash_map::{Entry, HashMap};
+use std::io::{BufRead, Write};
 use std::net::IpAddr;
-use std::str::FromStr;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use futures::future::join_all;
 use nom::IResult;
+use slog::{debug, warn, Logger};
 
+use crate::net::{canonical_ip, format_host, parse_ip, resolve_host};
 use crate::parser_utils::{
     escape_string, extract_value, parse_hex, parse_map, parse_quoted, ParserError,
 };
 
 use super::Server;
 
+#[derive(Clone, Debug)]
 pub struct FavoriteServer {
     pub name: Option<String>,
+    pub host_name: Option<String>,
     pub ip: Option<IpAddr>,
     pub port: Option<u32>,
     pub id: Option<String>,
+    pub notes: Option<String>,
+    pub tags: Vec<String>,
 }
 
 impl FavoriteServer {
     pub fn from_server(server: &Server) -> Self {
         Self {
             name: Some(server.name.clone()),
+            host_name: server.host_name.clone(),
             ip: Some(*server.ip()),
             port: Some(server.port),
             id: Some(server.id.clone()),
+            notes: None,
+            tags: Vec::new(),
         }
     }
 
+    // Two entries refer to the same server if they share the server ID, or the address the server
+    // was added by.
+    pub fn matches(&self, other: &FavoriteServer) -> bool {
+        if let (Some(id), Some(other_id)) = (&self.id, &other.id) {
+            if id == other_id {
+                return true;
+            }
+        }
+        if self.port.is_none() || self.port != other.port {
+            return false;
+        }
+        match (self.ip, other.ip) {
+            (Some(ip), Some(other_ip)) if canonical_ip(ip) == canonical_ip(other_ip) => true,
+            _ => self.host_name.is_some() && self.host_name == other.host_name,
+        }
+    }
+
+    pub fn address(&self) -> Option<String> {
+        let port = self.port?;
+        match (&self.host_name, &self.ip) {
+            (Some(host_name), _) => Some(format!("{}:{}", host_name, port)),
+            (None, Some(ip)) => Some(format_host(ip, port)),
+            (None, None) => None,
+        }
+    }
+
+    // The first tag doubles as the group the favorite is listed under.
+    pub fn group(&self) -> Option<&str> {
+        self.tags.first().map(String::as_str)
+    }
+
+    // Only the group counts here; the other tags don't put a favorite in their group.
+    pub fn is_in_group(&self, group: &str) -> bool {
+        self.group()
+            .map_or(false, |own_group| own_group.eq_ignore_ascii_case(group))
+    }
+
     pub fn parse(input: &str) -> Result<Self> {
         Ok(extract_value(parse_favorite_impl(input))
             .map_err(|err| ParserError::from_err(input, err))?)
     }
 
+    // Formats the entry the way the game stores it in Game.ini.
     pub fn to_string(&self) -> String {
+        self.format(false)
+    }
+
+    // Formats the entry for sharing, including what BUGLE keeps on the side: the host name, notes
+    // and tags.
+    pub fn to_export_string(&self) -> String {
+        self.format(true)
+    }
+
+    fn format(&self, extended: bool) -> String {
         use std::fmt::Write;
 
         let mut result = "(".to_string();
@@ -42,6 +100,16 @@ impl FavoriteServer {
             write!(&mut result, "{}=\"{}\",", KEY_NAME, escape_string(name)).unwrap();
         }
 
+        if let Some(host_name) = self.host_name.as_ref().filter(|_| extended) {
+            write!(
+                &mut result,
+                "{}=\"{}\",",
+                KEY_HOST_NAME,
+                escape_string(host_name)
+            )
+            .unwrap();
+        }
+
         if let Some(ip) = &self.ip {
             write!(&mut result, "{}=\"{}\",", KEY_IP, ip).unwrap();
         }
@@ -54,6 +122,15 @@ impl FavoriteServer {
             write!(&mut result, "{}={},", KEY_ID, id).unwrap();
         }
 
+        if let Some(notes) = self.notes.as_ref().filter(|_| extended) {
+            write!(&mut result, "{}=\"{}\",", KEY_NOTES, escape_string(notes)).unwrap();
+        }
+
+        if extended && !self.tags.is_empty() {
+            let tags = self.tags.join(",");
+            write!(&mut result, "{}=\"{}\",", KEY_TAGS, escape_string(&tags)).unwrap();
+        }
+
         result.pop();
         result.push(')');
 
@@ -61,48 +138,190 @@ impl FavoriteServer {
     }
 }
 
+// Adds the favorite to the list, replacing any entry for the same server. Notes and tags of the
+// replaced entry are kept, unless the new entry has its own.
+pub fn upsert_favorite(favorites: &mut Vec<FavoriteServer>, mut favorite: FavoriteServer) {
+    if let Some(idx) = favorites
+        .iter()
+        .position(|existing| existing.matches(&favorite))
+    {
+        let existing = favorites.remove(idx);
+        if favorite.notes.is_none() {
+            favorite.notes = existing.notes;
+        }
+        if favorite.tags.is_empty() {
+            favorite.tags = existing.tags;
+        }
+        if favorite.host_name.is_none() {
+            favorite.host_name = existing.host_name;
+        }
+    }
+    favorites.push(favorite);
+}
+
+pub fn remove_favorite(favorites: &mut Vec<FavoriteServer>, favorite: &FavoriteServer) {
+    favorites.retain(|existing| !existing.matches(favorite));
+}
+
+// Favorites are shared as plain text, one entry per line in the format the game uses in Game.ini,
+// extended with the details BUGLE keeps on the side. Lines copied straight out of Game.ini,
+// including the key, are accepted as well.
+pub fn export_favorites<W: Write>(favorites: &[FavoriteServer], mut writer: W) -> Result<()> {
+    for favorite in favorites {
+        writeln!(writer, "{}", favorite.to_export_string())?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn import_favorites<R: BufRead>(reader: R) -> Result<Vec<FavoriteServer>> {
+    let mut favorites = Vec::new();
+    for (line_no, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        let value = match line.strip_prefix(IMPORT_KEY_PREFIX) {
+            Some(value) => value.trim_start(),
+            None => line,
+        };
+        let favorite =
+            FavoriteServer::parse(value).map_err(|err| anyhow!("Line {}: {}", line_no + 1, err))?;
+        if favorite.ip.is_none() && favorite.host_name.is_none() && favorite.id.is_none() {
+            return Err(anyhow!("Line {}: {}", line_no + 1, ERR_NO_SERVER));
+        }
+        upsert_favorite(&mut favorites, favorite);
+    }
+    Ok(favorites)
+}
+
 pub struct FavoriteServers {
-    by_addr: HashSet<(IpAddr, u32)>,
-    by_id: HashSet<String>,
+    favorites: Vec<FavoriteServer>,
+    by_addr: HashMap<(IpAddr, u32), usize>,
+    by_id: HashMap<String, usize>,
+}
+
+impl FromIterator<FavoriteServer> for FavoriteServers {
+    fn from_iter<I: IntoIterator<Item = FavoriteServer>>(iter: I) -> Self {
+        let mut favorites = Self::new();
+        for favorite in iter {
+            favorites.insert(favorite);
+        }
+        favorites
+    }
 }
 
 impl FavoriteServers {
     pub fn new() -> Self {
         Self {
-            by_addr: HashSet::new(),
-            by_id: HashSet::new(),
+            favorites: Vec::new(),
+            by_addr: HashMap::new(),
+            by_id: HashMap::new(),
         }
     }
 
     pub fn insert(&mut self, favorite: FavoriteServer) -> bool {
+        let idx = self.favorites.len();
+        let mut inserted = false;
         if let (Some(ip), Some(port)) = (favorite.ip, favorite.port) {
-            self.by_addr.insert((ip, port))
-        } else if let Some(id) = favorite.id {
-            self.by_id.insert(id)
+            if let Entry::Vacant(entry) = self.by_addr.entry((canonical_ip(ip), port)) {
+                entry.insert(idx);
+                inserted = true;
+            }
+        }
+        if let Some(id) = &favorite.id {
+            if let Entry::Vacant(entry) = self.by_id.entry(id.clone()) {
+                entry.insert(idx);
+                inserted = true;
+            }
+        }
+        // Favorites added by host name may not have an address until the host name is resolved.
+        if !inserted && favorite.host_name.is_some() {
+            inserted = !self
+                .favorites
+                .iter()
+                .any(|existing| existing.matches(&favorite));
+        }
+        if inserted {
+            self.favorites.push(favorite);
+            true
         } else {
             false
         }
     }
 
-    pub fn contains(&self, server: &Server) -> bool {
-        self.by_addr.contains(&(*server.ip(), server.port)) || self.by_id.contains(&server.id)
+    pub fn find(&self, server: &Server) -> Option<&FavoriteServer> {
+        self.by_addr
+            .get(&(*server.ip(), server.port))
+            .or_else(|| self.by_id.get(&server.id))
+            .map(|&idx| &self.favorites[idx])
+    }
+
+    // Looks up the current addresses of favorites that were added by host name, so that they
+    // still match the server after its IP changes.
+    pub async fn resolve_host_names(&mut self, logger: &Logger) {
+        let lookups = self
+            .favorites
+            .iter()
+            .enumerate()
+            .filter_map(
+                |(idx, favorite)| match (&favorite.host_name, favorite.port) {
+                    (Some(host_name), Some(port)) => Some((idx, host_name.clone(), port)),
+                    _ => None,
+                },
+            )
+            .map(|(idx, host_name, port)| async move {
+                let result = resolve_host(&host_name, port as _).await;
+                (idx, host_name, port, result)
+            });
+        for (idx, host_name, port, result) in join_all(lookups).await {
+            match result {
+                Ok(addrs) => {
+                    debug!(
+                        logger,
+                        "Resolved favorite server host name";
+                        "host_name" => &host_name,
+                        "addrs" => ?addrs,
+                    );
+                    for addr in addrs {
+                        self.by_addr.insert((addr.ip(), port), idx);
+                    }
+                }
+                Err(err) => warn!(
+                    logger,
+                    "Error resolving favorite server host name";
+                    "host_name" => &host_name,
+                    "error" => %err,
+                ),
+            }
+        }
     }
 }
 
 const KEY_NAME: &str = "ServerName";
+const KEY_HOST_NAME: &str = "HostName";
 const KEY_IP: &str = "IPAddress";
 const KEY_PORT: &str = "Port";
 const KEY_ID: &str = "UID";
+const KEY_NOTES: &str = "Notes";
+const KEY_TAGS: &str = "Tags";
+
+const IMPORT_KEY_PREFIX: &str = "ServersList=";
+const ERR_NO_SERVER: &str = "Entry has no server address or ID.";
 
 fn parse_favorite_impl(input: &str) -> IResult<&str, FavoriteServer> {
     let (input, map) = parse_map(input)?;
     let name = map
         .get(KEY_NAME)
         .and_then(|value| extract_value(parse_quoted(value)).ok());
+    let host_name = map
+        .get(KEY_HOST_NAME)
+        .and_then(|value| extract_value(parse_quoted(value)).ok());
     let ip = map
         .get(KEY_IP)
         .and_then(|value| extract_value(parse_quoted(value)).ok())
-        .and_then(|s| IpAddr::from_str(&s).ok());
+        .and_then(|s| parse_ip(&s));
     let port = map
         .get(KEY_PORT)
         .and_then(|value| u32::from_str_radix(value, 10).ok());
@@ -110,6 +329,181 @@ fn parse_favorite_impl(input: &str) -> IResult<&str, FavoriteServer> {
         .get(KEY_ID)
         .and_then(|value| extract_value(parse_hex(value, 32)).ok())
         .map(str::to_string);
-    let favorite = FavoriteServer { name, ip, port, id };
+    let notes = map
+        .get(KEY_NOTES)
+        .and_then(|value| extract_value(parse_quoted(value)).ok())
+        .filter(|notes| !notes.is_empty());
+    let tags = map
+        .get(KEY_TAGS)
+        .and_then(|value| extract_value(parse_quoted(value)).ok())
+        .map(|tags| parse_tags(&tags))
+        .unwrap_or_default();
+    let favorite = FavoriteServer {
+        name,
+        host_name,
+        ip,
+        port,
+        id,
+        notes,
+        tags,
+    };
     Ok((input, favorite))
 }
+
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in input
+        .split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+    {
+        if !tags
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(tag))
+        {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        export_favorites, import_favorites, upsert_favorite, FavoriteServer, FavoriteServers,
+    };
+
+    const HOST_NAME_FAVORITE: &str = "(ServerName=\"My Server\",HostName=\"conan.example.com\",\
+                                      IPAddress=\"203.0.113.10\",Port=7777)";
+
+    #[test]
+    fn game_format_excludes_host_name() {
+        let favorite = FavoriteServer::parse(HOST_NAME_FAVORITE).unwrap();
+        assert_eq!(favorite.host_name.as_deref(), Some("conan.example.com"));
+        assert_eq!(
+            favorite.to_string(),
+            "(ServerName=\"My Server\",IPAddress=\"203.0.113.10\",Port=7777)"
+        );
+        assert_eq!(favorite.to_export_string(), HOST_NAME_FAVORITE);
+    }
+
+    #[test]
+    fn insert_skips_duplicates_with_host_names() {
+        let favorite = FavoriteServer::parse(HOST_NAME_FAVORITE).unwrap();
+        let mut unresolved = favorite.clone();
+        unresolved.ip = None;
+
+        let mut favorites = FavoriteServers::new();
+        assert!(favorites.insert(favorite.clone()));
+        assert!(!favorites.insert(favorite));
+        assert!(!favorites.insert(unresolved.clone()));
+        assert_eq!(favorites.favorites.len(), 1);
+
+        let mut favorites = FavoriteServers::new();
+        assert!(favorites.insert(unresolved.clone()));
+        assert!(!favorites.insert(unresolved));
+        assert_eq!(favorites.favorites.len(), 1);
+    }
+
+    #[test]
+    fn game_format_excludes_notes_and_tags() {
+        let mut favorite = FavoriteServer::parse(HOST_NAME_FAVORITE).unwrap();
+        favorite.notes = Some("Clan \"base\"".to_string());
+        favorite.tags = vec!["pvp".to_string(), "eu".to_string()];
+        assert_eq!(
+            favorite.to_string(),
+            "(ServerName=\"My Server\",IPAddress=\"203.0.113.10\",Port=7777)"
+        );
+        assert_eq!(
+            favorite.to_export_string(),
+            "(ServerName=\"My Server\",HostName=\"conan.example.com\",\
+             IPAddress=\"203.0.113.10\",Port=7777,Notes=\"Clan \\\"base\\\"\",Tags=\"pvp,eu\")"
+        );
+    }
+
+    #[test]
+    fn group_is_first_tag() {
+        let mut favorite = FavoriteServer::parse(HOST_NAME_FAVORITE).unwrap();
+        assert_eq!(favorite.group(), None);
+        assert!(!favorite.is_in_group("pvp"));
+
+        favorite.tags = vec!["PvP".to_string(), "eu".to_string()];
+        assert_eq!(favorite.group(), Some("PvP"));
+        assert!(favorite.is_in_group("pvp"));
+        assert!(!favorite.is_in_group("eu"));
+    }
+
+    #[test]
+    fn export_import_round_trip() {
+        let mut first = FavoriteServer::parse(HOST_NAME_FAVORITE).unwrap();
+        first.notes = Some("Clan \"base\", north".to_string());
+        first.tags = vec!["pvp".to_string(), "eu".to_string()];
+        let second = FavoriteServer::parse(
+            "(ServerName=\"Other\",IPAddress=\"2001:db8::1\",Port=7779,\
+             UID=0123456789abcdef0123456789abcdef)",
+        )
+        .unwrap();
+
+        let mut exported = Vec::new();
+        export_favorites(&[first.clone(), second.clone()], &mut exported).unwrap();
+        let imported = import_favorites(exported.as_slice()).unwrap();
+
+        assert_eq!(imported.len(), 2);
+        for (imported, original) in imported.iter().zip([first, second].iter()) {
+            assert_eq!(imported.to_export_string(), original.to_export_string());
+        }
+    }
+
+    #[test]
+    fn import_accepts_game_ini_lines() {
+        let input = "; Copied from Game.ini\n\
+                     \n\
+                     ServersList=(ServerName=\"A\",IPAddress=\"203.0.113.10\",Port=7777)\n\
+                     (ServerName=\"B\",IPAddress=\"203.0.113.11\",Port=7777)\n\
+                     (ServerName=\"A again\",IPAddress=\"203.0.113.10\",Port=7777,Tags=\"x\")\n";
+        let imported = import_favorites(input.as_bytes()).unwrap();
+
+        let names: Vec<_> = imported
+            .iter()
+            .map(|favorite| favorite.name.as_deref().unwrap())
+            .collect();
+        assert_eq!(names, ["B", "A again"]);
+        assert_eq!(imported[1].tags, ["x"]);
+
+        let err = import_favorites("(ServerName=\"Nowhere\")\n".as_bytes()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Line 1: Entry has no server address or ID."
+        );
+    }
+
+    #[test]
+    fn upsert_keeps_details_of_replaced_entry() {
+        let mut existing = FavoriteServer::parse(HOST_NAME_FAVORITE).unwrap();
+        existing.notes = Some("Notes".to_string());
+        existing.tags = vec!["pve".to_string()];
+        let mut favorites = vec![existing];
+
+        let mut renamed =
+            FavoriteServer::parse("(ServerName=\"Renamed\",IPAddress=\"203.0.113.10\",Port=7777)")
+                .unwrap();
+        upsert_favorite(&mut favorites, renamed.clone());
+        assert_eq!(favorites.len(), 1);
+        assert_eq!(favorites[0].name.as_deref(), Some("Renamed"));
+        assert_eq!(favorites[0].host_name.as_deref(), Some("conan.example.com"));
+        assert_eq!(favorites[0].notes.as_deref(), Some("Notes"));
+        assert_eq!(favorites[0].tags, ["pve"]);
+
+        renamed.tags = vec!["pvp".to_string()];
+        upsert_favorite(&mut favorites, renamed);
+        assert_eq!(favorites.len(), 1);
+        assert_eq!(favorites[0].tags, ["pvp"]);
+        assert_eq!(favorites[0].notes.as_deref(), Some("Notes"));
+
+        let other =
+            FavoriteServer::parse("(ServerName=\"Other\",IPAddress=\"203.0.113.10\",Port=7779)")
+                .unwrap();
+        upsert_favorite(&mut favorites, other);
+        assert_eq!(favorites.len(), 2);
+    }
+}
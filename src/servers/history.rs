@@ -0,0 +1,50 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::config::JsonStore;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecentServer {
+    pub addr: SocketAddr,
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub joined_at: SystemTime,
+    pub mods: Vec<String>,
+    pub launched: bool,
+}
+
+// Keeps track of the servers the user tried to join, most recent first. Game.ini only remembers
+// the last one, so BUGLE keeps its own history.
+pub struct ServerHistory {
+    entries: JsonStore<Vec<RecentServer>>,
+}
+
+impl ServerHistory {
+    pub fn load(path: Option<PathBuf>) -> Result<Self> {
+        Ok(Self {
+            entries: JsonStore::load(path)?,
+        })
+    }
+
+    pub fn transient() -> Self {
+        Self {
+            entries: JsonStore::transient(),
+        }
+    }
+
+    pub fn entries(&self) -> &[RecentServer] {
+        &self.entries
+    }
+
+    pub fn record(&mut self, entry: RecentServer) -> Result<()> {
+        self.entries.insert(0, entry);
+        self.entries.truncate(MAX_ENTRIES);
+        self.entries.save()
+    }
+}
+
+const MAX_ENTRIES: usize = 200;
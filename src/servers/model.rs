@@ -6,11 +6,12 @@ use bitflags::bitflags;
 use serde::de::{MapAccess, Visitor};
 use serde::Deserialize;
 use serde_repr::Deserialize_repr;
+use strum::IntoEnumIterator;
 use strum_macros::{AsRefStr, EnumIter, EnumString, FromRepr};
 
-use crate::net::{is_valid_ip, is_valid_port};
+use crate::net::{canonical_ip, format_host, is_valid_ip, is_valid_port};
 
-use super::FavoriteServers;
+use super::{FavoriteServers, ServerChanges};
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct Server {
@@ -107,12 +108,21 @@ pub struct Server {
     #[serde(skip)]
     pub favorite: bool,
 
+    #[serde(skip)]
+    pub host_name: Option<String>,
+
+    #[serde(skip)]
+    pub inferred_region: Option<Region>,
+
+    #[serde(skip)]
+    pub changes: ServerChanges,
+
     #[serde(skip)]
     pub validity: Validity,
 }
 
 pub struct DeserializationContext<'dc> {
-    pub build_id: u32,
+    pub build_id: Option<u32>,
     pub favorites: &'dc FavoriteServers,
 }
 
@@ -123,19 +133,28 @@ impl Server {
     ) -> Result<Self, D::Error> {
         let mut server = <Server as Deserialize>::deserialize(deserializer)?;
 
-        server.ip = if is_valid_ip(&server.reported_ip) || server.observed_ip.is_none() {
-            server.reported_ip
-        } else {
-            server.observed_ip.unwrap()
+        server.reported_ip = canonical_ip(server.reported_ip);
+        server.observed_ip = server.observed_ip.map(canonical_ip);
+        server.ip = match server.observed_ip {
+            Some(observed_ip) if !is_valid_ip(&server.reported_ip) && is_valid_ip(&observed_ip) => {
+                observed_ip
+            }
+            _ => server.reported_ip,
         };
 
         if server.name.is_empty() {
             server.name = server.host();
         }
 
-        server.favorite = ctx.favorites.contains(&server);
+        if let Some(favorite) = ctx.favorites.find(&server) {
+            server.favorite = true;
+            server.host_name = favorite.host_name.clone();
+        }
 
-        if server.build_id != ctx.build_id {
+        if ctx
+            .build_id
+            .map_or(false, |build_id| server.build_id != build_id)
+        {
             server.validity.insert(Validity::INVALID_BUILD);
         }
         if !is_valid_ip(server.ip()) {
@@ -150,6 +169,10 @@ impl Server {
         Ok(server)
     }
 
+    pub fn effective_region(&self) -> Region {
+        self.inferred_region.unwrap_or(self.region)
+    }
+
     pub fn mode(&self) -> Mode {
         if self.pvp_enabled {
             match self.kind {
@@ -166,7 +189,7 @@ impl Server {
     }
 
     pub fn host(&self) -> String {
-        format!("{}:{}", self.ip(), self.port)
+        format_host(self.ip(), self.port)
     }
 
     pub fn game_addr(&self) -> Option<SocketAddr> {
@@ -196,6 +219,19 @@ impl Server {
     pub fn is_modded(&self) -> bool {
         self.mods.is_some()
     }
+
+    pub fn mod_entries(&self) -> impl Iterator<Item = &str> {
+        // The first line holds the Steam and non-Steam mod counts, the rest are the mods themselves.
+        self.mods
+            .iter()
+            .flat_map(|mods| mods.lines().skip(1))
+            .filter(|entry| !entry.is_empty())
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.connected_players
+            .map_or(false, |players| players >= self.max_players)
+    }
 }
 
 #[derive(
@@ -231,7 +267,7 @@ pub enum Ownership {
     Official,
 }
 
-#[derive(Clone, Copy, Debug, Deserialize_repr, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, Deserialize_repr, AsRefStr, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(u8)]
 pub enum Community {
     Unspecified,
@@ -287,6 +323,10 @@ impl Default for Multiplier {
 }
 
 impl Multiplier {
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+
     pub fn to_string(&self) -> String {
         format!("{:.2}", self.0)
     }
@@ -367,7 +407,7 @@ pub struct CraftingSettings {
     pub thrall_crafting_time_mult: Multiplier,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, AsRefStr)]
 pub enum DropOnDeath {
     Nothing,
     All,
@@ -420,7 +460,7 @@ impl<'de> Deserialize<'de> for DropOnDeath {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EnumIter)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, AsRefStr, EnumIter)]
 pub enum Weekday {
     Mon,
     Tue,
@@ -443,6 +483,10 @@ impl RaidTime {
         (self.0 % 100) as _
     }
 
+    pub fn minutes_of_day(&self) -> u16 {
+        self.hours() as u16 * 60 + self.minutes() as u16
+    }
+
     pub fn to_string(&self) -> String {
         format!("{:02}:{:02}", self.hours(), self.minutes())
     }
@@ -463,6 +507,17 @@ impl RaidHours {
     pub fn get(&self, day: Weekday) -> Option<&(RaidTime, RaidTime)> {
         self.hours.get(&day)
     }
+
+    // One entry per day with raid hours, in weekday order, e.g. "Sat 18:00-22:00".
+    pub fn describe(&self) -> Vec<String> {
+        Weekday::iter()
+            .filter_map(|day| {
+                self.get(day).map(|(start, end)| {
+                    format!("{} {}-{}", day.as_ref(), start.to_string(), end.to_string())
+                })
+            })
+            .collect()
+    }
 }
 
 impl<'de> Deserialize<'de> for RaidHours {
@@ -573,10 +628,12 @@ pub struct Filter {
     pub type_filter: TypeFilter,
     pub mode: Option<Mode>,
     pub region: Option<Region>,
+    pub use_inferred_region: bool,
     pub battleye_required: Option<bool>,
     pub include_invalid: bool,
     pub exclude_password_protected: bool,
     pub mods: Option<bool>,
+    pub expression: String,
 }
 
 #[derive(Clone, Copy, Debug, AsRefStr, EnumIter, EnumString, Hash, PartialEq, Eq)]
@@ -589,6 +646,9 @@ pub enum SortKey {
     Players,
     Age,
     Ping,
+    XpRate,
+    HarvestAmount,
+    MaxClanSize,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -619,3 +679,46 @@ impl SortCriteria {
 const fn default_true() -> bool {
     true
 }
+
+// Builds a server the way the server list would report it, with the given JSON fields replacing
+// the defaults.
+#[cfg(test)]
+pub(super) fn test_server(fields: serde_json::Value) -> Server {
+    let mut json = serde_json::json!({
+        "EXTERNAL_SERVER_UID": "test-server",
+        "Name": "Test Server",
+        "MapName": "Exiled Lands",
+        "private": false,
+        "CSF": 0,
+        "S05": false,
+        "Sy": 0,
+        "maxplayers": 40,
+        "S0": false,
+        "S30": 0,
+        "ip": "203.0.113.10",
+        "Port": 7777,
+        "buildId": 1,
+        "Su": 0,
+        "Sz": 1.0,
+        "S21": 1.0,
+        "S22": 1.0,
+        "S23": 1.0,
+        "S24": 1.0,
+        "S7": false,
+        "Sa": false,
+        "Sl": 1.0,
+        "S8": 1.0,
+        "S4": 1.0,
+    });
+    if let (Some(json), serde_json::Value::Object(fields)) = (json.as_object_mut(), fields) {
+        json.extend(fields);
+    }
+
+    let text = json.to_string();
+    let favorites = FavoriteServers::new();
+    let ctx = DeserializationContext {
+        build_id: Some(1),
+        favorites: &favorites,
+    };
+    Server::deserialize(&mut serde_json::Deserializer::from_str(&text), &ctx).unwrap()
+}
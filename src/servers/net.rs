@@ -1,5 +1,8 @@
+mod congestion;
+mod fake_ping;
 mod http;
 mod ping;
 
+pub use self::fake_ping::{FakePingConfig, FakePingServer};
 pub use self::http::fetch_server_list;
-pub use self::ping::{PingClient, PingRequest, PingResponse, PingResult};
+pub use self::ping::{query_server, PingClient, PingRequest, PingResponse, PingResult};
@@ -0,0 +1,283 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashSet;
+use std::hash::{BuildHasher, Hasher};
+use std::net::SocketAddr;
+use std::num::NonZeroU32;
+use std::time::Duration;
+
+use slog::{debug, info, Logger};
+
+use super::ping::PingSettings;
+
+// Adjusts the ping rate and timeout to the observed network conditions. The rate is cut in half
+// whenever too many pings in a window are lost, and then slowly raised back up to the configured
+// limit. The timeout follows the smoothed round trip time, the same way TCP computes its RTO.
+// Only servers that answered before count towards the loss, since the server list is full of
+// servers that are down and would otherwise keep the rate at its minimum.
+pub struct CongestionControl {
+    logger: Logger,
+    max_rate: u32,
+    min_rate: u32,
+    rate: u32,
+    max_timeout: Duration,
+    min_timeout: Duration,
+    smoothed_rtt: Option<Duration>,
+    rtt_variance: Duration,
+    window_received: usize,
+    window_lost: usize,
+    responsive: HashSet<SocketAddr>,
+}
+
+impl CongestionControl {
+    pub fn new(logger: Logger, settings: &PingSettings) -> Self {
+        let max_rate = settings.rate_limit.get();
+        Self {
+            logger,
+            max_rate,
+            min_rate: u32::max(1, max_rate / MIN_RATE_DIVISOR),
+            rate: max_rate,
+            max_timeout: settings.timeout,
+            min_timeout: Duration::min(MIN_TIMEOUT, settings.timeout),
+            smoothed_rtt: None,
+            rtt_variance: Duration::ZERO,
+            window_received: 0,
+            window_lost: 0,
+            responsive: HashSet::new(),
+        }
+    }
+
+    pub fn rate(&self) -> NonZeroU32 {
+        NonZeroU32::new(self.rate).unwrap()
+    }
+
+    pub fn timeout(&self) -> Duration {
+        match self.smoothed_rtt {
+            Some(srtt) => (srtt + self.rtt_variance * 4).clamp(self.min_timeout, self.max_timeout),
+            None => self.max_timeout,
+        }
+    }
+
+    pub fn on_pong(&mut self, addr: SocketAddr, round_trip: Duration) {
+        self.responsive.insert(addr);
+        match self.smoothed_rtt {
+            Some(srtt) => {
+                let delta = if srtt > round_trip { srtt - round_trip } else { round_trip - srtt };
+                self.rtt_variance = (self.rtt_variance * 3 + delta) / 4;
+                self.smoothed_rtt = Some((srtt * 7 + round_trip) / 8);
+            }
+            None => {
+                self.smoothed_rtt = Some(round_trip);
+                self.rtt_variance = round_trip / 2;
+            }
+        }
+        self.window_received += 1;
+        self.end_window_if_full();
+    }
+
+    pub fn on_loss(&mut self, addr: SocketAddr) {
+        if !self.responsive.contains(&addr) {
+            return;
+        }
+        self.window_lost += 1;
+        self.end_window_if_full();
+    }
+
+    fn end_window_if_full(&mut self) {
+        let total = self.window_received + self.window_lost;
+        if total < WINDOW_SIZE {
+            return;
+        }
+
+        let loss_ratio = self.window_lost as f64 / total as f64;
+        self.window_received = 0;
+        self.window_lost = 0;
+
+        let old_rate = self.rate;
+        if loss_ratio > HIGH_LOSS_RATIO {
+            self.rate = u32::max(self.rate / 2, self.min_rate);
+        } else if loss_ratio < LOW_LOSS_RATIO {
+            let step = u32::max(1, self.max_rate / RATE_INCREASE_STEPS);
+            self.rate = u32::min(self.rate + step, self.max_rate);
+        }
+
+        if self.rate < old_rate {
+            info!(
+                self.logger,
+                "Backing off ping rate";
+                "loss_ratio" => loss_ratio,
+                "rate" => self.rate,
+                "timeout" => ?self.timeout(),
+            );
+        } else if self.rate > old_rate {
+            debug!(
+                self.logger,
+                "Raising ping rate";
+                "loss_ratio" => loss_ratio,
+                "rate" => self.rate,
+                "timeout" => ?self.timeout(),
+            );
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct RoundTripStats {
+    samples: Vec<Duration>,
+    retries: usize,
+    timeouts: usize,
+}
+
+impl RoundTripStats {
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty() && self.timeouts == 0
+    }
+
+    pub fn record_pong(&mut self, round_trip: Duration) {
+        self.samples.push(round_trip);
+    }
+
+    pub fn record_retry(&mut self) {
+        self.retries += 1;
+    }
+
+    pub fn record_timeout(&mut self) {
+        self.timeouts += 1;
+    }
+
+    pub fn log_and_reset(&mut self, logger: &Logger) {
+        self.samples.sort_unstable();
+        info!(
+            logger,
+            "Finished pinging servers";
+            "received" => self.samples.len(),
+            "retried" => self.retries,
+            "timed_out" => self.timeouts,
+            "p50" => ?self.percentile(50),
+            "p90" => ?self.percentile(90),
+            "p99" => ?self.percentile(99),
+        );
+        *self = Self::default();
+    }
+
+    fn percentile(&self, pct: usize) -> Option<Duration> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let idx = ((self.samples.len() * pct + 99) / 100).saturating_sub(1);
+        Some(self.samples[idx])
+    }
+}
+
+// Scales the delay by a random factor between 0.5 and 1.5, so that retries for servers that
+// timed out together don't all go out in the same burst.
+pub fn jitter(delay: Duration) -> Duration {
+    let random = RandomState::new().build_hasher().finish();
+    delay.mul_f64(0.5 + random as f64 / u64::MAX as f64)
+}
+
+const WINDOW_SIZE: usize = 50;
+const HIGH_LOSS_RATIO: f64 = 0.2;
+const LOW_LOSS_RATIO: f64 = 0.05;
+const MIN_RATE_DIVISOR: u32 = 16;
+const RATE_INCREASE_STEPS: u32 = 10;
+const MIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+    use std::num::NonZeroU32;
+    use std::time::Duration;
+
+    use slog::{o, Discard, Logger};
+
+    use super::{jitter, CongestionControl, PingSettings, RoundTripStats, WINDOW_SIZE};
+
+    fn congestion_control(rate_limit: u32) -> CongestionControl {
+        let settings = PingSettings {
+            rate_limit: NonZeroU32::new(rate_limit).unwrap(),
+            ..Default::default()
+        };
+        CongestionControl::new(Logger::root(Discard, o!()), &settings)
+    }
+
+    fn addr(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    #[test]
+    fn backs_off_on_loss_and_recovers() {
+        let mut congestion = congestion_control(160);
+        congestion.responsive.insert(addr(1));
+
+        for _ in 0..4 {
+            for _ in 0..WINDOW_SIZE {
+                congestion.on_loss(addr(1));
+            }
+        }
+        assert_eq!(congestion.rate().get(), 10);
+
+        for _ in 0..WINDOW_SIZE {
+            congestion.on_loss(addr(1));
+        }
+        assert_eq!(congestion.rate().get(), 10);
+
+        for _ in 0..WINDOW_SIZE {
+            congestion.on_pong(addr(1), Duration::from_millis(50));
+        }
+        assert_eq!(congestion.rate().get(), 26);
+    }
+
+    #[test]
+    fn ignores_loss_from_unresponsive_servers() {
+        let mut congestion = congestion_control(160);
+
+        for port in 0..(4 * WINDOW_SIZE as u16) {
+            congestion.on_loss(addr(port));
+        }
+        assert_eq!(congestion.rate().get(), 160);
+
+        congestion.on_pong(addr(1), Duration::from_millis(50));
+        for _ in 1..WINDOW_SIZE {
+            congestion.on_loss(addr(1));
+        }
+        assert_eq!(congestion.rate().get(), 80);
+    }
+
+    #[test]
+    fn timeout_follows_round_trip() {
+        let mut congestion = congestion_control(250);
+        assert_eq!(congestion.timeout(), Duration::from_secs(10));
+
+        for _ in 0..100 {
+            congestion.on_pong(addr(1), Duration::from_millis(100));
+        }
+        assert_eq!(congestion.timeout(), Duration::from_secs(1));
+
+        for _ in 0..100 {
+            congestion.on_pong(addr(1), Duration::from_secs(3));
+        }
+        assert!(congestion.timeout() >= Duration::from_millis(2900));
+        assert!(congestion.timeout() <= Duration::from_secs(10));
+    }
+
+    #[test]
+    fn computes_percentiles() {
+        let mut stats = RoundTripStats::default();
+        for ms in (1..=100).rev() {
+            stats.record_pong(Duration::from_millis(ms));
+        }
+        stats.samples.sort_unstable();
+        assert_eq!(stats.percentile(50), Some(Duration::from_millis(50)));
+        assert_eq!(stats.percentile(99), Some(Duration::from_millis(99)));
+        assert_eq!(RoundTripStats::default().percentile(50), None);
+    }
+
+    #[test]
+    fn jitter_stays_in_range() {
+        for _ in 0..100 {
+            let delay = jitter(Duration::from_secs(1));
+            assert!(delay >= Duration::from_millis(500));
+            assert!(delay <= Duration::from_millis(1500));
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::io::Result;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use slog::{debug, Logger};
+use tokio::net::UdpSocket;
+use tokio::task::JoinHandle;
+
+use crate::net::bind_udp_socket;
+
+#[derive(Clone, Debug)]
+pub struct FakePingConfig {
+    // If set, requests carrying a different build id are ignored.
+    pub build_id: Option<u32>,
+    pub players: i32,
+    pub age: Duration,
+    pub latency: Duration,
+    // Fraction of requests, between 0 and 1, that are dropped without a reply.
+    pub loss: f64,
+}
+
+impl Default for FakePingConfig {
+    fn default() -> Self {
+        Self {
+            build_id: None,
+            players: 0,
+            age: Duration::ZERO,
+            latency: Duration::ZERO,
+            loss: 0.0,
+        }
+    }
+}
+
+pub struct FakePingServer {
+    local_addr: SocketAddr,
+    requests_received: Arc<AtomicUsize>,
+    task: JoinHandle<()>,
+}
+
+impl FakePingServer {
+    pub fn bind(logger: Logger, addr: SocketAddr, config: FakePingConfig) -> Result<Self> {
+        let socket = {
+            let socket = bind_udp_socket(addr)?;
+            socket.set_nonblocking(true)?;
+            UdpSocket::from_std(socket)?
+        };
+        let local_addr = socket.local_addr()?;
+        let requests_received = Arc::new(AtomicUsize::new(0));
+        let responder = Responder {
+            logger,
+            socket: Arc::new(socket),
+            requests_received: Arc::clone(&requests_received),
+            loss_budget: 1.0 - config.loss,
+            config,
+        };
+        Ok(Self {
+            local_addr,
+            requests_received,
+            task: tokio::spawn(responder.run()),
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub fn requests_received(&self) -> usize {
+        self.requests_received.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for FakePingServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+struct Responder {
+    logger: Logger,
+    socket: Arc<UdpSocket>,
+    requests_received: Arc<AtomicUsize>,
+    loss_budget: f64,
+    config: FakePingConfig,
+}
+
+impl Responder {
+    async fn run(mut self) {
+        let mut buf = [0; 16];
+        loop {
+            let (size, addr) = match self.socket.recv_from(&mut buf).await {
+                Ok(result) => result,
+                Err(_) => continue,
+            };
+            if size != 4 {
+                continue;
+            }
+            let build_id = u32::from_be_bytes(buf[..4].try_into().unwrap());
+            if self
+                .config
+                .build_id
+                .map_or(false, |expected| expected != build_id)
+            {
+                debug!(self.logger, "Ignoring ping with wrong build id"; "build_id" => build_id);
+                continue;
+            }
+            self.requests_received.fetch_add(1, Ordering::SeqCst);
+            if self.should_drop() {
+                debug!(self.logger, "Dropping ping request"; "addr" => addr);
+                continue;
+            }
+
+            let reply = self.reply_packet();
+            let socket = Arc::clone(&self.socket);
+            let latency = self.config.latency;
+            tokio::spawn(async move {
+                tokio::time::sleep(latency).await;
+                let _ = socket.send_to(&reply, addr).await;
+            });
+        }
+    }
+
+    // Loss is applied deterministically, spreading the dropped requests evenly and always dropping
+    // the first one, so that tests can rely on the exact sequence of replies.
+    fn should_drop(&mut self) -> bool {
+        if self.config.loss <= 0.0 {
+            return false;
+        }
+        self.loss_budget += self.config.loss;
+        if self.loss_budget >= 1.0 {
+            self.loss_budget -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn reply_packet(&self) -> [u8; 16] {
+        let mut reply = [0; 16];
+        reply[..4].copy_from_slice(&self.config.players.to_le_bytes());
+        reply[8..].copy_from_slice(&self.config.age.as_secs().to_le_bytes());
+        reply
+    }
+}
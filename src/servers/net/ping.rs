@@ -1,32 +1,43 @@
 use std::collections::VecDeque;
 use std::io::Result;
 use std::net::SocketAddr;
+use std::num::NonZeroU32;
 use std::ops::Deref;
 use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::{Duration, Instant};
 
-use governor::clock::{QuantaClock, QuantaInstant};
+use governor::clock::{Clock, QuantaClock};
 use governor::middleware::NoOpMiddleware;
 use governor::state::{InMemoryState, NotKeyed};
 use governor::Quota;
 use linked_hash_map::{Entry, LinkedHashMap};
 use slog::{debug, info, warn, Logger};
 use tokio::net::UdpSocket;
+use tokio::sync::mpsc::unbounded_channel;
 use tokio::task::JoinHandle;
 use tokio::time::timeout;
 
-use crate::net::bind_udp_socket;
+use super::congestion::{jitter, CongestionControl, RoundTripStats};
+use crate::net::{bind_dual_stack_udp_socket, bind_udp_socket, canonical_socket_addr};
 use crate::servers::Server;
 
 #[derive(Debug)]
 pub struct PingRequest {
     server_idx: usize,
     pub addr: SocketAddr,
+    is_retry: bool,
 }
 
 impl PingRequest {
     pub fn for_server(server_idx: usize, server: &Server) -> Option<Self> {
-        server.ping_addr().map(|addr| Self { server_idx, addr })
+        if server.changes.removed {
+            return None;
+        }
+        server.ping_addr().map(|addr| Self {
+            server_idx,
+            addr,
+            is_retry: false,
+        })
     }
 }
 
@@ -46,6 +57,25 @@ pub enum PingResult {
     Timeout,
 }
 
+// The rate limit and timeout are upper bounds; the client lowers them to match the network
+// conditions it observes.
+#[derive(Clone, Copy, Debug)]
+pub struct PingSettings {
+    pub rate_limit: NonZeroU32,
+    pub timeout: Duration,
+    pub retry_delay: Duration,
+}
+
+impl Default for PingSettings {
+    fn default() -> Self {
+        Self {
+            rate_limit: NonZeroU32::new(250).unwrap(),
+            timeout: Duration::from_secs(10),
+            retry_delay: Duration::from_secs(1),
+        }
+    }
+}
+
 pub struct PingClient {
     client_impl: Arc<ClientImpl>,
 }
@@ -55,9 +85,18 @@ impl PingClient {
         logger: Logger,
         build_id: u32,
         on_response: impl Fn(PingResponse) + Send + 'static,
+    ) -> Result<Self> {
+        Self::with_settings(logger, build_id, PingSettings::default(), on_response)
+    }
+
+    pub fn with_settings(
+        logger: Logger,
+        build_id: u32,
+        settings: PingSettings,
+        on_response: impl Fn(PingResponse) + Send + 'static,
     ) -> Result<Self> {
         Ok(Self {
-            client_impl: ClientImpl::new(logger, build_id, on_response)?,
+            client_impl: ClientImpl::new(logger, build_id, settings, on_response)?,
         })
     }
 }
@@ -87,7 +126,10 @@ impl Drop for PingClient {
 pub struct ClientImpl {
     logger: Logger,
     build_id: u32,
+    settings: PingSettings,
     socket: UdpSocket,
+    dual_stack: bool,
+    congestion: Mutex<CongestionControl>,
     unsent: Mutex<UnsentRequests>,
     pending: Mutex<PendingRequests>,
 }
@@ -96,19 +138,33 @@ impl ClientImpl {
     fn new(
         logger: Logger,
         build_id: u32,
+        settings: PingSettings,
         on_response: impl Fn(PingResponse) + Send + 'static,
     ) -> Result<Arc<Self>> {
-        let bind_addr = SocketAddr::from(([0, 0, 0, 0], 0));
+        let socket = match bind_dual_stack_udp_socket(0) {
+            Ok(socket) => socket,
+            Err(err) => {
+                warn!(
+                    logger,
+                    "Failed to bind dual-stack ping socket, falling back to IPv4";
+                    "error" => %err,
+                );
+                bind_udp_socket(SocketAddr::from(([0, 0, 0, 0], 0)))?
+            }
+        };
+        let dual_stack = socket.local_addr()?.is_ipv6();
         let socket = {
-            let socket = bind_udp_socket(bind_addr)?;
             socket.set_nonblocking(true)?;
             UdpSocket::from_std(socket)?
         };
 
         let client = Arc::new(Self {
+            congestion: Mutex::new(CongestionControl::new(logger.clone(), &settings)),
             logger,
             build_id,
+            settings,
             socket,
+            dual_stack,
             unsent: Mutex::new(UnsentRequests::new()),
             pending: Mutex::new(PendingRequests::new()),
         });
@@ -148,6 +204,15 @@ impl ClientImpl {
         }
     }
 
+    fn socket_addr(&self, addr: SocketAddr) -> SocketAddr {
+        match addr {
+            SocketAddr::V4(v4) if self.dual_stack => {
+                SocketAddr::new(v4.ip().to_ipv6_mapped().into(), v4.port())
+            }
+            _ => addr,
+        }
+    }
+
     fn spawn_receiver(
         self: Arc<Self>,
         on_response: impl Fn(PingResponse) + Send + 'static,
@@ -162,8 +227,12 @@ impl ClientImpl {
     }
 }
 
-type RateLimiter =
-    governor::RateLimiter<NotKeyed, InMemoryState, QuantaClock, NoOpMiddleware<QuantaInstant>>;
+type RateLimiter<C = QuantaClock> =
+    governor::RateLimiter<NotKeyed, InMemoryState, C, NoOpMiddleware<<C as Clock>::Instant>>;
+
+fn rate_limiter<C: Clock>(rate: NonZeroU32, burst: NonZeroU32, clock: &C) -> RateLimiter<C> {
+    RateLimiter::<C>::direct_with_clock(Quota::per_second(rate).allow_burst(burst), clock)
+}
 
 struct PendingRequests {
     requests: LinkedHashMap<SocketAddr, PendingRequest>,
@@ -183,6 +252,7 @@ struct PendingRequest {
     idx: usize,
     sent_timestamp: Instant,
     should_retry: bool,
+    is_retry: bool,
 }
 
 struct UnsentRequests {
@@ -201,18 +271,21 @@ impl UnsentRequests {
 
 struct Sender {
     client: Arc<ClientImpl>,
+    rate: NonZeroU32,
     rate_limiter: RateLimiter,
 }
 
 impl Sender {
     fn new(client: Arc<ClientImpl>) -> Self {
+        let rate = client.congestion.lock().unwrap().rate();
         Self {
             client,
-            rate_limiter: RateLimiter::direct(Quota::per_second(250u32.try_into().unwrap())),
+            rate,
+            rate_limiter: rate_limiter(rate, rate, &QuantaClock::default()),
         }
     }
 
-    async fn run(self) {
+    async fn run(mut self) {
         let req_packet = self.client.build_id.to_be_bytes();
         let mut pings_sent = 0;
         loop {
@@ -241,8 +314,10 @@ impl Sender {
                     continue;
                 }
             }
+            self.adjust_rate();
             self.rate_limiter.until_ready().await;
-            if let Err(err) = self.client.socket.send_to(&req_packet, next.addr).await {
+            let target = self.client.socket_addr(next.addr);
+            if let Err(err) = self.client.socket.send_to(&req_packet, target).await {
                 warn!(self.client.logger, "Failed to send ping request"; "error" => err);
                 continue;
             }
@@ -257,17 +332,31 @@ impl Sender {
                         idx: next.server_idx,
                         sent_timestamp,
                         should_retry: false,
+                        is_retry: next.is_retry,
                     });
             }
         }
         info!(self.client.logger, "Finished sending {pings_sent} pings");
     }
+
+    fn adjust_rate(&mut self) {
+        let rate = self.client.congestion.lock().unwrap().rate();
+        if rate == self.rate {
+            return;
+        }
+        // A new rate limiter starts out with a full burst allowance, which would defeat the purpose
+        // of backing off, so only allow a single request to go out right away.
+        self.rate = rate;
+        self.rate_limiter =
+            rate_limiter(rate, NonZeroU32::new(1).unwrap(), &QuantaClock::default());
+    }
 }
 
 struct Receiver<F: Fn(PingResponse) + Send> {
     client: Arc<ClientImpl>,
     on_response: F,
-    max_time: Duration,
+    delayed_retries: Vec<(Instant, PingRequest)>,
+    stats: RoundTripStats,
 }
 
 impl<F: Fn(PingResponse) + Send> Receiver<F> {
@@ -275,24 +364,37 @@ impl<F: Fn(PingResponse) + Send> Receiver<F> {
         Self {
             client,
             on_response,
-            max_time: Duration::from_secs(10),
+            delayed_retries: Vec::new(),
+            stats: RoundTripStats::default(),
         }
     }
 
-    async fn run(self) {
-        let mut buf = [0; 16];
+    async fn run(mut self) {
+        let mut buf = [0; PONG_SIZE];
         loop {
-            let recv_result = timeout(self.max_time, self.client.socket.recv_from(&mut buf)).await;
+            let max_time = self.max_wait_time();
+            let recv_result = timeout(max_time, self.client.socket.recv_from(&mut buf)).await;
             if let Ok(Ok((size, addr))) = recv_result {
-                self.process_packet(&buf[..size], addr);
+                self.process_packet(&buf[..size], canonical_socket_addr(addr));
             }
             self.handle_timeouts();
+            self.send_delayed_retries();
+            self.log_stats_when_idle();
         }
     }
 
-    fn process_packet(&self, packet: &[u8], addr: SocketAddr) {
+    fn max_wait_time(&self) -> Duration {
+        let timeout = self.client.congestion.lock().unwrap().timeout();
+        let now = Instant::now();
+        self.delayed_retries
+            .iter()
+            .map(|(retry_at, _)| retry_at.saturating_duration_since(now))
+            .fold(timeout, Duration::min)
+    }
+
+    fn process_packet(&mut self, packet: &[u8], addr: SocketAddr) {
         let received_timestamp = Instant::now();
-        if packet.len() != 16 {
+        if packet.len() != PONG_SIZE {
             return;
         }
         let request = {
@@ -301,22 +403,31 @@ impl<F: Fn(PingResponse) + Send> Receiver<F> {
                 None => return,
             }
         };
-        let players = i32::max(0, i32::from_le_bytes(packet[..4].try_into().unwrap()));
-        let age = Duration::from_secs(u64::from_le_bytes(packet[8..].try_into().unwrap()));
+        let (connected_players, age) = parse_pong(packet);
+        let round_trip = received_timestamp - request.sent_timestamp;
+
+        self.client
+            .congestion
+            .lock()
+            .unwrap()
+            .on_pong(addr, round_trip);
+        self.stats.record_pong(round_trip);
 
         let response = PingResponse {
             server_idx: request.idx,
             result: PingResult::Pong {
-                connected_players: players as _,
+                connected_players,
                 age,
-                round_trip: received_timestamp - request.sent_timestamp,
+                round_trip,
             },
         };
         (self.on_response)(response);
     }
 
-    fn handle_timeouts(&self) {
-        let cutoff = Instant::now() - self.max_time;
+    fn handle_timeouts(&mut self) {
+        let now = Instant::now();
+        let mut congestion = self.client.congestion.lock().unwrap();
+        let cutoff = now - congestion.timeout();
         let mut retries = Vec::new();
         {
             let mut pending = self.client.pending.lock().unwrap();
@@ -324,23 +435,370 @@ impl<F: Fn(PingResponse) + Send> Receiver<F> {
                 if entry.get().sent_timestamp > cutoff {
                     break;
                 }
+                congestion.on_loss(*entry.key());
+                let retry = PingRequest {
+                    server_idx: entry.get().idx,
+                    addr: *entry.key(),
+                    is_retry: true,
+                };
                 if entry.get().should_retry {
-                    retries.push(PingRequest {
-                        server_idx: entry.get().idx,
-                        addr: *entry.key(),
-                    });
+                    retries.push(retry);
+                } else if !entry.get().is_retry {
+                    let retry_at = now + jitter(self.client.settings.retry_delay);
+                    self.delayed_retries.push((retry_at, retry));
+                    self.stats.record_retry();
                 } else {
                     let response = PingResponse {
                         server_idx: entry.get().idx,
                         result: PingResult::Timeout,
                     };
                     (self.on_response)(response);
+                    self.stats.record_timeout();
                 }
                 entry.remove();
             }
         }
+        drop(congestion);
+        if !retries.is_empty() {
+            self.client.send(retries);
+        }
+    }
+
+    fn send_delayed_retries(&mut self) {
+        let now = Instant::now();
+        let (due, delayed): (Vec<_>, Vec<_>) = std::mem::take(&mut self.delayed_retries)
+            .into_iter()
+            .partition(|(retry_at, _)| *retry_at <= now);
+        self.delayed_retries = delayed;
+        let retries: Vec<_> = due.into_iter().map(|(_, request)| request).collect();
         if !retries.is_empty() {
             self.client.send(retries);
         }
     }
+
+    fn log_stats_when_idle(&mut self) {
+        if self.stats.is_empty() || !self.delayed_retries.is_empty() {
+            return;
+        }
+        if !self.client.pending.lock().unwrap().requests.is_empty() {
+            return;
+        }
+        if !self.client.unsent.lock().unwrap().requests.is_empty() {
+            return;
+        }
+        self.stats.log_and_reset(&self.client.logger);
+    }
+}
+
+// Pings a single address, for servers that might not be in the server list at all. A client of its
+// own keeps the response from being mistaken for that of a listed server.
+pub async fn query_server(logger: Logger, build_id: u32, addr: SocketAddr) -> Result<PingResult> {
+    let (tx, mut rx) = unbounded_channel();
+    let settings = PingSettings {
+        timeout: QUERY_TIMEOUT,
+        ..Default::default()
+    };
+    let client = PingClient::with_settings(logger, build_id, settings, move |response| {
+        let _ = tx.send(response.result);
+    })?;
+    client.send([PingRequest {
+        server_idx: 0,
+        addr,
+        is_retry: false,
+    }]);
+    Ok(rx.recv().await.unwrap_or(PingResult::Timeout))
+}
+
+fn parse_pong(packet: &[u8]) -> (usize, Duration) {
+    let players = i32::max(0, i32::from_le_bytes(packet[..4].try_into().unwrap()));
+    let age = Duration::from_secs(u64::from_le_bytes(packet[8..16].try_into().unwrap()));
+    (players as _, age)
+}
+
+const PONG_SIZE: usize = 16;
+const QUERY_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+    use std::num::NonZeroU32;
+    use std::time::Duration;
+
+    use governor::clock::FakeRelativeClock;
+    use slog::{o, Discard, Logger};
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+    use super::{
+        query_server, rate_limiter, PingClient, PingRequest, PingResponse, PingResult, PingSettings,
+    };
+    use crate::servers::{FakePingConfig, FakePingServer};
+
+    const BUILD_ID: u32 = 123456;
+
+    fn logger() -> Logger {
+        Logger::root(Discard, o!())
+    }
+
+    fn fake_server(config: FakePingConfig) -> FakePingServer {
+        FakePingServer::bind(logger(), SocketAddr::from(([127, 0, 0, 1], 0)), config).unwrap()
+    }
+
+    fn client(settings: PingSettings) -> (PingClient, UnboundedReceiver<PingResponse>) {
+        let (tx, rx) = unbounded_channel();
+        let client = PingClient::with_settings(logger(), BUILD_ID, settings, move |response| {
+            let _ = tx.send(response);
+        })
+        .unwrap();
+        (client, rx)
+    }
+
+    fn request(server_idx: usize, server: &FakePingServer) -> PingRequest {
+        PingRequest {
+            server_idx,
+            addr: server.local_addr(),
+            is_retry: false,
+        }
+    }
+
+    fn short_timeout() -> PingSettings {
+        PingSettings {
+            timeout: Duration::from_millis(200),
+            retry_delay: Duration::from_millis(100),
+            ..Default::default()
+        }
+    }
+
+    async fn next_response(
+        rx: &mut UnboundedReceiver<PingResponse>,
+        wait: Duration,
+    ) -> Option<PingResponse> {
+        tokio::time::timeout(wait, rx.recv()).await.ok().flatten()
+    }
+
+    #[tokio::test]
+    async fn decodes_pong() {
+        let server = fake_server(FakePingConfig {
+            build_id: Some(BUILD_ID),
+            players: 17,
+            age: Duration::from_secs(3 * 86400),
+            latency: Duration::from_millis(50),
+            ..Default::default()
+        });
+        let (client, mut rx) = client(PingSettings::default());
+
+        client.send([request(7, &server)]);
+
+        let response = next_response(&mut rx, Duration::from_secs(2))
+            .await
+            .unwrap();
+        assert_eq!(response.server_idx, 7);
+        match response.result {
+            PingResult::Pong {
+                connected_players,
+                age,
+                round_trip,
+            } => {
+                assert_eq!(connected_players, 17);
+                assert_eq!(age, Duration::from_secs(3 * 86400));
+                assert!(round_trip >= Duration::from_millis(50));
+            }
+            PingResult::Timeout => panic!("unexpected timeout"),
+        }
+    }
+
+    #[tokio::test]
+    async fn clamps_negative_player_count() {
+        let server = fake_server(FakePingConfig {
+            players: -5,
+            ..Default::default()
+        });
+        let (client, mut rx) = client(PingSettings::default());
+
+        client.send([request(0, &server)]);
+
+        let response = next_response(&mut rx, Duration::from_secs(2))
+            .await
+            .unwrap();
+        assert!(matches!(
+            response.result,
+            PingResult::Pong {
+                connected_players: 0,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn reports_timeout_after_single_retry() {
+        let server = fake_server(FakePingConfig {
+            loss: 1.0,
+            ..Default::default()
+        });
+        let (client, mut rx) = client(short_timeout());
+
+        client.send([request(3, &server)]);
+
+        let response = next_response(&mut rx, Duration::from_secs(2))
+            .await
+            .unwrap();
+        assert_eq!(response.server_idx, 3);
+        assert!(matches!(response.result, PingResult::Timeout));
+        assert_eq!(server.requests_received(), 2);
+    }
+
+    #[tokio::test]
+    async fn retries_timed_out_request() {
+        let server = fake_server(FakePingConfig {
+            loss: 0.5,
+            ..Default::default()
+        });
+        let (client, mut rx) = client(short_timeout());
+
+        client.send([request(4, &server)]);
+
+        let response = next_response(&mut rx, Duration::from_secs(2))
+            .await
+            .unwrap();
+        assert_eq!(response.server_idx, 4);
+        assert!(matches!(response.result, PingResult::Pong { .. }));
+        assert_eq!(server.requests_received(), 2);
+    }
+
+    #[tokio::test]
+    async fn retries_repeated_request_instead_of_timing_out() {
+        // Drops every other request, starting with the first one.
+        let server = fake_server(FakePingConfig {
+            loss: 0.5,
+            ..Default::default()
+        });
+        let (client, mut rx) = client(short_timeout());
+
+        client.send([request(1, &server), request(1, &server)]);
+
+        let response = next_response(&mut rx, Duration::from_secs(2))
+            .await
+            .unwrap();
+        assert_eq!(response.server_idx, 1);
+        assert!(matches!(response.result, PingResult::Pong { .. }));
+        assert_eq!(server.requests_received(), 2);
+        assert!(next_response(&mut rx, Duration::from_millis(500))
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn discards_request_for_duplicate_address() {
+        let server = fake_server(FakePingConfig {
+            latency: Duration::from_millis(100),
+            ..Default::default()
+        });
+        let (client, mut rx) = client(short_timeout());
+
+        client.send([request(1, &server), request(2, &server)]);
+
+        let response = next_response(&mut rx, Duration::from_secs(2))
+            .await
+            .unwrap();
+        assert_eq!(response.server_idx, 1);
+        assert!(next_response(&mut rx, Duration::from_millis(500))
+            .await
+            .is_none());
+        assert_eq!(server.requests_received(), 1);
+    }
+
+    #[tokio::test]
+    async fn unreachable_server_does_not_block_others() {
+        let server = fake_server(FakePingConfig::default());
+        let (client, mut rx) = client(short_timeout());
+
+        // Nothing is listening on this address once the fake server has been dropped.
+        let other_addr = fake_server(FakePingConfig::default()).local_addr();
+        client.send([PingRequest {
+            server_idx: 0,
+            addr: other_addr,
+            is_retry: false,
+        }]);
+        client.send([request(1, &server)]);
+
+        let mut responses = Vec::new();
+        while let Some(response) = next_response(&mut rx, Duration::from_secs(1)).await {
+            responses.push(response.server_idx);
+        }
+        responses.sort();
+        assert_eq!(responses, vec![0, 1]);
+    }
+
+    #[tokio::test]
+    async fn respects_rate_limit() {
+        const RATE: u32 = 10;
+        const COUNT: usize = 2 * RATE as usize;
+
+        let servers: Vec<FakePingServer> = (0..COUNT)
+            .map(|_| fake_server(FakePingConfig::default()))
+            .collect();
+        let (client, mut rx) = client(PingSettings {
+            rate_limit: NonZeroU32::new(RATE).unwrap(),
+            ..Default::default()
+        });
+
+        client.send(
+            servers
+                .iter()
+                .enumerate()
+                .map(|(idx, server)| request(idx, server)),
+        );
+        for _ in 0..COUNT {
+            let response = next_response(&mut rx, Duration::from_secs(5))
+                .await
+                .unwrap();
+            assert!(matches!(response.result, PingResult::Pong { .. }));
+        }
+        assert!(servers.iter().all(|server| server.requests_received() == 1));
+    }
+
+    #[test]
+    fn schedules_requests_at_rate() {
+        let clock = FakeRelativeClock::default();
+        let rate = NonZeroU32::new(10).unwrap();
+
+        // The first requests go out as a burst, then one every 100ms.
+        let limiter = rate_limiter(rate, rate, &clock);
+        for _ in 0..rate.get() {
+            assert!(limiter.check().is_ok());
+        }
+        assert!(limiter.check().is_err());
+        clock.advance(Duration::from_millis(50));
+        assert!(limiter.check().is_err());
+        clock.advance(Duration::from_millis(100));
+        assert!(limiter.check().is_ok());
+        assert!(limiter.check().is_err());
+
+        // After the rate changes, only a single request goes out right away.
+        let limiter = rate_limiter(rate, NonZeroU32::new(1).unwrap(), &clock);
+        assert!(limiter.check().is_ok());
+        assert!(limiter.check().is_err());
+        clock.advance(Duration::from_millis(150));
+        assert!(limiter.check().is_ok());
+    }
+
+    #[tokio::test]
+    async fn queries_single_server() {
+        let server = fake_server(FakePingConfig {
+            build_id: Some(BUILD_ID),
+            players: 3,
+            ..Default::default()
+        });
+
+        let result = query_server(logger(), BUILD_ID, server.local_addr())
+            .await
+            .unwrap();
+        assert!(matches!(
+            result,
+            PingResult::Pong {
+                connected_players: 3,
+                ..
+            }
+        ));
+        assert_eq!(server.requests_received(), 1);
+    }
 }
@@ -0,0 +1,673 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt::{Debug, Display};
+
+use nom::branch::alt;
+use nom::bytes::complete::{tag, tag_no_case, take_while, take_while1};
+use nom::character::complete::{char, multispace0, satisfy};
+use nom::combinator::{all_consuming, cut, map, not, opt, recognize, value};
+use nom::multi::many0;
+use nom::sequence::{delimited, pair, preceded, terminated};
+use nom::IResult;
+
+use crate::parser_utils::{parse_quoted, ParserError};
+
+use super::{RaidHours, Server, Weekday};
+
+#[derive(Clone, Debug)]
+pub struct Query {
+    root: Node,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Self, QueryError> {
+        let (_, expr) = all_consuming(terminated(parse_or, multispace0))(input)
+            .map_err(|err| QueryError::Syntax(ParserError::from_err(input, err)))?;
+        Ok(Self {
+            root: compile(expr)?,
+        })
+    }
+
+    pub fn matches(&self, server: &Server) -> bool {
+        self.root.matches(server)
+    }
+}
+
+#[derive(Debug)]
+pub enum QueryError {
+    Syntax(ParserError),
+    UnknownField(String),
+    InvalidOperator(String, Op),
+    MissingOperator(String),
+    InvalidOperand(String, String),
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Syntax(err) => write!(f, "Syntax error at position {}", err.offset() + 1),
+            Self::UnknownField(field) => write!(f, "Unknown field '{}'", field),
+            Self::InvalidOperator(field, op) => {
+                write!(f, "Operator '{}' cannot be used with '{}'", op, field)
+            }
+            Self::MissingOperator(field) => write!(f, "'{}' must be compared to a value", field),
+            Self::InvalidOperand(field, operand) => {
+                write!(f, "'{}' is not a valid value for '{}'", operand, field)
+            }
+        }
+    }
+}
+
+impl Error for QueryError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+    Overlaps,
+}
+
+impl Display for Op {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Eq => "=",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Contains => "contains",
+            Self::Overlaps => "overlaps",
+        })
+    }
+}
+
+enum Expr<'i> {
+    And(Box<Expr<'i>>, Box<Expr<'i>>),
+    Or(Box<Expr<'i>>, Box<Expr<'i>>),
+    Not(Box<Expr<'i>>),
+    Test(&'i str, Option<(Op, String)>),
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Not(Box<Node>),
+    Test(&'static Field, Predicate),
+}
+
+impl Node {
+    fn matches(&self, server: &Server) -> bool {
+        match self {
+            Self::And(lhs, rhs) => lhs.matches(server) && rhs.matches(server),
+            Self::Or(lhs, rhs) => lhs.matches(server) || rhs.matches(server),
+            Self::Not(node) => !node.matches(server),
+            Self::Test(field, predicate) => predicate.matches((field.value)(server)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Predicate {
+    Compare(Op, f64),
+    Equals(bool, String),
+    Contains(String),
+    Is(bool),
+    Overlaps(u16, u16),
+    Present,
+}
+
+impl Predicate {
+    fn matches(&self, value: Value) -> bool {
+        match (self, value) {
+            (Self::Compare(op, rhs), Value::Number(Some(lhs))) => compare(*op, lhs, *rhs),
+            (Self::Compare(op, rhs), Value::Mods(server)) => {
+                compare(*op, server.mod_entries().count() as f64, *rhs)
+            }
+            (Self::Equals(equal, rhs), Value::Text(lhs)) => (lhs.to_lowercase() == *rhs) == *equal,
+            (Self::Equals(equal, rhs), Value::Keyword(lhs)) => {
+                (normalize_keyword(&lhs) == *rhs) == *equal
+            }
+            (Self::Contains(needle), Value::Text(text)) => text.to_lowercase().contains(needle),
+            (Self::Contains(needle), Value::Mods(server)) => server
+                .mod_entries()
+                .any(|entry| entry.eq_ignore_ascii_case(needle)),
+            (Self::Is(expected), Value::Bool(value)) => value == *expected,
+            (Self::Overlaps(start, end), Value::RaidHours(hours, day)) => raid_days(hours, day)
+                .any(|(raid_start, raid_end)| overlaps(raid_start, raid_end, *start, *end)),
+            (Self::Present, Value::Mods(server)) => server.is_modded(),
+            (Self::Present, Value::RaidHours(hours, day)) => raid_days(hours, day).next().is_some(),
+            _ => false,
+        }
+    }
+}
+
+enum Value<'s> {
+    Number(Option<f64>),
+    Text(Cow<'s, str>),
+    Keyword(Cow<'s, str>),
+    Bool(bool),
+    Mods(&'s Server),
+    RaidHours(&'s RaidHours, Option<Weekday>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FieldKind {
+    Number,
+    Text,
+    Keyword,
+    Bool,
+    Mods,
+    RaidHours,
+}
+
+struct Field {
+    names: &'static [&'static str],
+    kind: FieldKind,
+    value: for<'s> fn(&'s Server) -> Value<'s>,
+}
+
+impl Debug for Field {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.names[0])
+    }
+}
+
+macro_rules! field {
+    ([$($name:literal),+], $kind:ident, $value:expr) => {
+        Field {
+            names: &[$($name),+],
+            kind: FieldKind::$kind,
+            value: $value,
+        }
+    };
+}
+
+static FIELDS: &[Field] = &[
+    field!(["name"], Text, |s| Value::Text(Cow::Borrowed(&s.name))),
+    field!(["map"], Text, |s| Value::Text(Cow::Borrowed(&s.map))),
+    field!(["id"], Text, |s| Value::Text(Cow::Borrowed(&s.id))),
+    field!(["host"], Text, |s| Value::Text(Cow::Owned(s.host()))),
+    field!(["ip"], Text, |s| Value::Text(Cow::Owned(
+        s.ip().to_string()
+    ))),
+    field!(["port"], Number, |s| Value::Number(Some(s.port as _))),
+    field!(["mode"], Keyword, |s| Value::Keyword(Cow::Owned(
+        s.mode().as_ref().to_string()
+    ))),
+    field!(["region"], Keyword, |s| Value::Keyword(Cow::Borrowed(
+        s.region.as_ref()
+    ))),
+    field!(["effective_region"], Keyword, |s| Value::Keyword(
+        Cow::Borrowed(s.effective_region().as_ref())
+    )),
+    field!(["community"], Keyword, |s| Value::Keyword(Cow::Borrowed(
+        s.community.as_ref()
+    ))),
+    field!(["players", "connected_players"], Number, |s| {
+        Value::Number(s.connected_players.map(|players| players as _))
+    }),
+    field!(["max_players"], Number, |s| Value::Number(Some(
+        s.max_players as _
+    ))),
+    field!(["age"], Number, |s| Value::Number(
+        s.age.map(|age| (age.as_secs() / 86400) as _)
+    )),
+    field!(["ping"], Number, |s| Value::Number(
+        s.ping.map(|ping| ping.as_millis() as _)
+    )),
+    field!(["build_id"], Number, |s| Value::Number(Some(
+        s.build_id as _
+    ))),
+    field!(["max_ping"], Number, |s| Value::Number(
+        s.max_ping.map(|ping| ping as _)
+    )),
+    field!(["max_clan_size"], Number, |s| Value::Number(
+        s.max_clan_size.map(|size| size as _)
+    )),
+    field!(["password", "password_protected"], Bool, |s| Value::Bool(
+        s.password_protected
+    )),
+    field!(["battleye", "battleye_required"], Bool, |s| Value::Bool(
+        s.battleye_required
+    )),
+    field!(["official"], Bool, |s| Value::Bool(s.is_official())),
+    field!(["modded"], Bool, |s| Value::Bool(s.is_modded())),
+    field!(["pvp", "pvp_enabled"], Bool, |s| Value::Bool(s.pvp_enabled)),
+    field!(["favorite"], Bool, |s| Value::Bool(s.favorite)),
+    field!(["new"], Bool, |s| Value::Bool(s.changes.added)),
+    field!(["removed"], Bool, |s| Value::Bool(s.changes.removed)),
+    field!(["changed"], Bool, |s| Value::Bool(!s.changes.is_empty())),
+    field!(["valid"], Bool, |s| Value::Bool(s.is_valid())),
+    field!(["full"], Bool, |s| Value::Bool(s.is_full())),
+    field!(["xp_rate", "xp_rate_mult"], Number, |s| Value::Number(
+        Some(s.xp_rate_mult.value())
+    )),
+    field!(
+        ["day_cycle_speed", "daylight.day_cycle_speed"],
+        Number,
+        |s| { Value::Number(Some(s.daylight.day_cycle_speed_mult.value())) }
+    ),
+    field!(
+        ["dawn_dusk_speed", "daylight.dawn_dusk_speed"],
+        Number,
+        |s| { Value::Number(Some(s.daylight.dawn_dusk_speed_mult.value())) }
+    ),
+    field!(["catch_up_time", "daylight.use_catch_up_time"], Bool, |s| {
+        Value::Bool(s.daylight.use_catch_up_time)
+    }),
+    field!(["stamina_cost", "survival.stamina_cost"], Number, |s| {
+        Value::Number(Some(s.survival.stamina_cost_mult.value()))
+    }),
+    field!(["idle_thirst", "survival.idle_thirst"], Number, |s| {
+        Value::Number(Some(s.survival.idle_thirst_mult.value()))
+    }),
+    field!(["active_thirst", "survival.active_thirst"], Number, |s| {
+        Value::Number(Some(s.survival.active_thirst_mult.value()))
+    }),
+    field!(["idle_hunger", "survival.idle_hunger"], Number, |s| {
+        Value::Number(Some(s.survival.idle_hunger_mult.value()))
+    }),
+    field!(["active_hunger", "survival.active_hunger"], Number, |s| {
+        Value::Number(Some(s.survival.active_hunger_mult.value()))
+    }),
+    field!(
+        ["drop_on_death", "survival.drop_items_on_death"],
+        Keyword,
+        |s| { Value::Keyword(Cow::Borrowed(s.survival.drop_items_on_death.as_ref())) }
+    ),
+    field!(
+        ["corpse_lootable", "survival.anyone_can_loot_corpse"],
+        Bool,
+        |s| { Value::Bool(s.survival.anyone_can_loot_corpse) }
+    ),
+    field!(
+        ["offline_chars", "survival.offline_chars_in_world"],
+        Bool,
+        |s| { Value::Bool(s.survival.offline_chars_in_world) }
+    ),
+    field!(["durability", "combat.durability"], Number, |s| {
+        Value::Number(Some(s.combat.durability_mult.value()))
+    }),
+    field!(
+        ["thrall_wakeup_time", "combat.thrall_wakeup_time"],
+        Number,
+        |s| { Value::Number(Some(s.combat.thrall_wakeup_time_secs())) }
+    ),
+    field!(
+        ["harvest_amount", "harvesting.harvest_amount"],
+        Number,
+        |s| { Value::Number(Some(s.harvesting.harvest_amount_mult.value())) }
+    ),
+    field!(
+        ["item_spoil_rate", "harvesting.item_spoil_rate"],
+        Number,
+        |s| { Value::Number(Some(s.harvesting.item_spoil_rate_mult.value())) }
+    ),
+    field!(
+        ["rsrc_respawn_speed", "harvesting.rsrc_respawn_speed"],
+        Number,
+        |s| { Value::Number(Some(s.harvesting.rsrc_respawn_speed_mult.value())) }
+    ),
+    field!(["crafting_time", "crafting.crafting_time"], Number, |s| {
+        Value::Number(Some(s.crafting.crafting_time_mult.value()))
+    }),
+    field!(
+        ["thrall_crafting_time", "crafting.thrall_crafting_time"],
+        Number,
+        |s| { Value::Number(Some(s.crafting.thrall_crafting_time_mult.value())) }
+    ),
+    field!(["mods"], Mods, |s| Value::Mods(s)),
+    field!(["raid_hours"], RaidHours, |s| Value::RaidHours(
+        &s.raid_hours,
+        None
+    )),
+    field!(["raid_hours.mon"], RaidHours, |s| Value::RaidHours(
+        &s.raid_hours,
+        Some(Weekday::Mon)
+    )),
+    field!(["raid_hours.tue"], RaidHours, |s| Value::RaidHours(
+        &s.raid_hours,
+        Some(Weekday::Tue)
+    )),
+    field!(["raid_hours.wed"], RaidHours, |s| Value::RaidHours(
+        &s.raid_hours,
+        Some(Weekday::Wed)
+    )),
+    field!(["raid_hours.thu"], RaidHours, |s| Value::RaidHours(
+        &s.raid_hours,
+        Some(Weekday::Thu)
+    )),
+    field!(["raid_hours.fri"], RaidHours, |s| Value::RaidHours(
+        &s.raid_hours,
+        Some(Weekday::Fri)
+    )),
+    field!(["raid_hours.sat"], RaidHours, |s| Value::RaidHours(
+        &s.raid_hours,
+        Some(Weekday::Sat)
+    )),
+    field!(["raid_hours.sun"], RaidHours, |s| Value::RaidHours(
+        &s.raid_hours,
+        Some(Weekday::Sun)
+    )),
+];
+
+fn compile(expr: Expr) -> Result<Node, QueryError> {
+    Ok(match expr {
+        Expr::And(lhs, rhs) => Node::And(Box::new(compile(*lhs)?), Box::new(compile(*rhs)?)),
+        Expr::Or(lhs, rhs) => Node::Or(Box::new(compile(*lhs)?), Box::new(compile(*rhs)?)),
+        Expr::Not(expr) => Node::Not(Box::new(compile(*expr)?)),
+        Expr::Test(name, comparison) => {
+            let field = FIELDS
+                .iter()
+                .find(|field| field.names.iter().any(|n| n.eq_ignore_ascii_case(name)))
+                .ok_or_else(|| QueryError::UnknownField(name.to_string()))?;
+            Node::Test(field, compile_predicate(name, field.kind, comparison)?)
+        }
+    })
+}
+
+fn compile_predicate(
+    name: &str,
+    kind: FieldKind,
+    comparison: Option<(Op, String)>,
+) -> Result<Predicate, QueryError> {
+    let invalid_operand =
+        |operand: &str| QueryError::InvalidOperand(name.to_string(), operand.to_string());
+
+    let (op, operand) = match comparison {
+        Some(comparison) => comparison,
+        None => {
+            return match kind {
+                FieldKind::Bool => Ok(Predicate::Is(true)),
+                FieldKind::Mods | FieldKind::RaidHours => Ok(Predicate::Present),
+                _ => Err(QueryError::MissingOperator(name.to_string())),
+            }
+        }
+    };
+
+    match (kind, op) {
+        (
+            FieldKind::Number | FieldKind::Mods,
+            Op::Eq | Op::Ne | Op::Lt | Op::Le | Op::Gt | Op::Ge,
+        ) => {
+            let number = operand.parse().map_err(|_| invalid_operand(&operand))?;
+            Ok(Predicate::Compare(op, number))
+        }
+        (FieldKind::Text, Op::Eq | Op::Ne) => {
+            Ok(Predicate::Equals(op == Op::Eq, operand.to_lowercase()))
+        }
+        (FieldKind::Keyword, Op::Eq | Op::Ne) => {
+            Ok(Predicate::Equals(op == Op::Eq, normalize_keyword(&operand)))
+        }
+        (FieldKind::Text, Op::Contains) => Ok(Predicate::Contains(operand.to_lowercase())),
+        (FieldKind::Mods, Op::Contains) => Ok(Predicate::Contains(operand)),
+        (FieldKind::Bool, Op::Eq | Op::Ne) => {
+            let value = parse_bool(&operand).ok_or_else(|| invalid_operand(&operand))?;
+            Ok(Predicate::Is(value == (op == Op::Eq)))
+        }
+        (FieldKind::RaidHours, Op::Overlaps) => {
+            let (start, end) =
+                parse_time_range(&operand).ok_or_else(|| invalid_operand(&operand))?;
+            Ok(Predicate::Overlaps(start, end))
+        }
+        _ => Err(QueryError::InvalidOperator(name.to_string(), op)),
+    }
+}
+
+fn parse_or(input: &str) -> IResult<&str, Expr> {
+    let (input, first) = parse_and(input)?;
+    let (input, rest) = many0(preceded(keyword("or"), parse_and))(input)?;
+    Ok((
+        input,
+        rest.into_iter()
+            .fold(first, |lhs, rhs| Expr::Or(Box::new(lhs), Box::new(rhs))),
+    ))
+}
+
+fn parse_and(input: &str) -> IResult<&str, Expr> {
+    let (input, first) = parse_unary(input)?;
+    let (input, rest) = many0(preceded(keyword("and"), parse_unary))(input)?;
+    Ok((
+        input,
+        rest.into_iter()
+            .fold(first, |lhs, rhs| Expr::And(Box::new(lhs), Box::new(rhs))),
+    ))
+}
+
+fn parse_unary(input: &str) -> IResult<&str, Expr> {
+    alt((
+        map(preceded(keyword("not"), parse_unary), |expr| {
+            Expr::Not(Box::new(expr))
+        }),
+        delimited(
+            preceded(multispace0, char('(')),
+            parse_or,
+            cut(preceded(multispace0, char(')'))),
+        ),
+        parse_test,
+    ))(input)
+}
+
+fn parse_test(input: &str) -> IResult<&str, Expr> {
+    let (input, field) = preceded(multispace0, parse_field)(input)?;
+    let (input, comparison) = opt(pair(
+        preceded(multispace0, parse_op),
+        cut(preceded(multispace0, parse_operand)),
+    ))(input)?;
+    Ok((input, Expr::Test(field, comparison)))
+}
+
+fn parse_field(input: &str) -> IResult<&str, &str> {
+    recognize(pair(
+        satisfy(|c| c.is_ascii_alphabetic() || c == '_'),
+        take_while(|c| is_ident_char(c) || c == '.'),
+    ))(input)
+}
+
+fn parse_op(input: &str) -> IResult<&str, Op> {
+    alt((
+        value(Op::Ge, tag(">=")),
+        value(Op::Le, tag("<=")),
+        value(Op::Ne, tag("!=")),
+        value(Op::Eq, tag("==")),
+        value(Op::Eq, tag("=")),
+        value(Op::Gt, tag(">")),
+        value(Op::Lt, tag("<")),
+        value(Op::Contains, word("contains")),
+        value(Op::Overlaps, word("overlaps")),
+    ))(input)
+}
+
+fn parse_operand(input: &str) -> IResult<&str, String> {
+    alt((
+        parse_quoted,
+        map(
+            take_while1(|c: char| !c.is_whitespace() && c != '(' && c != ')'),
+            str::to_string,
+        ),
+    ))(input)
+}
+
+fn keyword<'i>(kw: &'static str) -> impl FnMut(&'i str) -> IResult<&'i str, &'i str> {
+    preceded(multispace0, word(kw))
+}
+
+fn word<'i>(text: &'static str) -> impl FnMut(&'i str) -> IResult<&'i str, &'i str> {
+    terminated(tag_no_case(text), not(satisfy(is_ident_char)))
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn parse_bool(text: &str) -> Option<bool> {
+    match text.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "1" => Some(true),
+        "false" | "no" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_time_range(text: &str) -> Option<(u16, u16)> {
+    let (start, end) = text.split_once('-')?;
+    Some((parse_time(start)?, parse_time(end)?))
+}
+
+fn parse_time(text: &str) -> Option<u16> {
+    let (hours, minutes) = text.split_once(':').unwrap_or((text, "0"));
+    let hours: u16 = hours.parse().ok()?;
+    let minutes: u16 = minutes.parse().ok()?;
+    if hours <= 24 && minutes < 60 {
+        Some(hours * 60 + minutes)
+    } else {
+        None
+    }
+}
+
+fn normalize_keyword(text: &str) -> String {
+    text.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn compare(op: Op, lhs: f64, rhs: f64) -> bool {
+    match op {
+        Op::Eq => lhs == rhs,
+        Op::Ne => lhs != rhs,
+        Op::Lt => lhs < rhs,
+        Op::Le => lhs <= rhs,
+        Op::Gt => lhs > rhs,
+        Op::Ge => lhs >= rhs,
+        _ => false,
+    }
+}
+
+fn raid_days(hours: &RaidHours, day: Option<Weekday>) -> impl Iterator<Item = (u16, u16)> + '_ {
+    use strum::IntoEnumIterator;
+
+    Weekday::iter()
+        .filter(move |d| day.map_or(true, |day| day == *d))
+        .filter_map(|day| hours.get(day))
+        .map(|(start, end)| (start.minutes_of_day(), end.minutes_of_day()))
+}
+
+fn overlaps(start: u16, end: u16, range_start: u16, range_end: u16) -> bool {
+    const DAY: i32 = 24 * 60;
+
+    // Windows ending at or before their start time run past midnight.
+    let (start, end) = unwrap_window(start, end);
+    let (range_start, range_end) = unwrap_window(range_start, range_end);
+    [-DAY, 0, DAY]
+        .iter()
+        .any(|shift| start < range_end + shift && range_start + shift < end)
+}
+
+fn unwrap_window(start: u16, end: u16) -> (i32, i32) {
+    let (start, end) = (start as i32, end as i32);
+    if end <= start {
+        (start, end + 24 * 60)
+    } else {
+        (start, end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::super::model::test_server;
+    use super::{overlaps, Query};
+
+    fn parse_error(input: &str) -> String {
+        Query::parse(input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn example_expression() {
+        let query = Query::parse(
+            "players >= 10 and xp_rate > 2 and raid_hours.sat overlaps 18:00-22:00 \
+            and not mods contains 880454836",
+        )
+        .unwrap();
+
+        let raids = json!({ "Sz": 3.0, "S97": 1900, "S104": 2300, "S111": true });
+        let mut server = test_server(raids.clone());
+        server.connected_players = Some(12);
+        assert!(query.matches(&server));
+
+        server.connected_players = Some(5);
+        assert!(!query.matches(&server));
+
+        let mut modded = raids;
+        modded["S17"] = json!("2:0\n1113901982\n880454836\n");
+        let mut server = test_server(modded);
+        server.connected_players = Some(12);
+        assert!(!query.matches(&server));
+    }
+
+    #[test]
+    fn precedence_and_parentheses() {
+        // Official, without PvP and not a favorite.
+        let server = test_server(json!({ "CSF": 1 }));
+
+        assert!(Query::parse("official or pvp and favorite")
+            .unwrap()
+            .matches(&server));
+        assert!(!Query::parse("(official or pvp) and favorite")
+            .unwrap()
+            .matches(&server));
+        assert!(!Query::parse("not official and pvp")
+            .unwrap()
+            .matches(&server));
+        assert!(Query::parse("not (official and pvp)")
+            .unwrap()
+            .matches(&server));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse_error("foo > 1"), "Unknown field 'foo'");
+        assert_eq!(
+            parse_error("name > 3"),
+            "Operator '>' cannot be used with 'name'"
+        );
+        assert_eq!(
+            parse_error("players >= many"),
+            "'many' is not a valid value for 'players'"
+        );
+        assert_eq!(
+            parse_error("players"),
+            "'players' must be compared to a value"
+        );
+        assert_eq!(
+            parse_error("raid_hours.sat overlaps 18:00"),
+            "'18:00' is not a valid value for 'raid_hours.sat'"
+        );
+        assert!(parse_error("players >= 10 and").starts_with("Syntax error"));
+        assert!(parse_error("(players >= 10").starts_with("Syntax error"));
+    }
+
+    #[test]
+    fn raid_hours_past_midnight() {
+        let server = test_server(json!({ "S97": 2200, "S104": 200, "S111": true }));
+        let matches = |query: &str| Query::parse(query).unwrap().matches(&server);
+
+        assert!(matches("raid_hours.sat overlaps 01:00-03:00"));
+        assert!(matches("raid_hours.sat overlaps 21:00-22:30"));
+        assert!(matches("raid_hours overlaps 23:30-00:30"));
+        assert!(!matches("raid_hours.sat overlaps 03:00-05:00"));
+        assert!(!matches("raid_hours.sun overlaps 01:00-03:00"));
+
+        assert!(overlaps(0, 60, 23 * 60 + 30, 30));
+        assert!(!overlaps(60, 120, 23 * 60, 30));
+        assert!(!overlaps(22 * 60, 2 * 60, 2 * 60, 3 * 60));
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::Duration;
+
+use strum::IntoEnumIterator;
+
+use super::{Ownership, Region, Server};
+
+// Private servers often declare the wrong region. The region they are actually in is inferred
+// from two sources, both anchored on official servers, whose region can be trusted:
+//
+// - Official servers in the same subnet. Hosting providers allocate address blocks per data
+//   center, so a server sharing a /24 (or /48 for IPv6) with an official one is very likely in the
+//   same region. Failing that, the region most official servers in the /16 (or /32) are in is used.
+// - Round trip times. The pings of official servers tell what the typical round trip to each region
+//   is from where the user is. If a server's round trip is way off for its declared region, but
+//   close to that of another region, it's probably in that other region. Unlike the subnets, which
+//   are rebuilt from every server list, the round trips keep accumulating across refreshes.
+#[derive(Default)]
+pub struct RegionInference {
+    by_subnet: HashMap<Subnet, Region>,
+    by_wide_subnet: HashMap<Subnet, Region>,
+    rtt_stats: HashMap<Region, RttStats>,
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+struct Subnet(IpAddr, u8);
+
+#[derive(Default)]
+struct RttStats {
+    log_sum: f64,
+    count: usize,
+}
+
+impl RegionInference {
+    pub fn update_subnets(&mut self, servers: &[Server]) {
+        let mut by_subnet = HashMap::new();
+        let mut wide_votes: HashMap<Subnet, HashMap<Region, usize>> = HashMap::new();
+        for server in servers.iter().filter(|server| is_anchor(server)) {
+            by_subnet
+                .entry(Subnet::narrow(server.ip()))
+                .or_insert(server.region);
+            *wide_votes
+                .entry(Subnet::wide(server.ip()))
+                .or_default()
+                .entry(server.region)
+                .or_default() += 1;
+        }
+        self.by_wide_subnet = wide_votes
+            .into_iter()
+            .filter_map(|(subnet, votes)| {
+                let (region, _) = votes.into_iter().max_by_key(|&(_, count)| count)?;
+                Some((subnet, region))
+            })
+            .collect();
+        self.by_subnet = by_subnet;
+    }
+
+    pub fn record_ping(&mut self, server: &Server) {
+        if let (true, Some(ping)) = (is_anchor(server), server.ping) {
+            let stats = self.rtt_stats.entry(server.region).or_default();
+            stats.log_sum += log_ms(ping);
+            stats.count += 1;
+        }
+    }
+
+    // Returns the region the server is inferred to be in, if it differs from the declared one.
+    pub fn infer(&self, server: &Server) -> Option<Region> {
+        if server.ownership == Ownership::Official {
+            return None;
+        }
+        let inferred = self
+            .by_subnet
+            .get(&Subnet::narrow(server.ip()))
+            .or_else(|| self.by_wide_subnet.get(&Subnet::wide(server.ip())))
+            .copied()
+            .or_else(|| self.infer_from_rtt(server));
+        inferred.filter(|&region| region != server.region)
+    }
+
+    fn infer_from_rtt(&self, server: &Server) -> Option<Region> {
+        let ping = log_ms(server.ping?);
+        let declared = self.rtt_stats.get(&server.region)?.log_mean()?;
+        let declared_distance = (ping - declared).abs();
+        if declared_distance < RTT_MISMATCH_FACTOR.ln() {
+            return None;
+        }
+
+        let (region, distance) = Region::iter()
+            .filter_map(|region| {
+                let mean = self.rtt_stats.get(&region)?.log_mean()?;
+                Some((region, (ping - mean).abs()))
+            })
+            .min_by(|(_, lhs), (_, rhs)| lhs.total_cmp(rhs))?;
+        if distance < RTT_MATCH_FACTOR.ln() {
+            Some(region)
+        } else {
+            None
+        }
+    }
+}
+
+impl Subnet {
+    fn narrow(ip: &IpAddr) -> Self {
+        match ip {
+            IpAddr::V4(_) => Self::new(ip, 24),
+            IpAddr::V6(_) => Self::new(ip, 48),
+        }
+    }
+
+    fn wide(ip: &IpAddr) -> Self {
+        match ip {
+            IpAddr::V4(_) => Self::new(ip, 16),
+            IpAddr::V6(_) => Self::new(ip, 32),
+        }
+    }
+
+    fn new(ip: &IpAddr, prefix_len: u8) -> Self {
+        let masked = match ip {
+            IpAddr::V4(ip) => {
+                let mask = u32::MAX << (32 - prefix_len);
+                IpAddr::V4((u32::from(*ip) & mask).into())
+            }
+            IpAddr::V6(ip) => {
+                let mask = u128::MAX << (128 - prefix_len);
+                IpAddr::V6((u128::from(*ip) & mask).into())
+            }
+        };
+        Self(masked, prefix_len)
+    }
+}
+
+impl RttStats {
+    fn log_mean(&self) -> Option<f64> {
+        if self.count < MIN_RTT_SAMPLES {
+            None
+        } else {
+            Some(self.log_sum / self.count as f64)
+        }
+    }
+}
+
+fn is_anchor(server: &Server) -> bool {
+    server.ownership == Ownership::Official && server.is_valid()
+}
+
+fn log_ms(duration: Duration) -> f64 {
+    (duration.as_secs_f64() * 1000.0).max(1.0).ln()
+}
+
+const MIN_RTT_SAMPLES: usize = 5;
+const RTT_MISMATCH_FACTOR: f64 = 2.0;
+const RTT_MATCH_FACTOR: f64 = 1.5;
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use super::super::model::test_server;
+    use super::super::{Region, Server};
+    use super::{RegionInference, Subnet};
+
+    fn server(ip: &str, region: Region, official: bool) -> Server {
+        test_server(json!({
+            "ip": ip,
+            "Sy": region as u8,
+            "CSF": official as u8,
+        }))
+    }
+
+    fn pinged(mut server: Server, ping_ms: u64) -> Server {
+        server.ping = Some(Duration::from_millis(ping_ms));
+        server
+    }
+
+    fn subnet(ip: &str, prefix_len: u8) -> Subnet {
+        Subnet(ip.parse::<IpAddr>().unwrap(), prefix_len)
+    }
+
+    fn rtt_inference() -> RegionInference {
+        let mut inference = RegionInference::default();
+        for idx in 1..=5 {
+            inference.record_ping(&pinged(
+                server(&format!("1.1.{}.1", idx), Region::EU, true),
+                30,
+            ));
+            inference.record_ping(&pinged(
+                server(&format!("2.2.{}.1", idx), Region::America, true),
+                150,
+            ));
+        }
+        inference
+    }
+
+    #[test]
+    fn subnet_masking() {
+        let ip: IpAddr = "203.0.113.77".parse().unwrap();
+        assert_eq!(Subnet::narrow(&ip), subnet("203.0.113.0", 24));
+        assert_eq!(Subnet::wide(&ip), subnet("203.0.0.0", 16));
+
+        let ip: IpAddr = "2a01:4f8:1234:5678::1".parse().unwrap();
+        assert_eq!(Subnet::narrow(&ip), subnet("2a01:4f8:1234::", 48));
+        assert_eq!(Subnet::wide(&ip), subnet("2a01:4f8::", 32));
+    }
+
+    #[test]
+    fn infers_region_from_narrow_subnet() {
+        let mut inference = RegionInference::default();
+        inference.update_subnets(&[
+            server("203.0.113.10", Region::EU, true),
+            server("203.0.200.10", Region::Asia, true),
+        ]);
+
+        let private = server("203.0.113.200", Region::America, false);
+        assert_eq!(inference.infer(&private), Some(Region::EU));
+
+        let private = server("203.0.113.200", Region::EU, false);
+        assert_eq!(inference.infer(&private), None);
+
+        let official = server("203.0.113.20", Region::America, true);
+        assert_eq!(inference.infer(&official), None);
+    }
+
+    #[test]
+    fn infers_region_from_wide_subnet_votes() {
+        let mut inference = RegionInference::default();
+        inference.update_subnets(&[
+            server("198.51.1.10", Region::Asia, true),
+            server("198.51.2.10", Region::Asia, true),
+            server("198.51.3.10", Region::Oceania, true),
+            server("2a01:4f8:1::10", Region::EU, true),
+        ]);
+
+        let private = server("198.51.200.5", Region::America, false);
+        assert_eq!(inference.infer(&private), Some(Region::Asia));
+
+        let private = server("2a01:4f8:ffff::10", Region::America, false);
+        assert_eq!(inference.infer(&private), Some(Region::EU));
+
+        let private = server("192.0.2.10", Region::America, false);
+        assert_eq!(inference.infer(&private), None);
+    }
+
+    #[test]
+    fn infers_region_from_round_trips() {
+        let inference = rtt_inference();
+
+        // Way off for the declared region, close to another one.
+        let private = pinged(server("5.5.5.5", Region::EU, false), 140);
+        assert_eq!(inference.infer(&private), Some(Region::America));
+
+        // Within the mismatch factor of the declared region.
+        let private = pinged(server("5.5.5.5", Region::EU, false), 50);
+        assert_eq!(inference.infer(&private), None);
+
+        // Off for the declared region, but not close enough to any other.
+        let private = pinged(server("5.5.5.5", Region::EU, false), 400);
+        assert_eq!(inference.infer(&private), None);
+
+        // Not enough samples for the declared region.
+        let private = pinged(server("5.5.5.5", Region::Asia, false), 150);
+        assert_eq!(inference.infer(&private), None);
+    }
+
+    #[test]
+    fn round_trips_survive_subnet_updates() {
+        let mut inference = rtt_inference();
+        inference.update_subnets(&[server("203.0.113.10", Region::EU, true)]);
+
+        let private = pinged(server("5.5.5.5", Region::EU, false), 140);
+        assert_eq!(inference.infer(&private), Some(Region::America));
+    }
+
+    #[test]
+    fn too_few_round_trips() {
+        let mut inference = RegionInference::default();
+        for idx in 1..5 {
+            inference.record_ping(&pinged(
+                server(&format!("2.2.{}.1", idx), Region::America, true),
+                150,
+            ));
+        }
+        inference.record_ping(&pinged(server("1.1.1.1", Region::EU, true), 30));
+
+        let private = pinged(server("5.5.5.5", Region::EU, false), 140);
+        assert_eq!(inference.infer(&private), None);
+    }
+}
@@ -0,0 +1,230 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Result};
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+
+use crate::config::save_json;
+
+// Remembers the passwords of password-protected servers. The passwords are kept in a file next
+// to BUGLE's config, encrypted with a key derived from a master passphrase chosen by the user.
+// The vault has to be unlocked with the passphrase once per session, before the passwords can be
+// read or changed.
+pub struct PasswordVault {
+    path: Option<PathBuf>,
+    unlocked: Option<UnlockedVault>,
+}
+
+struct UnlockedVault {
+    salt: Vec<u8>,
+    key: Key,
+    entries: Vec<VaultEntry>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct VaultEntry {
+    id: Option<String>,
+    addr: SocketAddr,
+    password: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    salt: String,
+    nonce: String,
+    data: String,
+}
+
+impl PasswordVault {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self {
+            path,
+            unlocked: None,
+        }
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.path.is_some()
+    }
+
+    pub fn exists(&self) -> bool {
+        self.path
+            .as_ref()
+            .map(|path| path.exists())
+            .unwrap_or_default()
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.unlocked.is_some()
+    }
+
+    pub fn unlock(&mut self, passphrase: &str) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => bail!(ERR_NO_VAULT),
+        };
+        let file: VaultFile = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        let salt = hex::decode(&file.salt)?;
+        let nonce = hex::decode(&file.nonce)?;
+        if nonce.len() != NONCE_SIZE {
+            bail!(ERR_CORRUPTED);
+        }
+        let data = hex::decode(&file.data)?;
+
+        let key = derive_key(passphrase, &salt)?;
+        let plaintext = ChaCha20Poly1305::new(&key)
+            .decrypt(Nonce::from_slice(&nonce), data.as_slice())
+            .map_err(|_| anyhow!(ERR_WRONG_PASSPHRASE))?;
+        let entries = serde_json::from_slice(&plaintext)?;
+
+        self.unlocked = Some(UnlockedVault { salt, key, entries });
+        Ok(())
+    }
+
+    pub fn create(&mut self, passphrase: &str) -> Result<()> {
+        if self.path.is_none() {
+            bail!(ERR_NO_VAULT);
+        }
+        let salt: [u8; SALT_SIZE] = rand_bytes();
+        let key = derive_key(passphrase, &salt)?;
+        self.unlocked = Some(UnlockedVault {
+            salt: salt.to_vec(),
+            key,
+            entries: Vec::new(),
+        });
+        self.save()
+    }
+
+    pub fn password(&self, id: &str, addr: SocketAddr) -> Option<&str> {
+        let unlocked = self.unlocked.as_ref()?;
+        unlocked
+            .find(id, addr)
+            .map(|idx| unlocked.entries[idx].password.as_str())
+    }
+
+    pub fn remember(&mut self, id: &str, addr: SocketAddr, password: String) -> Result<()> {
+        let unlocked = match &mut self.unlocked {
+            Some(unlocked) => unlocked,
+            None => bail!(ERR_LOCKED),
+        };
+        let entry = VaultEntry {
+            id: Some(id.to_string()),
+            addr,
+            password,
+        };
+        match unlocked.find(id, addr) {
+            Some(idx) => unlocked.entries[idx] = entry,
+            None => unlocked.entries.push(entry),
+        }
+        self.save()
+    }
+
+    pub fn forget(&mut self, id: &str, addr: SocketAddr) -> Result<()> {
+        let unlocked = match &mut self.unlocked {
+            Some(unlocked) => unlocked,
+            None => bail!(ERR_LOCKED),
+        };
+        match unlocked.find(id, addr) {
+            Some(idx) => unlocked.entries.remove(idx),
+            None => return Ok(()),
+        };
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        let (path, unlocked) = match (&self.path, &self.unlocked) {
+            (Some(path), Some(unlocked)) => (path, unlocked),
+            _ => bail!(ERR_LOCKED),
+        };
+        let plaintext = serde_json::to_vec(&unlocked.entries)?;
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let data = ChaCha20Poly1305::new(&unlocked.key)
+            .encrypt(&nonce, plaintext.as_slice())
+            .map_err(|err| anyhow!("{}: {}", ERR_ENCRYPTING, err))?;
+        let file = VaultFile {
+            salt: hex::encode(&unlocked.salt),
+            nonce: hex::encode(nonce),
+            data: hex::encode(data),
+        };
+
+        save_json(&file, path)
+    }
+}
+
+impl UnlockedVault {
+    // Matches by server ID first, and falls back to the address, so that a remembered password
+    // survives the server getting a new ID after a wipe.
+    fn find(&self, id: &str, addr: SocketAddr) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.id.as_deref() == Some(id))
+            .or_else(|| self.entries.iter().position(|entry| entry.addr == addr))
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| anyhow!("{}: {}", ERR_DERIVING_KEY, err))?;
+    Ok(key)
+}
+
+fn rand_bytes<const N: usize>() -> [u8; N] {
+    use chacha20poly1305::aead::rand_core::RngCore;
+
+    let mut bytes = [0u8; N];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+const SALT_SIZE: usize = 16;
+const NONCE_SIZE: usize = 12;
+
+const ERR_NO_VAULT: &str = "Saved passwords are not available without a config file.";
+const ERR_LOCKED: &str = "The password vault is locked.";
+const ERR_WRONG_PASSPHRASE: &str = "Wrong passphrase, or the password vault is corrupted.";
+const ERR_CORRUPTED: &str = "The password vault is corrupted.";
+const ERR_ENCRYPTING: &str = "Error encrypting the password vault";
+const ERR_DERIVING_KEY: &str = "Error deriving the password vault key";
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use super::PasswordVault;
+
+    fn addr(port: u16) -> SocketAddr {
+        SocketAddr::new([203, 0, 113, 7].into(), port)
+    }
+
+    #[test]
+    fn round_trips_passwords_with_passphrase() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.json");
+
+        let mut vault = PasswordVault::new(Some(path.clone()));
+        vault.create("correct horse").unwrap();
+        vault
+            .remember("a", addr(7777), "hunter2".to_string())
+            .unwrap();
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("hunter2"));
+
+        let mut vault = PasswordVault::new(Some(path));
+        assert!(vault.unlock("wrong horse").is_err());
+        assert!(!vault.is_unlocked());
+
+        vault.unlock("correct horse").unwrap();
+        assert_eq!(vault.password("a", addr(1234)), Some("hunter2"));
+        assert_eq!(vault.password("b", addr(7777)), Some("hunter2"));
+        assert_eq!(vault.password("b", addr(1234)), None);
+
+        vault.forget("a", addr(7777)).unwrap();
+        assert_eq!(vault.password("a", addr(7777)), None);
+    }
+}
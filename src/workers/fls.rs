@@ -4,7 +4,7 @@ use fltk::app;
 use slog::Logger;
 
 use crate::auth::playfab;
-use crate::game::platform::steam::SteamTicket;
+use crate::game::platform::PlatformTicket;
 use crate::game::Game;
 use crate::Message;
 
@@ -19,7 +19,7 @@ impl FlsWorker {
         Arc::new(Self { logger, game, tx })
     }
 
-    pub fn login_with_steam(self: Arc<Self>, ticket: &SteamTicket) {
+    pub fn login_with_steam(self: Arc<Self>, ticket: &dyn PlatformTicket) {
         let ticket = ticket.data().into();
         tokio::spawn(async move {
             let account = playfab::login_with_steam(&self.logger, &*self.game, ticket).await;
@@ -1,18 +1,24 @@
+use std::net::SocketAddr;
 use std::sync::{Arc, Mutex};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use fltk::app;
 use slog::{o, Logger};
 use tokio::task::JoinHandle;
 
 use crate::game::Game;
 use crate::gui::{ServerBrowserUpdate, Update};
-use crate::servers::{fetch_server_list, DeserializationContext, PingClient, PingRequest, Server};
+use crate::net::{parse_host_name, parse_socket_addr, resolve_host};
+use crate::servers::{
+    fetch_server_list, query_server, DeserializationContext, FavoriteDetails, FavoriteServers,
+    PingClient, PingRequest, PingResult, Server,
+};
 use crate::Message;
 
 pub struct ServerLoaderWorker {
     logger: Logger,
     game: Arc<Game>,
+    favorite_details: Arc<Mutex<FavoriteDetails>>,
     tx: app::Sender<Message>,
     server_loader: Mutex<ServerLoader>,
 }
@@ -25,10 +31,16 @@ struct ServerLoader {
 }
 
 impl ServerLoaderWorker {
-    pub fn new(logger: Logger, game: Arc<Game>, tx: app::Sender<Message>) -> Arc<Self> {
+    pub fn new(
+        logger: Logger,
+        game: Arc<Game>,
+        favorite_details: Arc<Mutex<FavoriteDetails>>,
+        tx: app::Sender<Message>,
+    ) -> Arc<Self> {
         Arc::new(Self {
             logger,
             game,
+            favorite_details,
             tx,
             server_loader: Mutex::new(Default::default()),
         })
@@ -58,6 +70,16 @@ impl ServerLoaderWorker {
         self.with_ping_client(|client| client.priority_send(request))
     }
 
+    pub fn query_server(self: &Arc<Self>, target: String) {
+        let this = Arc::clone(self);
+        tokio::spawn(async move {
+            let result = this.query_server_impl(&target).await;
+            this.tx.send(Message::Update(Update::ServerBrowser(
+                ServerBrowserUpdate::ServerQueried { target, result },
+            )));
+        });
+    }
+
     fn spawn_fetcher(self: Arc<Self>, generation: u32) -> JoinHandle<()> {
         tokio::spawn(async move {
             let servers = self.fetch_servers().await;
@@ -86,7 +108,7 @@ impl ServerLoaderWorker {
         let ping_logger = self.logger.new(o!("ping_generation" => generation));
         Ok(PingClient::new(
             ping_logger,
-            self.game.build_id(),
+            self.game.build_id().unwrap_or_default(),
             move |response| {
                 if self.server_loader.lock().unwrap().generation != generation {
                     return;
@@ -98,8 +120,33 @@ impl ServerLoaderWorker {
         )?)
     }
 
+    async fn query_server_impl(&self, target: &str) -> Result<(SocketAddr, PingResult)> {
+        let addr = match parse_socket_addr(target) {
+            Some(addr) => addr,
+            None => {
+                let (host_name, port) =
+                    parse_host_name(target).ok_or_else(|| anyhow!(ERR_INVALID_ADDR))?;
+                resolve_host(&host_name, port).await?[0]
+            }
+        };
+        let ping_port = addr
+            .port()
+            .checked_add(1)
+            .ok_or_else(|| anyhow!(ERR_INVALID_ADDR))?;
+        let result = query_server(
+            self.logger.clone(),
+            self.game.build_id().unwrap_or_default(),
+            SocketAddr::new(addr.ip(), ping_port),
+        )
+        .await?;
+        Ok((addr, result))
+    }
+
     async fn fetch_servers(&self) -> Result<Vec<Server>> {
-        let favorites = self.game.load_favorites()?;
+        let mut favorites = self.game.load_favorite_list()?;
+        self.favorite_details.lock().unwrap().apply(&mut favorites);
+        let mut favorites: FavoriteServers = favorites.into_iter().collect();
+        favorites.resolve_host_names(&self.logger).await;
         Ok(fetch_server_list(
             self.logger.clone(),
             &*self.game,
@@ -111,3 +158,5 @@ impl ServerLoaderWorker {
         .await?)
     }
 }
+
+const ERR_INVALID_ADDR: &str = "Invalid server address.";